@@ -0,0 +1,45 @@
+//! One criterion group per algorithm, each sweeping the expert
+//! counts and reward shapes in `shapes`, so a change that speeds up
+//! one size can be checked against all the others.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use little_sorry::{
+    DiscountedRegretMatcher, LazyDcfrRegretMatcher, RegretMatcher, RegretMinimizer,
+    VanillaCfrRegretMatcher,
+};
+
+mod shapes;
+
+use shapes::{RewardShape, NUM_EXPERTS};
+
+fn bench_algorithm<M: RegretMinimizer>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    for num_experts in NUM_EXPERTS {
+        for shape in RewardShape::ALL {
+            let rounds = shape.rounds(num_experts);
+            group.bench_with_input(
+                BenchmarkId::new(shape.name(), num_experts),
+                &rounds,
+                |b, rounds| {
+                    let mut m = M::new(num_experts).unwrap();
+                    let mut round = 0;
+                    b.iter(|| {
+                        m.update_regret(&rounds[round % rounds.len()]).unwrap();
+                        round += 1;
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+pub fn bench(c: &mut Criterion) {
+    bench_algorithm::<RegretMatcher>(c, "regret_matching");
+    bench_algorithm::<VanillaCfrRegretMatcher>(c, "vanilla_cfr");
+    bench_algorithm::<DiscountedRegretMatcher>(c, "dcfr");
+    bench_algorithm::<LazyDcfrRegretMatcher>(c, "lazy_dcfr");
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! The reward vectors the hot path benchmarks feed in. Rounds are
+//! generated up front and cycled through so building them isn't
+//! part of what's measured.
+use little_sorry::Scalar;
+
+/// Expert counts from a tiny matrix game, through one action per
+/// card, up to wide enough that the `blas` feature uses BLAS. Run
+/// with and without it to find the crossover.
+pub const NUM_EXPERTS: [usize; 5] = [3, 16, 52, 256, 4096];
+
+const ROUNDS: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+pub enum RewardShape {
+    /// Every action gets a reward.
+    Dense,
+    /// About one action in ten gets a non zero reward.
+    Sparse,
+    /// Only a single action is rewarded each round.
+    OneHot,
+}
+
+impl RewardShape {
+    pub const ALL: [RewardShape; 3] = [Self::Dense, Self::Sparse, Self::OneHot];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dense => "dense",
+            Self::Sparse => "sparse",
+            Self::OneHot => "one_hot",
+        }
+    }
+
+    pub fn rounds(self, num_experts: usize) -> Vec<Vec<Scalar>> {
+        (0..ROUNDS)
+            .map(|round| {
+                (0..num_experts)
+                    .map(|a| {
+                        let v = ((a * 7 + round * 3) % 11) as Scalar - 5.0;
+                        match self {
+                            Self::Dense => v,
+                            Self::Sparse if (a + round) % 10 == 0 => v,
+                            Self::OneHot if a == round % num_experts => 1.0,
+                            _ => 0.0,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
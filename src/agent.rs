@@ -0,0 +1,112 @@
+//! Anything that picks actions and can be told how they'd have
+//! done. Every `RegretMinimizer` is an agent, and so is anything
+//! else that implements the two methods, like a scripted bot, a
+//! person at a prompt or a learned policy, so the loops that play
+//! games don't have to care how decisions get made.
+use rand::RngCore;
+
+use std::vec::Vec;
+
+use crate::dist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer};
+use crate::scalar::Scalar;
+
+pub trait Agent {
+    fn num_actions(&self) -> usize;
+
+    /// Pick the next action to play.
+    fn act(&mut self, rng: &mut dyn RngCore) -> usize;
+
+    /// Hear the reward every action would have gotten in the round
+    /// just played.
+    fn observe(&mut self, rewards: &[Scalar]) -> Result<(), LittleError>;
+}
+
+impl<M: RegretMinimizer> Agent for M {
+    fn num_actions(&self) -> usize {
+        self.num_experts()
+    }
+
+    fn act(&mut self, mut rng: &mut dyn RngCore) -> usize {
+        self.next_action(&mut rng)
+    }
+
+    fn observe(&mut self, rewards: &[Scalar]) -> Result<(), LittleError> {
+        self.update_regret(rewards)
+    }
+}
+
+/// An agent that always plays the same mixed strategy and ignores
+/// what it observes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedAgent {
+    strategy: Vec<Scalar>,
+}
+
+impl FixedAgent {
+    /// Play `strategy`, normalized to sum to one.
+    pub fn new(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Ok(Self {
+            strategy: regret_minimizer::normalize_strategy(strategy)?,
+        })
+    }
+
+    /// Always play `action` out of `num_actions`.
+    pub fn pure(action: usize, num_actions: usize) -> Result<Self, LittleError> {
+        if action >= num_actions {
+            return Err(LittleError::ActionOutOfRange {
+                action,
+                num_experts: num_actions,
+            });
+        }
+        let mut strategy = vec![0.0; num_actions];
+        strategy[action] = 1.0;
+        Ok(Self { strategy })
+    }
+
+    pub fn strategy(&self) -> &[Scalar] {
+        &self.strategy
+    }
+}
+
+impl Agent for FixedAgent {
+    fn num_actions(&self) -> usize {
+        self.strategy.len()
+    }
+
+    fn act(&mut self, rng: &mut dyn RngCore) -> usize {
+        dist::sample_weights(&self.strategy, rng)
+    }
+
+    fn observe(&mut self, _rewards: &[Scalar]) -> Result<(), LittleError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_agents_share_a_loop() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(RegretMatcher::new(3).unwrap()),
+            Box::new(FixedAgent::pure(2, 3).unwrap()),
+        ];
+        // Paper always wins.
+        for _i in 0..100 {
+            for agent in &mut agents {
+                agent.act(&mut rng);
+                agent.observe(&[0.0, 1.0, 0.0]).unwrap();
+            }
+        }
+        assert_eq!(agents[0].act(&mut rng), 1);
+        assert_eq!(agents[1].act(&mut rng), 2);
+        assert!(FixedAgent::new(&[0.0, 0.0]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+#![allow(clippy::cast_precision_loss)]
+//! Head to head matches between agents in a two player normal form
+//! game. Every pair of agents plays a match in each seat, with both
+//! sides observing full feedback as they go, and the results
+//! are collected into matrices of win rates and average payoffs.
+//! Unlike exploitability this shows how algorithms fare against
+//! each other rather than against a worst case opponent.
+use ndarray::prelude::*;
+use rand::{thread_rng, Rng, RngCore};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Agent;
+use crate::errors::LittleError;
+use crate::evaluation::SampleStats;
+use crate::games::NormalFormGame;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArenaReport {
+    /// `win_rates[[i, j]]` is the fraction of rounds agent `i` got
+    /// more than agent `j`, with ties counting half.
+    pub win_rates: Array2<Scalar>,
+    /// `payoffs[[i, j]]` is agent `i`'s average reward per round
+    /// against agent `j`.
+    pub payoffs: Array2<Scalar>,
+    /// The standard error of each entry of `payoffs`.
+    pub standard_errors: Array2<Scalar>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Arena {
+    game: NormalFormGame,
+    // Rounds played in each seat by every pair.
+    rounds: usize,
+}
+
+impl Arena {
+    /// Pair agents up for `rounds` rounds of `game` in each seat.
+    /// Every agent sits in both seats, so the game must give both
+    /// players the same number of actions.
+    pub fn new(game: NormalFormGame, rounds: usize) -> Result<Self, LittleError> {
+        if game.num_actions_one() != game.num_actions_two() {
+            return Err(LittleError::InvalidParameter(
+                "both players must have the same number of actions",
+            ));
+        }
+        if rounds == 0 {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one round",
+            ));
+        }
+        Ok(Self { game, rounds })
+    }
+
+    pub fn game(&self) -> &NormalFormGame {
+        &self.game
+    }
+
+    pub fn run(&self, agents: &mut [Box<dyn Agent>]) -> Result<ArenaReport, LittleError> {
+        self.run_with_rng(agents, &mut thread_rng())
+    }
+
+    /// Play every pair of `agents` against each other. Agents keep
+    /// what they learn from one match to the next. Nobody plays
+    /// themselves, so the diagonals are left at zero.
+    pub fn run_with_rng<R: Rng>(
+        &self,
+        agents: &mut [Box<dyn Agent>],
+        rng: &mut R,
+    ) -> Result<ArenaReport, LittleError> {
+        if agents
+            .iter()
+            .any(|a| a.num_actions() != self.game.num_actions_one())
+        {
+            return Err(LittleError::InvalidParameter(
+                "agents must have the game's number of actions",
+            ));
+        }
+        let n = agents.len();
+        let mut win_rates = Array2::zeros((n, n));
+        let mut rewards = Array2::from_elem((n, n), SampleStats::new());
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                let (left, right) = agents.split_at_mut(i.max(j));
+                let (one, two) = if i < j {
+                    (&mut left[i], &mut right[0])
+                } else {
+                    (&mut right[0], &mut left[j])
+                };
+                let (score, rewards_one, rewards_two) =
+                    self.play(one.as_mut(), two.as_mut(), rng)?;
+                let total = 2.0 * self.rounds as Scalar;
+                win_rates[[i, j]] += score / total;
+                win_rates[[j, i]] += (self.rounds as Scalar - score) / total;
+                rewards[[i, j]].merge(&rewards_one);
+                rewards[[j, i]].merge(&rewards_two);
+            }
+        }
+        Ok(ArenaReport {
+            win_rates,
+            payoffs: rewards.mapv(|s| s.mean()),
+            standard_errors: rewards.mapv(|s| s.standard_error()),
+        })
+    }
+
+    // One match with `one` in the first seat. Returns the first
+    // seat's score, counting ties as half a win, and each seat's
+    // rewards.
+    fn play<R: Rng>(
+        &self,
+        one: &mut dyn Agent,
+        two: &mut dyn Agent,
+        rng: &mut R,
+    ) -> Result<(Scalar, SampleStats, SampleStats), LittleError> {
+        let rng: &mut dyn RngCore = rng;
+        let mut score = 0.0;
+        let (mut stats_one, mut stats_two) = (SampleStats::new(), SampleStats::new());
+        for _i in 0..self.rounds {
+            let a = one.act(rng);
+            let b = two.act(rng);
+            let rewards_one = self.game.rewards_one(b);
+            let rewards_two = self.game.rewards_two(a);
+            let (r1, r2) = (rewards_one[a], rewards_two[b]);
+            score += if r1 > r2 {
+                1.0
+            } else if r1 == r2 {
+                0.5
+            } else {
+                0.0
+            };
+            stats_one.push(r1);
+            stats_two.push(r2);
+            one.observe(&rewards_one.to_vec())?;
+            two.observe(&rewards_two.to_vec())?;
+        }
+        Ok((score, stats_one, stats_two))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::FixedAgent;
+    use crate::regret_matcher::RegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_learner_beats_rock() {
+        let rock = FixedAgent::pure(0, 3).unwrap();
+        let mut agents: Vec<Box<dyn Agent>> =
+            vec![Box::new(RegretMatcher::new(3).unwrap()), Box::new(rock)];
+        let arena = Arena::new(NormalFormGame::rock_paper_scissors(), 500).unwrap();
+        let report = arena
+            .run_with_rng(&mut agents, &mut StdRng::seed_from_u64(7))
+            .unwrap();
+        assert!(report.win_rates[[0, 1]] > 0.9, "{:?}", report.win_rates);
+        assert!((report.win_rates[[0, 1]] + report.win_rates[[1, 0]] - 1.0).abs() < 1e-5);
+        assert!(report.payoffs[[0, 1]] > 0.8);
+        assert!((report.payoffs[[0, 1]] + report.payoffs[[1, 0]]).abs() < 1e-5);
+        assert_eq!(report.payoffs[[0, 0]], 0.0);
+        assert!(report.standard_errors[[0, 1]] < 0.05);
+    }
+}
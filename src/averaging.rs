@@ -0,0 +1,172 @@
+#![allow(clippy::cast_precision_loss)]
+//! How the strategies played on each iteration are combined into
+//! the average strategy. Regret matching guarantees convergence of
+//! the uniform average but in practice weighting later iterations
+//! more heavily usually gets there much faster.
+//!
+//! Every scheme can be written as
+//! `sum = sum * decay(t) + weight(t) * p`
+//! and the average is `sum` normalized to add up to one.
+use ndarray::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::compensated::{Compensated, CompensatedArray, CompensatedSliceMut};
+use crate::errors::LittleError;
+use crate::regret_minimizer;
+use crate::scalar::{Accumulator, Scalar};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AveragingScheme {
+    /// Every iteration counts the same.
+    #[default]
+    Uniform,
+    /// Iteration `t` is weighted by `t`.
+    Linear,
+    /// Iteration `t` is weighted by `t^2`.
+    Quadratic,
+    /// The sum is multiplied by `(t / (t + 1))^gamma` after every
+    /// iteration, as in DCFR.
+    Discounted(Scalar),
+    /// Only the most recent strategy counts.
+    LastIterate,
+}
+
+impl AveragingScheme {
+    /// How much the strategy from iteration `t` (starting at 1)
+    /// is weighted when it's added.
+    #[must_use]
+    pub fn weight(&self, t: usize) -> Scalar {
+        match self {
+            Self::Uniform | Self::LastIterate => 1.0,
+            Self::Linear => t as Scalar,
+            Self::Quadratic => (t as Scalar) * (t as Scalar),
+            Self::Discounted(_) => self.decay(t),
+        }
+    }
+
+    /// What the existing sum is multiplied by on iteration `t`.
+    #[must_use]
+    pub fn decay(&self, t: usize) -> Scalar {
+        match self {
+            Self::Uniform | Self::Linear | Self::Quadratic => 1.0,
+            Self::Discounted(gamma) => {
+                let t = t as Scalar;
+                (t / (t + 1.0)).powf(*gamma)
+            }
+            Self::LastIterate => 0.0,
+        }
+    }
+
+    /// Add the strategy `p` played on iteration `t` to `sum`.
+    pub fn accumulate(
+        &self,
+        t: usize,
+        sum: &mut CompensatedArray<Accumulator>,
+        p: ArrayView1<Scalar>,
+    ) {
+        self.accumulate_weighted(t, sum, p, 1.0);
+    }
+
+    /// Like `accumulate` with `p` also scaled by `extra`, such as
+    /// the probability of reaching the decision at all.
+    pub fn accumulate_weighted(
+        &self,
+        t: usize,
+        sum: &mut CompensatedArray<Accumulator>,
+        p: ArrayView1<Scalar>,
+        extra: Scalar,
+    ) {
+        self.accumulate_into(t, sum.view_mut(), p, extra);
+    }
+
+    /// `accumulate_weighted` into sums borrowed from a larger
+    /// array, such as one matcher's share of a bank.
+    pub fn accumulate_into(
+        &self,
+        t: usize,
+        mut sum: CompensatedSliceMut<Accumulator>,
+        p: ArrayView1<Scalar>,
+        extra: Scalar,
+    ) {
+        let decay = self.decay(t) as Accumulator;
+        if decay == 0.0 {
+            sum.fill(0.0);
+        } else if decay != 1.0 {
+            sum.scale(decay);
+        }
+        let weight = (self.weight(t) * extra) as Accumulator;
+        sum.scaled_add(weight, p);
+    }
+
+    /// `accumulate_weighted` for a strategy passed in by the
+    /// caller, which must have one entry per entry of `sum`. This is
+    /// how matchers implement `RegretMinimizer::accumulate_average`.
+    pub fn accumulate_strategy(
+        &self,
+        t: usize,
+        sum: &mut CompensatedArray<Accumulator>,
+        strategy: &[Scalar],
+        extra: Scalar,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_strategy_len(strategy, sum.len())?;
+        self.accumulate_weighted(t, sum, ArrayView1::from(strategy), extra);
+        Ok(())
+    }
+
+    /// For an average kept as a running mean rather than a sum,
+    /// fold iteration `t` into the `total` weight and return the
+    /// fraction of the way the mean should move towards the
+    /// strategy played, or `None` if nothing has any weight yet.
+    pub fn mean_step(
+        &self,
+        t: usize,
+        total: &mut Compensated<Accumulator>,
+        extra: Scalar,
+    ) -> Option<Accumulator> {
+        let weight = (self.weight(t) * extra) as Accumulator;
+        total.scale(self.decay(t) as Accumulator);
+        total.add(weight);
+        if total.value() <= 0.0 {
+            return None;
+        }
+        Some(weight / total.value())
+    }
+
+    /// Normalize a sum built by `accumulate` into the average
+    /// strategy. Returns `None` if nothing has been added yet.
+    #[must_use]
+    pub fn average(sum: ArrayView1<Accumulator>) -> Option<Vec<Scalar>> {
+        let total = sum.sum();
+        if total <= 0.0 {
+            return None;
+        }
+        Some(sum.iter().map(|s| (s / total) as Scalar).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn average_of(scheme: AveragingScheme) -> Vec<Scalar> {
+        let mut sum = CompensatedArray::zeros(2);
+        scheme.accumulate(1, &mut sum, array![1.0, 0.0].view());
+        scheme.accumulate(2, &mut sum, array![0.0, 1.0].view());
+        scheme.accumulate(3, &mut sum, array![0.0, 1.0].view());
+        AveragingScheme::average(sum.values()).unwrap()
+    }
+
+    #[test]
+    fn test_schemes() {
+        let uniform = average_of(AveragingScheme::Uniform);
+        assert!((uniform[0] - 1.0 / 3.0).abs() < 1e-6);
+        let linear = average_of(AveragingScheme::Linear);
+        assert!((linear[0] - 1.0 / 6.0).abs() < 1e-6);
+        let quadratic = average_of(AveragingScheme::Quadratic);
+        assert!((quadratic[0] - 1.0 / 14.0).abs() < 1e-6);
+        assert_eq!(average_of(AveragingScheme::LastIterate), vec![0.0, 1.0]);
+    }
+}
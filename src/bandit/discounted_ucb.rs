@@ -0,0 +1,153 @@
+#![allow(clippy::cast_precision_loss)]
+//! Discounted UCB. Like UCB1 but every past observation is
+//! multiplied by `discount` each round, so old rewards fade
+//! away and the policy can follow an opponent that drifts.
+use ndarray::prelude::*;
+use rand::Rng;
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+const DEFAULT_EXPLORATION: Scalar = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct DiscountedUcbPolicy {
+    // Multiplier applied to all past observations every update.
+    discount: Scalar,
+    // Scales the confidence bonus.
+    exploration: Scalar,
+    // Discounted number of times each action was played.
+    counts: Array1<Scalar>,
+    // Discounted total reward earned by each action.
+    reward_sums: Array1<Scalar>,
+    // Undiscounted play counts, used for the average strategy.
+    plays: Array1<Scalar>,
+    num_updates: usize,
+}
+
+impl DiscountedUcbPolicy {
+    /// Create a policy with `discount` in `(0, 1]`. A discount
+    /// of 1.0 is plain UCB1.
+    pub fn new(num_experts: usize, discount: Scalar) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, discount, DEFAULT_EXPLORATION)
+    }
+
+    pub fn new_with_params(
+        num_experts: usize,
+        discount: Scalar,
+        exploration: Scalar,
+    ) -> Result<Self, LittleError> {
+        if !(discount > 0.0 && discount <= 1.0) {
+            return Err(LittleError::InvalidParameter("discount must be in (0, 1]"));
+        }
+        if !(exploration >= 0.0 && exploration.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "exploration must not be negative",
+            ));
+        }
+        Ok(Self {
+            discount,
+            exploration,
+            counts: Array1::zeros(num_experts),
+            reward_sums: Array1::zeros(num_experts),
+            plays: Array1::zeros(num_experts),
+            num_updates: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn discount(&self) -> Scalar {
+        self.discount
+    }
+
+    /// The upper confidence bound of every action.
+    #[must_use]
+    pub fn upper_bounds(&self) -> Vec<Scalar> {
+        // The effective number of observations still remembered.
+        let total = self.counts.sum();
+        let log_t = total.max(1.0).ln();
+        self.counts
+            .iter()
+            .zip(self.reward_sums.iter())
+            .map(|(n, s)| {
+                if *n <= 0.0 {
+                    Scalar::INFINITY
+                } else {
+                    s / n + self.exploration * (2.0 * log_t / n).sqrt()
+                }
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn next_action(&self) -> usize {
+        regret_minimizer::argmax(&self.upper_bounds())
+    }
+
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.counts.len())?;
+        self.counts *= self.discount;
+        self.reward_sums *= self.discount;
+        self.counts[action] += 1.0;
+        self.reward_sums[action] += reward;
+        self.plays[action] += 1.0;
+        self.num_updates += 1;
+        Ok(())
+    }
+
+    /// How often each action has been played.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return regret_minimizer::uniform(self.counts.len());
+        }
+        (&self.plays / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for DiscountedUcbPolicy {
+    fn num_experts(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn next_action<R: Rng>(&self, _rng: &mut R) -> usize {
+        Self::next_action(self)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_discount() {
+        assert!(DiscountedUcbPolicy::new(3, 0.0).is_err());
+        assert!(DiscountedUcbPolicy::new(3, 1.1).is_err());
+    }
+
+    #[test]
+    fn test_tracks_switch() {
+        let mut policy = DiscountedUcbPolicy::new(2, 0.95).unwrap();
+        for i in 0..2_000 {
+            // Action zero is best for the first half and
+            // action one for the second.
+            let best = usize::from(i >= 1_000);
+            let a = policy.next_action();
+            policy
+                .update_sampled(a, if a == best { 1.0 } else { 0.0 })
+                .unwrap();
+        }
+        assert_eq!(policy.next_action(), 1);
+    }
+}
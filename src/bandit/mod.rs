@@ -0,0 +1,12 @@
+//! Index and sampling policies for the stochastic bandit setting.
+//! These share the `BanditMinimizer` interface with EXP3 so they
+//! can be swapped in wherever a bandit regret minimizer is used.
+pub mod discounted_ucb;
+pub mod sliding_window_ucb;
+pub mod thompson;
+pub mod ucb1;
+
+pub use self::discounted_ucb::DiscountedUcbPolicy;
+pub use self::sliding_window_ucb::SlidingWindowUcbPolicy;
+pub use self::thompson::{ThompsonPrior, ThompsonSampling};
+pub use self::ucb1::Ucb1Policy;
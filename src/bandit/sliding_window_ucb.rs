@@ -0,0 +1,146 @@
+#![allow(clippy::cast_precision_loss)]
+//! Sliding window UCB. Only the most recent `window` plays are
+//! used to estimate the mean rewards, so anything older than
+//! that is forgotten completely.
+use ndarray::prelude::*;
+use rand::Rng;
+
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+const DEFAULT_EXPLORATION: Scalar = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct SlidingWindowUcbPolicy {
+    window: usize,
+    // Scales the confidence bonus.
+    exploration: Scalar,
+    // The (action, reward) pairs still inside the window.
+    history: VecDeque<(usize, Scalar)>,
+    // Counts and reward totals over the window.
+    counts: Array1<Scalar>,
+    reward_sums: Array1<Scalar>,
+    // Play counts over all time, used for the average strategy.
+    plays: Array1<Scalar>,
+    num_updates: usize,
+}
+
+impl SlidingWindowUcbPolicy {
+    pub fn new(num_experts: usize, window: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, window, DEFAULT_EXPLORATION)
+    }
+
+    pub fn new_with_params(
+        num_experts: usize,
+        window: usize,
+        exploration: Scalar,
+    ) -> Result<Self, LittleError> {
+        if window == 0 {
+            return Err(LittleError::InvalidParameter("window must be positive"));
+        }
+        if !(exploration >= 0.0 && exploration.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "exploration must not be negative",
+            ));
+        }
+        Ok(Self {
+            window,
+            exploration,
+            history: VecDeque::with_capacity(window),
+            counts: Array1::zeros(num_experts),
+            reward_sums: Array1::zeros(num_experts),
+            plays: Array1::zeros(num_experts),
+            num_updates: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The upper confidence bound of every action.
+    #[must_use]
+    pub fn upper_bounds(&self) -> Vec<Scalar> {
+        let log_t = (self.history.len().max(1) as Scalar).ln();
+        self.counts
+            .iter()
+            .zip(self.reward_sums.iter())
+            .map(|(n, s)| {
+                if *n == 0.0 {
+                    Scalar::INFINITY
+                } else {
+                    s / n + self.exploration * (2.0 * log_t / n).sqrt()
+                }
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn next_action(&self) -> usize {
+        regret_minimizer::argmax(&self.upper_bounds())
+    }
+
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.counts.len())?;
+        if self.history.len() == self.window {
+            if let Some((old_action, old_reward)) = self.history.pop_front() {
+                self.counts[old_action] -= 1.0;
+                self.reward_sums[old_action] -= old_reward;
+            }
+        }
+        self.history.push_back((action, reward));
+        self.counts[action] += 1.0;
+        self.reward_sums[action] += reward;
+        self.plays[action] += 1.0;
+        self.num_updates += 1;
+        Ok(())
+    }
+
+    /// How often each action has been played.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return regret_minimizer::uniform(self.counts.len());
+        }
+        (&self.plays / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for SlidingWindowUcbPolicy {
+    fn num_experts(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn next_action<R: Rng>(&self, _rng: &mut R) -> usize {
+        Self::next_action(self)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forgets_outside_window() {
+        let mut policy = SlidingWindowUcbPolicy::new(2, 3).unwrap();
+        policy.update_sampled(0, 1.0).unwrap();
+        for _i in 0..3 {
+            policy.update_sampled(1, 0.0).unwrap();
+        }
+        // The only play of action zero has left the window.
+        assert_eq!(policy.upper_bounds()[0], Scalar::INFINITY);
+    }
+}
@@ -0,0 +1,206 @@
+#![allow(clippy::cast_precision_loss)]
+//! Thompson sampling. Keep a posterior over each action's mean
+//! reward, draw one sample from every posterior and play the
+//! action whose sample is largest.
+use ndarray::prelude::*;
+use rand::distributions::Distribution;
+use rand::{thread_rng, Rng};
+use rand_distr::{Beta, Normal};
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+/// The reward model and its conjugate prior.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThompsonPrior {
+    /// Rewards in `[0, 1]` with a `Beta(alpha, beta)` prior.
+    /// Fractional rewards count as fractional successes.
+    BetaBernoulli { alpha: Scalar, beta: Scalar },
+    /// Gaussian rewards with known `noise_variance` and a
+    /// normal prior on the mean.
+    Gaussian {
+        prior_mean: Scalar,
+        prior_variance: Scalar,
+        noise_variance: Scalar,
+    },
+}
+
+impl ThompsonPrior {
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::BetaBernoulli { alpha, beta } => *alpha > 0.0 && *beta > 0.0,
+            Self::Gaussian {
+                prior_mean,
+                prior_variance,
+                noise_variance,
+            } => prior_mean.is_finite() && *prior_variance > 0.0 && *noise_variance > 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ThompsonSampling {
+    prior: ThompsonPrior,
+    // Number of times each action was played.
+    counts: Array1<Scalar>,
+    // Total reward earned by each action.
+    reward_sums: Array1<Scalar>,
+    num_updates: usize,
+}
+
+impl ThompsonSampling {
+    pub fn new(num_experts: usize, prior: ThompsonPrior) -> Result<Self, LittleError> {
+        if !prior.is_valid() {
+            return Err(LittleError::InvalidParameter(
+                "prior parameters must be positive",
+            ));
+        }
+        Ok(Self {
+            prior,
+            counts: Array1::zeros(num_experts),
+            reward_sums: Array1::zeros(num_experts),
+            num_updates: 0,
+        })
+    }
+
+    /// Bernoulli rewards with a uniform `Beta(1, 1)` prior.
+    #[must_use]
+    pub fn beta_bernoulli(num_experts: usize) -> Self {
+        Self::new(
+            num_experts,
+            ThompsonPrior::BetaBernoulli {
+                alpha: 1.0,
+                beta: 1.0,
+            },
+        )
+        .unwrap()
+    }
+
+    /// Gaussian rewards with unit noise and a standard normal prior.
+    #[must_use]
+    pub fn gaussian(num_experts: usize) -> Self {
+        Self::new(
+            num_experts,
+            ThompsonPrior::Gaussian {
+                prior_mean: 0.0,
+                prior_variance: 1.0,
+                noise_variance: 1.0,
+            },
+        )
+        .unwrap()
+    }
+
+    // Draw one sample of the mean reward of `action`.
+    fn sample_mean<R: Rng>(&self, action: usize, rng: &mut R) -> Scalar {
+        let n = self.counts[action];
+        let s = self.reward_sums[action];
+        match self.prior {
+            ThompsonPrior::BetaBernoulli { alpha, beta } => {
+                // Clamp so out of range rewards can't make
+                // the posterior parameters invalid.
+                let successes = s.clamp(0.0, n);
+                Beta::new(alpha + successes, beta + n - successes).map_or(0.5, |d| d.sample(rng))
+            }
+            ThompsonPrior::Gaussian {
+                prior_mean,
+                prior_variance,
+                noise_variance,
+            } => {
+                let precision = 1.0 / prior_variance + n / noise_variance;
+                let mean = (prior_mean / prior_variance + s / noise_variance) / precision;
+                Normal::new(mean, (1.0 / precision).sqrt()).map_or(mean, |d| d.sample(rng))
+            }
+        }
+    }
+
+    pub fn next_action(&self) -> usize {
+        BanditMinimizer::next_action(self, &mut thread_rng())
+    }
+
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.counts.len())?;
+        self.counts[action] += 1.0;
+        self.reward_sums[action] += reward;
+        self.num_updates += 1;
+        Ok(())
+    }
+
+    /// How often each action has been played.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return regret_minimizer::uniform(self.counts.len());
+        }
+        (&self.counts / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for ThompsonSampling {
+    fn num_experts(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        let samples: Vec<Scalar> = (0..self.counts.len())
+            .map(|a| self.sample_mean(a, rng))
+            .collect();
+        regret_minimizer::argmax(&samples)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_bad_prior() {
+        let prior = ThompsonPrior::BetaBernoulli {
+            alpha: 0.0,
+            beta: 1.0,
+        };
+        assert!(ThompsonSampling::new(3, prior).is_err());
+    }
+
+    #[test]
+    fn test_beta_finds_best_arm() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut policy = ThompsonSampling::beta_bernoulli(3);
+        let means = [0.2, 0.3, 0.7];
+        for _i in 0..2_000 {
+            let a = BanditMinimizer::next_action(&policy, &mut rng);
+            let reward = if rng.gen::<Scalar>() < means[a] {
+                1.0
+            } else {
+                0.0
+            };
+            policy.update_sampled(a, reward).unwrap();
+        }
+        assert_eq!(regret_minimizer::argmax(&policy.best_weight()), 2);
+    }
+
+    #[test]
+    fn test_gaussian_finds_best_arm() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut policy = ThompsonSampling::gaussian(3);
+        let means = [1.0, -1.0, 0.0];
+        for _i in 0..2_000 {
+            let a = BanditMinimizer::next_action(&policy, &mut rng);
+            let noise: Scalar = Normal::new(0.0, 1.0).unwrap().sample(&mut rng);
+            policy.update_sampled(a, means[a] + noise).unwrap();
+        }
+        assert_eq!(regret_minimizer::argmax(&policy.best_weight()), 0);
+    }
+}
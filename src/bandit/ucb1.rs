@@ -0,0 +1,152 @@
+#![allow(clippy::cast_precision_loss)]
+//! UCB1. Play the action with the highest upper confidence
+//! bound on its mean reward. Unlike the regret matchers this
+//! is deterministic given the history.
+use ndarray::prelude::*;
+use rand::Rng;
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+const DEFAULT_EXPLORATION: Scalar = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct Ucb1Policy {
+    // Scales the confidence bonus. 1.0 is the textbook UCB1.
+    exploration: Scalar,
+    // Number of times each action was played.
+    counts: Array1<Scalar>,
+    // Total reward earned by each action.
+    reward_sums: Array1<Scalar>,
+    num_updates: usize,
+}
+
+impl Ucb1Policy {
+    #[must_use]
+    pub fn new(num_experts: usize) -> Self {
+        Self {
+            exploration: DEFAULT_EXPLORATION,
+            counts: Array1::zeros(num_experts),
+            reward_sums: Array1::zeros(num_experts),
+            num_updates: 0,
+        }
+    }
+
+    pub fn new_with_params(num_experts: usize, exploration: Scalar) -> Result<Self, LittleError> {
+        if !(exploration >= 0.0 && exploration.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "exploration must not be negative",
+            ));
+        }
+        let mut policy = Self::new(num_experts);
+        policy.exploration = exploration;
+        Ok(policy)
+    }
+
+    /// The upper confidence bound of every action. Actions that
+    /// have never been played have an infinite bound.
+    #[must_use]
+    pub fn upper_bounds(&self) -> Vec<Scalar> {
+        let log_t = (self.num_updates.max(1) as Scalar).ln();
+        self.counts
+            .iter()
+            .zip(self.reward_sums.iter())
+            .map(|(n, s)| {
+                if *n == 0.0 {
+                    Scalar::INFINITY
+                } else {
+                    s / n + self.exploration * (2.0 * log_t / n).sqrt()
+                }
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn next_action(&self) -> usize {
+        regret_minimizer::argmax(&self.upper_bounds())
+    }
+
+    /// Record the `reward` for playing `action`. Rewards
+    /// should be in `[0, 1]`.
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.counts.len())?;
+        self.counts[action] += 1.0;
+        self.reward_sums[action] += reward;
+        self.num_updates += 1;
+        Ok(())
+    }
+
+    /// The empirical mean reward of each action.
+    #[must_use]
+    pub fn mean_rewards(&self) -> Vec<Scalar> {
+        self.counts
+            .iter()
+            .zip(self.reward_sums.iter())
+            .map(|(n, s)| if *n == 0.0 { 0.0 } else { s / n })
+            .collect()
+    }
+
+    /// How often each action has been played.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return regret_minimizer::uniform(self.counts.len());
+        }
+        (&self.counts / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for Ucb1Policy {
+    fn num_experts(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn next_action<R: Rng>(&self, _rng: &mut R) -> usize {
+        Self::next_action(self)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tries_every_arm_first() {
+        let mut policy = Ucb1Policy::new(4);
+        let mut seen = Vec::new();
+        for _i in 0..4 {
+            let a = policy.next_action();
+            seen.push(a);
+            policy.update_sampled(a, 0.0).unwrap();
+        }
+        assert_eq!(seen, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_finds_best_arm() {
+        let mut policy = Ucb1Policy::new(3);
+        let means = [0.2, 0.8, 0.5];
+        for i in 0..2_000 {
+            let a = policy.next_action();
+            // Deterministic rewards that average to the mean.
+            let reward = if (i * 7 % 10) as Scalar / 10.0 < means[a] {
+                1.0
+            } else {
+                0.0
+            };
+            policy.update_sampled(a, reward).unwrap();
+        }
+        assert_eq!(regret_minimizer::argmax(&policy.best_weight()), 1);
+    }
+}
@@ -0,0 +1,234 @@
+#![allow(clippy::cast_precision_loss)]
+//! Many discounted CFR matchers stored structure of arrays style.
+//! Tree CFR keeps one matcher per information set, and millions of
+//! separately allocated matchers scatter their regrets all over the
+//! heap. A bank keeps every matcher's regrets, current strategy and
+//! strategy sum in three contiguous arrays, addressed by the index
+//! `add` hands out, so updates are tight loops over neighbouring
+//! memory that the compiler can vectorize.
+use ndarray::ArrayView1;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::ops::Range;
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::discount::DiscountParams;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::kernels;
+use crate::regret_minimizer;
+use crate::scalar::{Accumulator, Scalar};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatcherBank {
+    params: DiscountParams,
+    averaging: AveragingScheme,
+    // Matcher `i` owns `offsets[i]..offsets[i + 1]` of every array.
+    offsets: Vec<usize>,
+    regrets: CompensatedArray<Scalar>,
+    strategy: Vec<Scalar>,
+    strategy_sum: CompensatedArray<Accumulator>,
+    // Matchers are updated independently so each has its own
+    // iteration count for discounting.
+    num_updates: Vec<usize>,
+}
+
+impl MatcherBank {
+    /// An empty bank whose matchers all discount with `params`.
+    #[must_use]
+    pub fn new_with_params(params: DiscountParams) -> Self {
+        Self {
+            params,
+            averaging: AveragingScheme::Discounted(params.gamma),
+            offsets: vec![0],
+            regrets: CompensatedArray::zeros(0),
+            strategy: Vec::new(),
+            strategy_sum: CompensatedArray::zeros(0),
+            num_updates: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    /// Add a matcher over `num_experts` actions, returning the index
+    /// it's addressed by.
+    pub fn add(&mut self, num_experts: usize) -> Result<usize, LittleError> {
+        if num_experts == 0 {
+            return Err(LittleError::InvalidParameter(
+                "num_experts must be greater than 0",
+            ));
+        }
+        let uniform = 1.0 / num_experts as Scalar;
+        self.regrets.resize(self.regrets.len() + num_experts);
+        self.strategy
+            .resize(self.strategy.len() + num_experts, uniform);
+        self.strategy_sum
+            .resize(self.strategy_sum.len() + num_experts);
+        self.offsets.push(self.regrets.len());
+        self.num_updates.push(0);
+        Ok(self.num_updates.len() - 1)
+    }
+
+    /// The number of matchers in the bank.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.num_updates.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.num_updates.is_empty()
+    }
+
+    fn range(&self, index: usize) -> Range<usize> {
+        self.offsets[index]..self.offsets[index + 1]
+    }
+
+    /// Panics if there's no matcher `index`, as do all the
+    /// accessors below.
+    #[must_use]
+    pub fn num_experts(&self, index: usize) -> usize {
+        self.range(index).len()
+    }
+
+    #[must_use]
+    pub fn num_updates(&self, index: usize) -> usize {
+        self.num_updates[index]
+    }
+
+    #[must_use]
+    pub fn current_strategy(&self, index: usize) -> &[Scalar] {
+        &self.strategy[self.range(index)]
+    }
+
+    #[must_use]
+    pub fn cumulative_regrets(&self, index: usize) -> &[Scalar] {
+        &self.regrets.as_slice()[self.range(index)]
+    }
+
+    /// The average strategy of matcher `index`, or its current
+    /// strategy if nothing has been averaged yet.
+    #[must_use]
+    pub fn best_weight(&self, index: usize) -> Vec<Scalar> {
+        let sum = ArrayView1::from(&self.strategy_sum.as_slice()[self.range(index)]);
+        AveragingScheme::average(sum).unwrap_or_else(|| self.current_strategy(index).to_vec())
+    }
+
+    pub fn next_action<R: Rng>(&self, index: usize, rng: &mut R) -> usize {
+        dist::sample_weights(self.current_strategy(index), rng)
+    }
+
+    /// Feed matcher `index` the reward every action would have
+    /// gotten this round.
+    pub fn update_regret(&mut self, index: usize, rewards: &[Scalar]) -> Result<(), LittleError> {
+        self.update_regret_weighted(index, rewards, 1.0)
+    }
+
+    /// Like `update_regret` with the strategy added to the average
+    /// scaled by `average_weight`, such as the reach probability.
+    pub fn update_regret_weighted(
+        &mut self,
+        index: usize,
+        rewards: &[Scalar],
+        average_weight: Scalar,
+    ) -> Result<(), LittleError> {
+        if index >= self.len() {
+            return Err(LittleError::InvalidParameter("no matcher with that index"));
+        }
+        let range = self.range(index);
+        if rewards.len() != range.len() {
+            return Err(LittleError::InvalidParameter(
+                "rewards must have one entry per expert",
+            ));
+        }
+        if !(average_weight >= 0.0 && average_weight.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "average_weight must be finite and not negative",
+            ));
+        }
+        self.num_updates[index] += 1;
+        let t = self.num_updates[index];
+        let strategy = &mut self.strategy[range.clone()];
+        let mut regrets = self.regrets.slice_mut(range.clone());
+
+        self.averaging.accumulate_into(
+            t,
+            self.strategy_sum.slice_mut(range),
+            ArrayView1::from(&*strategy),
+            average_weight,
+        );
+
+        let expected = kernels::dot(strategy, rewards);
+        let positive_factor = self.params.positive_factor(t);
+        let negative_factor = self.params.negative_factor(t);
+        for (i, reward) in rewards.iter().enumerate() {
+            regrets.add(i, reward - expected);
+            let factor = if regrets.values()[i] >= 0.0 {
+                positive_factor
+            } else {
+                negative_factor
+            };
+            regrets.scale_at(i, factor);
+        }
+        regret_minimizer::regret_match(regrets.values(), strategy);
+        Ok(())
+    }
+}
+
+impl Default for MatcherBank {
+    fn default() -> Self {
+        Self::new_with_params(DiscountParams::recommended())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::regret_minimizer::RegretMinimizer;
+
+    #[test]
+    fn test_matches_dcfr() {
+        let mut bank = MatcherBank::default();
+        let two = bank.add(2).unwrap();
+        let three = bank.add(3).unwrap();
+        let mut single = DiscountedRegretMatcher::new(3).unwrap();
+        let rounds = [[1.0, 0.0, 0.5], [0.0, 2.0, 0.0], [0.5, 0.5, 1.0]];
+        for r in &rounds {
+            bank.update_regret(three, r).unwrap();
+            bank.update_regret(two, &[1.0, 0.0]).unwrap();
+            single.update_regret(r).unwrap();
+        }
+        for (a, b) in bank
+            .cumulative_regrets(three)
+            .iter()
+            .zip(single.cumulative_regrets())
+        {
+            assert!((a - b).abs() < 1e-5);
+        }
+        for (a, b) in bank.best_weight(three).iter().zip(single.best_weight()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        assert_eq!(bank.current_strategy(two), &[1.0, 0.0]);
+        assert_eq!(bank.num_updates(two), 3);
+    }
+
+    #[test]
+    fn test_bad_index() {
+        let mut bank = MatcherBank::default();
+        bank.add(2).unwrap();
+        assert!(bank.update_regret(1, &[0.0, 0.0]).is_err());
+        assert!(bank.update_regret(0, &[0.0]).is_err());
+        assert!(bank.add(0).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! Best response oracles for matrix games. Throughout `payoff[[a, b]]`
+//! is the reward for playing `a` when the opponent plays `b`, so the
+//! column player of a `NormalFormGame` uses the transpose of its
+//! payoff. These are what CFR-BR trains against, what exploitability
+//! is measured with and what double oracle methods grow their
+//! restricted games from.
+use ndarray::prelude::*;
+
+use std::vec::Vec;
+
+use crate::regret_minimizer;
+use crate::scalar::Scalar;
+
+/// The expected reward of every action against
+/// `opponent_strategy`.
+#[must_use]
+pub fn action_values(payoff: ArrayView2<Scalar>, opponent_strategy: &[Scalar]) -> Vec<Scalar> {
+    payoff.dot(&ArrayView1::from(opponent_strategy)).to_vec()
+}
+
+/// The action that earns the most against `opponent_strategy`.
+/// Returns the action and its expected reward.
+#[must_use]
+pub fn best_response(payoff: ArrayView2<Scalar>, opponent_strategy: &[Scalar]) -> (usize, Scalar) {
+    let values = action_values(payoff, opponent_strategy);
+    let action = regret_minimizer::argmax(&values);
+    (action, values[action])
+}
+
+/// Every action earning within `tolerance` of the best response,
+/// in order.
+#[must_use]
+pub fn best_response_set(
+    payoff: ArrayView2<Scalar>,
+    opponent_strategy: &[Scalar],
+    tolerance: Scalar,
+) -> Vec<usize> {
+    let values = action_values(payoff, opponent_strategy);
+    let best = values[regret_minimizer::argmax(&values)];
+    (0..values.len())
+        .filter(|a| values[*a] >= best - tolerance)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_response() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        // Against pure rock, paper is best and wins every time.
+        assert_eq!(best_response(payoff.view(), &[1.0, 0.0, 0.0]), (1, 1.0));
+        // Against uniform every action is as good as any other.
+        let uniform = [1.0 / 3.0; 3];
+        assert_eq!(
+            best_response_set(payoff.view(), &uniform, 1e-6),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            best_response_set(payoff.view(), &[0.5, 0.5, 0.0], 1e-6),
+            vec![1]
+        );
+    }
+}
@@ -0,0 +1,73 @@
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+
+//! Print heads up push or fold charts.
+//!
+//! Usage: `push_fold EQUITY_CSV [STACK...]`
+//!
+//! The first line of the CSV names the hands and each line after is
+//! a row of the equity table, so row `i` column `j` is hand `i`'s
+//! equity against hand `j`. Hands named like `AA`, `AKs` or `AKo`
+//! are dealt in proportion to their number of combos and anything
+//! else uniformly. Card removal is ignored.
+
+use std::env;
+use std::fs;
+
+use little_sorry::poker::PushFold;
+use little_sorry::vanilla_cfr::VanillaCfrRegretMatcher;
+use little_sorry::Scalar;
+use ndarray::Array2;
+
+static ITERATIONS: usize = 1_000;
+static DEFAULT_STACKS: [Scalar; 3] = [5.0, 10.0, 15.0];
+
+fn combos(hand: &str) -> Scalar {
+    let chars: Vec<char> = hand.chars().collect();
+    match chars.as_slice() {
+        [a, b] if a == b => 6.0,
+        [_, _, 's'] => 4.0,
+        [_, _, 'o'] => 12.0,
+        _ => 1.0,
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("usage: push_fold EQUITY_CSV [STACK...]");
+    let mut stacks: Vec<Scalar> = args
+        .map(|s| s.parse().expect("stacks are numbers"))
+        .collect();
+    if stacks.is_empty() {
+        stacks = DEFAULT_STACKS.to_vec();
+    }
+
+    let csv = fs::read_to_string(path).expect("can't read the equity table");
+    let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+    let hands: Vec<String> = lines
+        .next()
+        .expect("the table is empty")
+        .split(',')
+        .map(|h| h.trim().to_string())
+        .collect();
+    let values: Vec<Scalar> = lines
+        .flat_map(|l| l.split(','))
+        .map(|v| v.trim().parse().expect("equities are numbers"))
+        .collect();
+    let equity = Array2::from_shape_vec((hands.len(), hands.len()), values)
+        .expect("the table must have a row and column per hand");
+    let weights: Vec<Scalar> = hands.iter().map(|h| combos(h)).collect();
+
+    for stack in stacks {
+        let game = PushFold::independent(hands.clone(), equity.clone(), &weights, stack).unwrap();
+        let chart = game.solve::<VanillaCfrRegretMatcher>(ITERATIONS).unwrap();
+        println!("stack {stack}bb");
+        for ((hand, push), call) in chart.hands.iter().zip(&chart.push).zip(&chart.call) {
+            println!(
+                "{hand:>4} push {:5.1}% call {:5.1}%",
+                100.0 * push,
+                100.0 * call
+            );
+        }
+    }
+}
@@ -1,12 +1,16 @@
 #![deny(clippy::all)]
 #![deny(clippy::pedantic)]
 
+use little_sorry::convergence::ConvergenceCheck;
 use little_sorry::rps::RPSRunner;
+use little_sorry::ConvergenceCriterion;
 
 static NUM_ITERS: usize = 100_000_000;
+static CHECK_EVERY: usize = 10_000;
 
 fn main() {
     let mut runner = RPSRunner::new().unwrap();
+    let mut check = ConvergenceCheck::new(ConvergenceCriterion::Exploitability(5e-3)).unwrap();
     dbg!(&runner.matcher_one);
     for i in 0..NUM_ITERS {
         runner.run_one();
@@ -14,7 +18,15 @@ fn main() {
         if i % 50 == 0 || i == NUM_ITERS - 1 {
             runner.update_regret().unwrap();
         }
+        // Stop early once the average is close enough to the
+        // equilibrium rather than always running every iteration.
+        if (i + 1) % CHECK_EVERY == 0 && check.check(runner.exploitability(), &runner.best_weight())
+        {
+            dbg!(i + 1);
+            break;
+        }
     }
     dbg!(&runner);
+    dbg!(runner.exploitability());
     dbg!(runner.best_weight());
 }
@@ -0,0 +1,21 @@
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+
+use little_sorry::games::exploitability::exploitability;
+use little_sorry::games::liars_dice::LiarsDice;
+use little_sorry::games::GameCfr;
+use little_sorry::vanilla_cfr::VanillaCfrRegretMatcher;
+
+static ITERATIONS: usize = 1_000;
+static CHECK_EVERY: usize = 100;
+
+fn main() {
+    let game = LiarsDice::new(1, 4).unwrap();
+    let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(game);
+    for i in (0..ITERATIONS).step_by(CHECK_EVERY) {
+        cfr.run(CHECK_EVERY).unwrap();
+        let e = exploitability(&game, &cfr.average_policy());
+        println!("{} {e}", i + CHECK_EVERY);
+    }
+    println!("{} infosets", cfr.infosets().len());
+}
@@ -0,0 +1,121 @@
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+
+//! Grid search Discounted CFR's exponents on one game and write the
+//! exploitability curve of every setting as CSV.
+//!
+//! Usage: `sweep_params GAME ITERATIONS [--every N] [--alpha A,...]
+//! [--beta B,...] [--gamma G,...] [--out PATH]`
+//!
+//! `GAME` is `leduc`, `goofspiel` (three cards) or `liars_dice` (one
+//! die with four faces). Exploitability is measured every `--every`
+//! iterations, 100 by default. Each exponent list defaults to a few
+//! values around the recommended 1.5, 0 and 2. Rows go to stdout
+//! unless `--out` is given.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use little_sorry::dcfr::DiscountedRegretMatcher;
+use little_sorry::discount::DiscountParams;
+use little_sorry::games::{Game, GameCfr, Goofspiel, Leduc, LiarsDice};
+use little_sorry::Scalar;
+
+static USAGE: &str = "usage: sweep_params GAME ITERATIONS [--every N] [--alpha A,...] \
+                      [--beta B,...] [--gamma G,...] [--out PATH]";
+static DEFAULT_EVERY: usize = 100;
+static DEFAULT_ALPHAS: [Scalar; 3] = [1.0, 1.5, 2.0];
+static DEFAULT_BETAS: [Scalar; 3] = [-1.0, 0.0, 0.5];
+static DEFAULT_GAMMAS: [Scalar; 3] = [1.0, 2.0, 3.0];
+
+struct Sweep {
+    game: String,
+    iterations: usize,
+    every: usize,
+    alphas: Vec<Scalar>,
+    betas: Vec<Scalar>,
+    gammas: Vec<Scalar>,
+    out: Option<String>,
+}
+
+fn parse_list(list: &str) -> Vec<Scalar> {
+    list.split(',')
+        .map(|v| v.trim().parse().expect("exponents are numbers"))
+        .collect()
+}
+
+fn parse_args() -> Sweep {
+    let mut args = env::args().skip(1);
+    let game = args.next().expect(USAGE);
+    let iterations = args
+        .next()
+        .expect(USAGE)
+        .parse()
+        .expect("ITERATIONS is a number");
+    let mut sweep = Sweep {
+        game,
+        iterations,
+        every: DEFAULT_EVERY,
+        alphas: DEFAULT_ALPHAS.to_vec(),
+        betas: DEFAULT_BETAS.to_vec(),
+        gammas: DEFAULT_GAMMAS.to_vec(),
+        out: None,
+    };
+    while let Some(flag) = args.next() {
+        let value = args.next().expect(USAGE);
+        match flag.as_str() {
+            "--every" => sweep.every = value.parse().expect("--every is a number"),
+            "--alpha" => sweep.alphas = parse_list(&value),
+            "--beta" => sweep.betas = parse_list(&value),
+            "--gamma" => sweep.gammas = parse_list(&value),
+            "--out" => sweep.out = Some(value),
+            _ => panic!("{USAGE}"),
+        }
+    }
+    assert!(sweep.every > 0, "--every must be positive");
+    sweep
+}
+
+fn run<G: Game + Clone>(game: &G, sweep: &Sweep, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "game,alpha,beta,gamma,iteration,exploitability")?;
+    for &alpha in &sweep.alphas {
+        for &beta in &sweep.betas {
+            for &gamma in &sweep.gammas {
+                let params = DiscountParams::new(alpha, beta, gamma).unwrap();
+                let mut cfr = GameCfr::new(game.clone())
+                    .with_minimizers(move |n| DiscountedRegretMatcher::new_with_params(n, params));
+                while cfr.num_iterations() < sweep.iterations {
+                    let step = sweep.every.min(sweep.iterations - cfr.num_iterations());
+                    cfr.run(step).unwrap();
+                    writeln!(
+                        out,
+                        "{},{alpha},{beta},{gamma},{},{}",
+                        sweep.game,
+                        cfr.num_iterations(),
+                        cfr.exploitability()
+                    )?;
+                }
+                eprintln!(
+                    "{} alpha {alpha} beta {beta} gamma {gamma} done",
+                    sweep.game
+                );
+            }
+        }
+    }
+    out.flush()
+}
+
+fn main() -> io::Result<()> {
+    let sweep = parse_args();
+    let mut out: Box<dyn Write> = match &sweep.out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    match sweep.game.as_str() {
+        "leduc" => run(&Leduc, &sweep, &mut out),
+        "goofspiel" => run(&Goofspiel::new(3).unwrap(), &sweep, &mut out),
+        "liars_dice" => run(&LiarsDice::new(1, 4).unwrap(), &sweep, &mut out),
+        _ => panic!("unknown game {}, {USAGE}", sweep.game),
+    }
+}
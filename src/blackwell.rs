@@ -0,0 +1,286 @@
+#![allow(clippy::cast_precision_loss)]
+//! Regret matching viewed as Blackwell approachability. Each
+//! round produces a vector payoff, the instantaneous regret of
+//! every action. Plain regret matching tries to drive the average
+//! of those vectors into the negative orthant. Here the set being
+//! approached is a parameter, so the same machinery can be aimed
+//! at other convex sets of acceptable regret vectors.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Debug;
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+/// A closed convex set of average regret vectors to approach.
+pub trait TargetSet: Clone + Debug {
+    /// Write the closest point in the set to `point` into `out`.
+    fn project(&self, point: &[Scalar], out: &mut [Scalar]);
+
+    /// Check that the set is over `num_experts` dimensions.
+    fn check(&self, _num_experts: usize) -> Result<(), LittleError> {
+        Ok(())
+    }
+}
+
+/// Every regret is at most zero. This is plain regret matching.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NegativeOrthant;
+
+impl TargetSet for NegativeOrthant {
+    fn project(&self, point: &[Scalar], out: &mut [Scalar]) {
+        for (o, p) in out.iter_mut().zip(point) {
+            *o = Scalar::min(0.0, *p);
+        }
+    }
+}
+
+/// Regret for each action is at most its own threshold. Useful
+/// when some regret is acceptable for some actions, for example
+/// as the cost of satisfying another constraint.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShiftedOrthant {
+    pub thresholds: Vec<Scalar>,
+}
+
+impl TargetSet for ShiftedOrthant {
+    fn project(&self, point: &[Scalar], out: &mut [Scalar]) {
+        for ((o, p), t) in out.iter_mut().zip(point).zip(&self.thresholds) {
+            *o = Scalar::min(*t, *p);
+        }
+    }
+
+    fn check(&self, num_experts: usize) -> Result<(), LittleError> {
+        if self.thresholds.len() != num_experts {
+            return Err(LittleError::InvalidParameter(
+                "thresholds must have one entry per expert",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlackwellMatcher<S: TargetSet> {
+    target: S,
+    // Sum of the vector payoffs (instantaneous regrets).
+    cumulative_regret: CompensatedArray<Scalar>,
+    // Scratch space for the projection and the direction to it.
+    projection: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl<S: TargetSet> BlackwellMatcher<S> {
+    pub fn new_with_target(num_experts: usize, target: S) -> Result<Self, LittleError> {
+        target.check(num_experts)?;
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            target,
+            cumulative_regret: CompensatedArray::zeros(num_experts),
+            projection: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn target(&self) -> &S {
+        &self.target
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        let expected = self.p.dot(&reward_array);
+        for (i, r) in reward_array.iter().enumerate() {
+            self.cumulative_regret
+                .add(i, options.regret_weight * (r - expected));
+        }
+        self.approach()
+    }
+
+    // Choose the strategy that steers the average regret towards
+    // the target set.
+    fn approach(&mut self) -> Result<(), LittleError> {
+        // The average payoff and its projection on to the target.
+        let average = &self.cumulative_regret.values() / self.num_updates as Scalar;
+        self.target.project(
+            average.as_slice().unwrap(),
+            self.projection.as_slice_mut().unwrap(),
+        );
+        // Playing in proportion to the direction from the target
+        // to the average forces the next payoff to be orthogonal
+        // to it, which is Blackwell's condition. Once inside the
+        // set the direction is zero and any strategy will do so
+        // regret_match falls back to uniform.
+        let direction = average - &self.projection;
+        regret_minimizer::regret_match(
+            direction.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    /// Distance from the average regret vector to the target set.
+    #[must_use]
+    pub fn distance_to_target(&self) -> Scalar {
+        if self.num_updates == 0 {
+            return 0.0;
+        }
+        let average = &self.cumulative_regret.values() / self.num_updates as Scalar;
+        let mut projection = Array1::zeros(average.len());
+        self.target.project(
+            average.as_slice().unwrap(),
+            projection.as_slice_mut().unwrap(),
+        );
+        (average - projection).mapv(|v| v * v).sum().sqrt()
+    }
+}
+
+impl<S: TargetSet + Default> RegretMinimizer for BlackwellMatcher<S> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_target(num_experts, S::default())
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.values().to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    /// The regrets count as the total from a single update, and
+    /// the strategy they lead to is played for that update.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.cumulative_regret = CompensatedArray::from(Array1::from(regrets.to_vec()));
+        matcher.num_updates = 1;
+        matcher.approach()?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(matcher.p.as_slice().unwrap()));
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orthant_matches_regret_matching() {
+        let mut m: BlackwellMatcher<NegativeOrthant> = BlackwellMatcher::new(3).unwrap();
+        m.update_regret(&[1.0, 0.0, 2.0]).unwrap();
+        // Regrets are [0, -1, 1] so all the weight goes to the last.
+        assert_eq!(m.current_strategy(), &[0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_shifted_target_tolerates_regret() {
+        let target = ShiftedOrthant {
+            thresholds: vec![0.5, 0.5],
+        };
+        let mut m = BlackwellMatcher::new_with_target(2, target).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        // Average regret is [0.25, -0.25] which is acceptable, so
+        // there is no pressure towards either action.
+        assert_eq!(m.distance_to_target(), 0.0);
+        assert_eq!(m.current_strategy(), &[0.5, 0.5]);
+        let target = ShiftedOrthant {
+            thresholds: vec![0.5],
+        };
+        assert!(BlackwellMatcher::new_with_target(2, target).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! One place to configure any of the full information matchers.
+//!
+//! ```
+//! use little_sorry::builder::MatcherBuilder;
+//!
+//! let matcher = MatcherBuilder::dcfr()
+//!     .alpha(1.5)
+//!     .gamma(2.0)
+//!     .experts(52)
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Every algorithm starts out with the same defaults as its `new`
+//! and parameters are checked when `build` is called.
+use crate::averaging::AveragingScheme;
+use crate::dcfr::DiscountedRegretMatcher;
+use crate::discount::DiscountParams;
+use crate::errors::LittleError;
+use crate::lazy_dcfr::LazyDcfrRegretMatcher;
+use crate::mwu::{self, MwuRegretMatcher};
+use crate::omd::{OmdRegretMatcher, StepSize};
+use crate::optimistic_hedge::{self, OptimisticHedgeRegretMatcher};
+use crate::regret_matcher::RegretMatcher;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+use crate::smooth::{self, SmoothRegretMatcher};
+use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+use crate::windowed::{self, WindowedRegretMatcher};
+
+/// The algorithm specific half of a `MatcherBuilder`.
+pub trait Algorithm {
+    type Matcher: RegretMinimizer;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct MatcherBuilder<A> {
+    algorithm: A,
+    num_experts: Option<usize>,
+    averaging: Option<AveragingScheme>,
+}
+
+impl<A: Algorithm> MatcherBuilder<A> {
+    fn with_algorithm(algorithm: A) -> Self {
+        Self {
+            algorithm,
+            num_experts: None,
+            averaging: None,
+        }
+    }
+
+    /// How many actions the matcher chooses between. Required.
+    #[must_use]
+    pub fn experts(mut self, num_experts: usize) -> Self {
+        self.num_experts = Some(num_experts);
+        self
+    }
+
+    /// How the strategies played are weighted in the average.
+    #[must_use]
+    pub fn averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = Some(averaging);
+        self
+    }
+
+    pub fn build(&self) -> Result<A::Matcher, LittleError> {
+        let num_experts = self
+            .num_experts
+            .ok_or(LittleError::InvalidParameter("experts must be set"))?;
+        if num_experts == 0 {
+            return Err(LittleError::InvalidParameter("experts must be positive"));
+        }
+        let matcher = self.algorithm.build(num_experts)?;
+        Ok(match self.averaging {
+            Some(averaging) => matcher.with_averaging(averaging),
+            None => matcher,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dcfr {
+    params: DiscountParams,
+    adaptive: bool,
+}
+
+impl Algorithm for Dcfr {
+    type Matcher = DiscountedRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        if self.adaptive {
+            return DiscountedRegretMatcher::adaptive(num_experts);
+        }
+        let p = self.params;
+        DiscountedRegretMatcher::new_with_params(
+            num_experts,
+            DiscountParams::new(p.alpha, p.beta, p.gamma)?,
+        )
+    }
+}
+
+impl MatcherBuilder<Dcfr> {
+    /// Discounted CFR with the recommended parameters.
+    #[must_use]
+    pub fn dcfr() -> Self {
+        Self::with_algorithm(Dcfr {
+            params: DiscountParams::recommended(),
+            adaptive: false,
+        })
+    }
+
+    #[must_use]
+    pub fn alpha(mut self, alpha: Scalar) -> Self {
+        self.algorithm.params.alpha = alpha;
+        self
+    }
+
+    #[must_use]
+    pub fn beta(mut self, beta: Scalar) -> Self {
+        self.algorithm.params.beta = beta;
+        self
+    }
+
+    #[must_use]
+    pub fn gamma(mut self, gamma: Scalar) -> Self {
+        self.algorithm.params.gamma = gamma;
+        self
+    }
+
+    #[must_use]
+    pub fn params(mut self, params: DiscountParams) -> Self {
+        self.algorithm.params = params;
+        self
+    }
+
+    /// Use the Linear CFR parameters.
+    #[must_use]
+    pub fn lcfr(self) -> Self {
+        self.params(DiscountParams::lcfr())
+    }
+
+    /// Let the matcher pick its own parameters as it goes. Any
+    /// parameters set are ignored.
+    #[must_use]
+    pub fn adaptive(mut self) -> Self {
+        self.algorithm.adaptive = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LazyDcfr {
+    params: DiscountParams,
+}
+
+impl Algorithm for LazyDcfr {
+    type Matcher = LazyDcfrRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        let p = self.params;
+        LazyDcfrRegretMatcher::new_with_params(
+            num_experts,
+            DiscountParams::new(p.alpha, p.beta, p.gamma)?,
+        )
+    }
+}
+
+impl MatcherBuilder<LazyDcfr> {
+    /// Discounted CFR that scales lazily, with the recommended
+    /// parameters.
+    #[must_use]
+    pub fn lazy_dcfr() -> Self {
+        Self::with_algorithm(LazyDcfr {
+            params: DiscountParams::recommended(),
+        })
+    }
+
+    #[must_use]
+    pub fn alpha(mut self, alpha: Scalar) -> Self {
+        self.algorithm.params.alpha = alpha;
+        self
+    }
+
+    #[must_use]
+    pub fn beta(mut self, beta: Scalar) -> Self {
+        self.algorithm.params.beta = beta;
+        self
+    }
+
+    #[must_use]
+    pub fn gamma(mut self, gamma: Scalar) -> Self {
+        self.algorithm.params.gamma = gamma;
+        self
+    }
+
+    #[must_use]
+    pub fn params(mut self, params: DiscountParams) -> Self {
+        self.algorithm.params = params;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VanillaCfr;
+
+impl Algorithm for VanillaCfr {
+    type Matcher = VanillaCfrRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        VanillaCfrRegretMatcher::new(num_experts)
+    }
+}
+
+impl MatcherBuilder<VanillaCfr> {
+    #[must_use]
+    pub fn vanilla_cfr() -> Self {
+        Self::with_algorithm(VanillaCfr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegretMatching;
+
+impl Algorithm for RegretMatching {
+    type Matcher = RegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        RegretMatcher::new(num_experts)
+    }
+}
+
+impl MatcherBuilder<RegretMatching> {
+    #[must_use]
+    pub fn regret_matching() -> Self {
+        Self::with_algorithm(RegretMatching)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Smooth {
+    temperature: Scalar,
+    mix: Scalar,
+}
+
+impl Algorithm for Smooth {
+    type Matcher = SmoothRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        SmoothRegretMatcher::new_with_params(num_experts, self.temperature, self.mix)
+    }
+}
+
+impl MatcherBuilder<Smooth> {
+    #[must_use]
+    pub fn smooth() -> Self {
+        Self::with_algorithm(Smooth {
+            temperature: smooth::DEFAULT_TEMPERATURE,
+            mix: smooth::DEFAULT_MIX,
+        })
+    }
+
+    #[must_use]
+    pub fn temperature(mut self, temperature: Scalar) -> Self {
+        self.algorithm.temperature = temperature;
+        self
+    }
+
+    #[must_use]
+    pub fn mix(mut self, mix: Scalar) -> Self {
+        self.algorithm.mix = mix;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mwu {
+    // None uses the doubling trick.
+    epsilon: Option<Scalar>,
+}
+
+impl Algorithm for Mwu {
+    type Matcher = MwuRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        match self.epsilon {
+            Some(epsilon) => MwuRegretMatcher::new_with_params(num_experts, epsilon),
+            None => MwuRegretMatcher::with_doubling_trick(num_experts),
+        }
+    }
+}
+
+impl MatcherBuilder<Mwu> {
+    #[must_use]
+    pub fn mwu() -> Self {
+        Self::with_algorithm(Mwu {
+            epsilon: Some(mwu::DEFAULT_EPSILON),
+        })
+    }
+
+    #[must_use]
+    pub fn epsilon(mut self, epsilon: Scalar) -> Self {
+        self.algorithm.epsilon = Some(epsilon);
+        self
+    }
+
+    /// Pick epsilon with the doubling trick instead.
+    #[must_use]
+    pub fn doubling_trick(mut self) -> Self {
+        self.algorithm.epsilon = None;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Omd {
+    step_size: StepSize,
+}
+
+impl Algorithm for Omd {
+    type Matcher = OmdRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        OmdRegretMatcher::new_with_params(num_experts, self.step_size)
+    }
+}
+
+impl MatcherBuilder<Omd> {
+    #[must_use]
+    pub fn omd() -> Self {
+        Self::with_algorithm(Omd {
+            step_size: StepSize::default(),
+        })
+    }
+
+    #[must_use]
+    pub fn step_size(mut self, step_size: StepSize) -> Self {
+        self.algorithm.step_size = step_size;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptimisticHedge {
+    eta: Scalar,
+}
+
+impl Algorithm for OptimisticHedge {
+    type Matcher = OptimisticHedgeRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        OptimisticHedgeRegretMatcher::new_with_params(num_experts, self.eta)
+    }
+}
+
+impl MatcherBuilder<OptimisticHedge> {
+    #[must_use]
+    pub fn optimistic_hedge() -> Self {
+        Self::with_algorithm(OptimisticHedge {
+            eta: optimistic_hedge::DEFAULT_ETA,
+        })
+    }
+
+    #[must_use]
+    pub fn eta(mut self, eta: Scalar) -> Self {
+        self.algorithm.eta = eta;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Windowed {
+    window: usize,
+}
+
+impl Algorithm for Windowed {
+    type Matcher = WindowedRegretMatcher;
+
+    fn build(&self, num_experts: usize) -> Result<Self::Matcher, LittleError> {
+        WindowedRegretMatcher::new_with_params(num_experts, self.window)
+    }
+}
+
+impl MatcherBuilder<Windowed> {
+    #[must_use]
+    pub fn windowed() -> Self {
+        Self::with_algorithm(Windowed {
+            window: windowed::DEFAULT_WINDOW,
+        })
+    }
+
+    #[must_use]
+    pub fn window(mut self, window: usize) -> Self {
+        self.algorithm.window = window;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let m = MatcherBuilder::dcfr()
+            .alpha(1.0)
+            .gamma(3.0)
+            .experts(4)
+            .build()
+            .unwrap();
+        assert_eq!(m.params(), DiscountParams::new(1.0, 0.0, 3.0).unwrap());
+        assert_eq!(m.num_experts(), 4);
+        let m = MatcherBuilder::windowed()
+            .averaging(AveragingScheme::Linear)
+            .experts(2)
+            .build()
+            .unwrap();
+        assert_eq!(m.averaging(), AveragingScheme::Linear);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(MatcherBuilder::vanilla_cfr().build().is_err());
+        assert!(MatcherBuilder::mwu().experts(0).build().is_err());
+        assert!(MatcherBuilder::dcfr()
+            .gamma(-1.0)
+            .experts(3)
+            .build()
+            .is_err());
+        assert!(MatcherBuilder::smooth()
+            .mix(2.0)
+            .experts(3)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,119 @@
+#![allow(clippy::cast_precision_loss)]
+//! CFR-BR for two player zero-sum matrix games. One side runs a
+//! regret minimizer while the other always plays an exact best
+//! response to the minimizer's average strategy. The value the
+//! best responder earns is how exploitable that average is, so
+//! this both trains and measures convergence at the same time.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+pub use crate::best_response::best_response;
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+#[cfg(feature = "metrics")]
+use crate::telemetry::MatcherMetrics;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CfrBrRegretMatcher<M: RegretMinimizer> {
+    matcher: M,
+    // Reward to the learner for each (learner action, responder action).
+    payoff: Array2<Scalar>,
+    // The best responder's payoff, the negation of `payoff` transposed.
+    responder_payoff: Array2<Scalar>,
+    // What the best responder played last and what it earned.
+    last_response: usize,
+    last_response_value: Scalar,
+}
+
+impl<M: RegretMinimizer> CfrBrRegretMatcher<M> {
+    /// `payoff[[a, b]]` is the learner's reward for playing `a`
+    /// against the best responder playing `b`. The game is zero-sum.
+    pub fn new(payoff: Array2<Scalar>) -> Result<Self, LittleError> {
+        let matcher = M::new(payoff.nrows())?;
+        Ok(Self::new_with_matcher(matcher, payoff))
+    }
+
+    #[must_use]
+    pub fn new_with_matcher(matcher: M, payoff: Array2<Scalar>) -> Self {
+        let responder_payoff = payoff.t().mapv(|v| -v);
+        Self {
+            matcher,
+            payoff,
+            responder_payoff,
+            last_response: 0,
+            last_response_value: 0.0,
+        }
+    }
+
+    /// Run one iteration. The responder best responds to the
+    /// learner's average strategy and the learner updates against
+    /// that response.
+    pub fn update(&mut self) -> Result<(), LittleError> {
+        let average = self.matcher.best_weight();
+        let (response, value) = best_response(self.responder_payoff.view(), &average);
+        self.last_response = response;
+        self.last_response_value = value;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(response, exploitability = value, "best responded");
+        let rewards = self.payoff.column(response).to_vec();
+        self.matcher.update_regret(&rewards)
+    }
+
+    #[must_use]
+    pub fn matcher(&self) -> &M {
+        &self.matcher
+    }
+
+    /// The action the best responder played on the last update.
+    #[must_use]
+    pub fn last_response(&self) -> usize {
+        self.last_response
+    }
+
+    /// How much the best responder earned on the last update. In
+    /// a symmetric game like RPS this is the exploitability of the
+    /// learner's average strategy and goes to zero as it converges.
+    #[must_use]
+    pub fn best_response_value(&self) -> Scalar {
+        self.last_response_value
+    }
+
+    pub fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.matcher.next_action(rng)
+    }
+
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        self.matcher.best_weight()
+    }
+
+    /// Report the learner and the best responder's last value.
+    #[cfg(feature = "metrics")]
+    pub fn observe_metrics(&self, metrics: &mut MatcherMetrics) {
+        metrics.observe(&self.matcher);
+        metrics.observe_exploitability(self.last_response_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+
+    #[test]
+    fn test_rps_exploitability_shrinks() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let mut m: CfrBrRegretMatcher<RegretMatcher> = CfrBrRegretMatcher::new(payoff).unwrap();
+        for _i in 0..5_000 {
+            m.update().unwrap();
+        }
+        assert!(m.best_response_value() < 0.05);
+    }
+}
@@ -0,0 +1,168 @@
+//! Save a matcher to disk and load it back so a long solve can be
+//! stopped and resumed. A checkpoint is a short header followed by
+//! the matcher encoded with bincode:
+//!
+//! * 4 bytes of magic, `LSRY`
+//! * the format version as a little endian `u32`
+//! * the payload length as a little endian `u64`
+//! * the CRC32 of the payload as a little endian `u32`
+//!
+//! Floats are stored exactly so a loaded matcher continues bit for
+//! bit where the saved one left off.
+//!
+//! Whole tree solves are saved as a `SolveCheckpoint`: every
+//! infoset's matcher, the iteration count and the sampling rng. An
+//! rng that implements serde, like `rand_chacha`'s with its `serde1`
+//! feature, resumes the exact sequence of samples.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::solver::InfoSetStore;
+
+const MAGIC: &[u8; 4] = b"LSRY";
+/// Bumped whenever the layout of a checkpoint changes.
+pub const CHECKPOINT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 20;
+
+/// Anything serializable can be checkpointed.
+pub trait Checkpoint: Serialize + DeserializeOwned {
+    /// Write a checkpoint to `path`. The file is written next to
+    /// `path` first and then moved into place so a crash part way
+    /// through never leaves a truncated checkpoint behind.
+    fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), LittleError> {
+        write_atomic(path.as_ref(), &encode(self)?)
+    }
+
+    /// Read back a checkpoint written by `save_checkpoint`.
+    fn load_checkpoint<P: AsRef<Path>>(path: P) -> Result<Self, LittleError> {
+        decode(&fs::read(path)?)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Checkpoint for T {}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), LittleError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The state of a tree solve: the matcher at each infoset, with
+/// its regrets and strategy sums, how many iterations have run and
+/// the rng the solve samples with, `()` for one that doesn't.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolveCheckpoint<M: RegretMinimizer, R = ()> {
+    pub infosets: InfoSetStore<M>,
+    pub num_iterations: usize,
+    pub rng: R,
+}
+
+// Encodes the same as a `SolveCheckpoint` without cloning the store.
+#[derive(Serialize)]
+struct SolveCheckpointRef<'a, M: RegretMinimizer, R> {
+    infosets: &'a InfoSetStore<M>,
+    num_iterations: usize,
+    rng: &'a R,
+}
+
+/// Write a solve's state to `path`, atomically like
+/// `Checkpoint::save_checkpoint`.
+pub fn save_solve<M, R, P>(
+    path: P,
+    infosets: &InfoSetStore<M>,
+    num_iterations: usize,
+    rng: &R,
+) -> Result<(), LittleError>
+where
+    M: RegretMinimizer + Serialize,
+    R: Serialize,
+    P: AsRef<Path>,
+{
+    let checkpoint = SolveCheckpointRef {
+        infosets,
+        num_iterations,
+        rng,
+    };
+    write_atomic(path.as_ref(), &encode(&checkpoint)?)
+}
+
+/// Read back a solve saved by `save_solve`.
+pub fn load_solve<M, R, P>(path: P) -> Result<SolveCheckpoint<M, R>, LittleError>
+where
+    M: RegretMinimizer + DeserializeOwned,
+    R: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    decode(&fs::read(path)?)
+}
+
+/// Encode `value` as a checkpoint in memory.
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, LittleError> {
+    let payload = bincode::serialize(value)?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decode a checkpoint made by `encode`, checking the header and
+/// the payload's checksum first.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, LittleError> {
+    if bytes.len() < HEADER_LEN || &bytes[0..4] != MAGIC {
+        return Err(LittleError::InvalidCheckpoint("not a checkpoint"));
+    }
+    let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    if version != CHECKPOINT_VERSION {
+        return Err(LittleError::InvalidCheckpoint("unsupported version"));
+    }
+    let len = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    let crc = u32::from_le_bytes(bytes[16..20].try_into().unwrap());
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() as u64 != len {
+        return Err(LittleError::InvalidCheckpoint("truncated"));
+    }
+    if crc32fast::hash(payload) != crc {
+        return Err(LittleError::InvalidCheckpoint("checksum mismatch"));
+    }
+    Ok(bincode::deserialize(payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_minimizer::{RegretMinimizer, StateTransfer};
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_round_trip() {
+        let mut m = VanillaCfrRegretMatcher::new(3).unwrap();
+        m.update_regret(&[0.1, 0.7, 0.3]).unwrap();
+        let path = std::env::temp_dir().join(format!("little-sorry-{}.ckpt", std::process::id()));
+        m.save_checkpoint(&path).unwrap();
+        let loaded = VanillaCfrRegretMatcher::load_checkpoint(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.export_state(), m.export_state());
+        assert_eq!(loaded.current_strategy(), m.current_strategy());
+    }
+
+    #[test]
+    fn test_corruption_detected() {
+        let m = VanillaCfrRegretMatcher::new(3).unwrap();
+        let mut bytes = encode(&m).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(decode::<VanillaCfrRegretMatcher>(&bytes).is_err());
+        assert!(decode::<VanillaCfrRegretMatcher>(&bytes[..10]).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//! Running sums that optionally use Kahan summation. After
+//! millions of updates each new term is tiny compared to the sum
+//! and most of its bits are rounded away, which slowly biases
+//! cumulative regrets and the average strategy. With the
+//! `compensated` feature enabled the rounding error from every
+//! addition is carried into the next one. Without it these are
+//! plain sums with no extra storage. Like the rest of a matcher's
+//! per action arrays the sums are stored inline for small
+//! decisions.
+use ndarray::{Array1, ArrayView1, NdFloat};
+use smallvec::SmallVec;
+
+use std::ops::Range;
+use std::vec::Vec;
+
+#[cfg(all(
+    not(feature = "compensated"),
+    any(feature = "f64", not(feature = "f64-accumulators"))
+))]
+use crate::kernels;
+use crate::scalar::{Accumulator, Scalar};
+use crate::small::Inline;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An array of running sums, stored inline for up to
+/// `INLINE_EXPERTS` of them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompensatedArray<T> {
+    values: Inline<T>,
+    // The low order bits lost by the last addition to each sum.
+    #[cfg(feature = "compensated")]
+    compensation: Inline<T>,
+}
+
+impl<T: NdFloat> CompensatedArray<T> {
+    #[must_use]
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            values: SmallVec::from_elem(T::zero(), len),
+            #[cfg(feature = "compensated")]
+            compensation: SmallVec::from_elem(T::zero(), len),
+        }
+    }
+
+    /// The current sums.
+    #[must_use]
+    pub fn values(&self) -> ArrayView1<'_, T> {
+        ArrayView1::from(&self.values[..])
+    }
+
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Grow or shrink to `len` sums, any new ones starting at zero.
+    pub fn resize(&mut self, len: usize) {
+        self.values.resize(len, T::zero());
+        #[cfg(feature = "compensated")]
+        {
+            self.compensation.resize(len, T::zero());
+        }
+    }
+
+    /// Every sum, borrowed so it can be updated in place.
+    pub fn view_mut(&mut self) -> CompensatedSliceMut<'_, T> {
+        self.slice_mut(0..self.len())
+    }
+
+    /// The sums in `range`, such as one matcher's share of a bank.
+    pub fn slice_mut(&mut self, range: Range<usize>) -> CompensatedSliceMut<'_, T> {
+        CompensatedSliceMut {
+            #[cfg(feature = "compensated")]
+            compensation: &mut self.compensation[range.clone()],
+            values: &mut self.values[range],
+        }
+    }
+
+    /// Add `value` to the sum at `index`.
+    pub fn add(&mut self, index: usize, value: T) {
+        self.view_mut().add(index, value);
+    }
+
+    /// Multiply every sum by `factor`.
+    pub fn scale(&mut self, factor: T) {
+        self.view_mut().scale(factor);
+    }
+
+    /// Multiply the sum at `index` by `factor`.
+    pub fn scale_at(&mut self, index: usize, factor: T) {
+        self.view_mut().scale_at(index, factor);
+    }
+
+    /// Set every sum to `value`.
+    pub fn fill(&mut self, value: T) {
+        self.view_mut().fill(value);
+    }
+}
+
+impl CompensatedArray<Accumulator> {
+    /// Add `alpha * x[i]` to the sum at each `i`.
+    pub fn scaled_add(&mut self, alpha: Accumulator, x: ArrayView1<Scalar>) {
+        self.view_mut().scaled_add(alpha, x);
+    }
+}
+
+impl<T: NdFloat> From<Array1<T>> for CompensatedArray<T> {
+    fn from(values: Array1<T>) -> Self {
+        Self::from(values.to_vec())
+    }
+}
+
+impl<T: NdFloat> From<Vec<T>> for CompensatedArray<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self {
+            #[cfg(feature = "compensated")]
+            compensation: SmallVec::from_elem(T::zero(), values.len()),
+            values: SmallVec::from_vec(values),
+        }
+    }
+}
+
+/// Running sums borrowed from a `CompensatedArray`.
+#[derive(Debug)]
+pub struct CompensatedSliceMut<'a, T> {
+    values: &'a mut [T],
+    #[cfg(feature = "compensated")]
+    compensation: &'a mut [T],
+}
+
+impl<T: NdFloat> CompensatedSliceMut<'_, T> {
+    /// The current sums.
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        self.values
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Add `value` to the sum at `index`.
+    pub fn add(&mut self, index: usize, value: T) {
+        #[cfg(feature = "compensated")]
+        {
+            kahan_add(
+                &mut self.values[index],
+                &mut self.compensation[index],
+                value,
+            );
+        }
+        #[cfg(not(feature = "compensated"))]
+        {
+            self.values[index] += value;
+        }
+    }
+
+    /// Multiply every sum by `factor`.
+    pub fn scale(&mut self, factor: T) {
+        self.values.iter_mut().for_each(|v| *v *= factor);
+        #[cfg(feature = "compensated")]
+        {
+            self.compensation.iter_mut().for_each(|c| *c *= factor);
+        }
+    }
+
+    /// Multiply the sum at `index` by `factor`.
+    pub fn scale_at(&mut self, index: usize, factor: T) {
+        self.values[index] *= factor;
+        #[cfg(feature = "compensated")]
+        {
+            self.compensation[index] *= factor;
+        }
+    }
+
+    /// Set every sum to `value`.
+    pub fn fill(&mut self, value: T) {
+        self.values.fill(value);
+        #[cfg(feature = "compensated")]
+        {
+            self.compensation.fill(T::zero());
+        }
+    }
+}
+
+impl CompensatedSliceMut<'_, Accumulator> {
+    /// Add `alpha * x[i]` to the sum at each `i`. When the sums are
+    /// plain scalars this is an axpy, so it goes through `kernels`.
+    pub fn scaled_add(&mut self, alpha: Accumulator, x: ArrayView1<Scalar>) {
+        #[cfg(all(
+            not(feature = "compensated"),
+            any(feature = "f64", not(feature = "f64-accumulators"))
+        ))]
+        if let Some(x) = x.as_slice() {
+            kernels::axpy(alpha, x, self.values);
+            return;
+        }
+        for (i, v) in x.iter().enumerate() {
+            self.add(i, alpha * *v as Accumulator);
+        }
+    }
+}
+
+/// A single running sum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Compensated<T> {
+    value: T,
+    // The low order bits lost by the last addition.
+    #[cfg(feature = "compensated")]
+    compensation: T,
+}
+
+impl<T: NdFloat> Compensated<T> {
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            #[cfg(feature = "compensated")]
+            compensation: T::zero(),
+        }
+    }
+
+    /// The current sum.
+    #[must_use]
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    pub fn add(&mut self, value: T) {
+        #[cfg(feature = "compensated")]
+        {
+            kahan_add(&mut self.value, &mut self.compensation, value);
+        }
+        #[cfg(not(feature = "compensated"))]
+        {
+            self.value += value;
+        }
+    }
+
+    /// Multiply the sum by `factor`.
+    pub fn scale(&mut self, factor: T) {
+        self.value *= factor;
+        #[cfg(feature = "compensated")]
+        {
+            self.compensation *= factor;
+        }
+    }
+}
+
+#[cfg(feature = "compensated")]
+#[inline]
+fn kahan_add<T: NdFloat>(sum: &mut T, compensation: &mut T, value: T) {
+    let y = value - *compensation;
+    let t = *sum + y;
+    *compensation = (t - *sum) - y;
+    *sum = t;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small::INLINE_EXPERTS;
+
+    #[test]
+    fn test_sums() {
+        let mut s = CompensatedArray::<f32>::zeros(2);
+        s.add(0, 1.5);
+        s.add(1, 2.0);
+        s.scale(2.0);
+        assert_eq!(s.values().to_vec(), vec![3.0, 4.0]);
+        s.fill(0.0);
+        assert_eq!(s.values().to_vec(), vec![0.0, 0.0]);
+        let mut s = CompensatedArray::<Accumulator>::zeros(2);
+        s.scaled_add(2.0, ArrayView1::from(&[1.0, -0.5]));
+        s.scaled_add(1.0, ArrayView1::from(&[1.0, 1.0]));
+        assert_eq!(s.values().to_vec(), vec![3.0, 0.0]);
+    }
+
+    #[test]
+    fn test_inline() {
+        assert!(!CompensatedArray::<f32>::zeros(INLINE_EXPERTS)
+            .values
+            .spilled());
+        assert!(CompensatedArray::<f32>::zeros(INLINE_EXPERTS + 1)
+            .values
+            .spilled());
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut s = CompensatedArray::<f32>::zeros(2);
+        s.resize(4);
+        let mut slice = s.slice_mut(1..3);
+        slice.add(0, 1.0);
+        slice.add(1, 2.0);
+        slice.scale(3.0);
+        assert_eq!(slice.values(), &[3.0, 6.0]);
+        assert_eq!(s.values().to_vec(), vec![0.0, 3.0, 6.0, 0.0]);
+    }
+
+    #[cfg(feature = "compensated")]
+    #[test]
+    fn test_small_terms_are_kept() {
+        let mut s = Compensated::new(1.0_f32);
+        for _i in 0..10_000 {
+            s.add(1e-8);
+        }
+        // Without compensation every one of these is rounded away.
+        assert!((s.value() - 1.0001).abs() < 1e-6);
+    }
+}
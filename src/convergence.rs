@@ -0,0 +1,126 @@
+//! Deciding when a solve is done. Training loops check in every so
+//! often with the current exploitability and average strategy, and
+//! stop once the chosen criterion says there's nothing more to gain
+//! instead of running a fixed number of iterations.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ConvergenceCriterion {
+    /// Stop once the exploitability is at most this.
+    Exploitability(Scalar),
+    /// Stop once the average strategy moves less than this, in L1
+    /// distance, between two checks.
+    StrategyChange(Scalar),
+    /// Stop once `patience` checks in a row have failed to improve
+    /// on the lowest exploitability seen by more than `min_delta`.
+    Patience { patience: usize, min_delta: Scalar },
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConvergenceReport {
+    pub iterations: usize,
+    /// The exploitability at the last check.
+    pub exploitability: Scalar,
+    /// False when the iteration limit was hit first.
+    pub converged: bool,
+}
+
+/// The state a criterion needs between checks.
+#[derive(Debug, Clone)]
+pub struct ConvergenceCheck {
+    criterion: ConvergenceCriterion,
+    best: Scalar,
+    stale_checks: usize,
+    last_strategy: Option<Vec<Scalar>>,
+}
+
+impl ConvergenceCheck {
+    pub fn new(criterion: ConvergenceCriterion) -> Result<Self, LittleError> {
+        let valid = match criterion {
+            ConvergenceCriterion::Exploitability(t) | ConvergenceCriterion::StrategyChange(t) => {
+                t >= 0.0
+            }
+            ConvergenceCriterion::Patience {
+                patience,
+                min_delta,
+            } => patience > 0 && min_delta >= 0.0,
+        };
+        if !valid {
+            return Err(LittleError::InvalidParameter(
+                "convergence thresholds must not be negative",
+            ));
+        }
+        Ok(Self {
+            criterion,
+            best: Scalar::INFINITY,
+            stale_checks: 0,
+            last_strategy: None,
+        })
+    }
+
+    /// Record a check and return whether the run has converged.
+    pub fn check(&mut self, exploitability: Scalar, strategy: &[Scalar]) -> bool {
+        match self.criterion {
+            ConvergenceCriterion::Exploitability(threshold) => exploitability <= threshold,
+            ConvergenceCriterion::StrategyChange(threshold) => {
+                let change = self.last_strategy.as_ref().map(|last| {
+                    last.iter()
+                        .zip(strategy)
+                        .map(|(a, b)| (a - b).abs())
+                        .sum::<Scalar>()
+                });
+                self.last_strategy = Some(strategy.to_vec());
+                change.is_some_and(|c| c < threshold)
+            }
+            ConvergenceCriterion::Patience {
+                patience,
+                min_delta,
+            } => {
+                if exploitability < self.best - min_delta {
+                    self.best = exploitability;
+                    self.stale_checks = 0;
+                } else {
+                    self.stale_checks += 1;
+                }
+                self.stale_checks >= patience
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_criteria() {
+        let mut threshold =
+            ConvergenceCheck::new(ConvergenceCriterion::Exploitability(0.1)).unwrap();
+        assert!(!threshold.check(0.2, &[]));
+        assert!(threshold.check(0.05, &[]));
+
+        let mut change = ConvergenceCheck::new(ConvergenceCriterion::StrategyChange(0.1)).unwrap();
+        assert!(!change.check(1.0, &[0.5, 0.5]));
+        assert!(!change.check(1.0, &[0.7, 0.3]));
+        assert!(change.check(1.0, &[0.72, 0.28]));
+
+        let mut patience = ConvergenceCheck::new(ConvergenceCriterion::Patience {
+            patience: 2,
+            min_delta: 0.01,
+        })
+        .unwrap();
+        assert!(!patience.check(1.0, &[]));
+        assert!(!patience.check(0.995, &[]));
+        assert!(patience.check(0.999, &[]));
+        assert!(ConvergenceCheck::new(ConvergenceCriterion::Exploitability(-1.0)).is_err());
+    }
+}
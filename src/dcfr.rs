@@ -0,0 +1,501 @@
+#![allow(clippy::cast_precision_loss)]
+//! Discounted CFR. Regret matching where, after every iteration,
+//! positive regrets, negative regrets and the average strategy are
+//! each shrunk by a factor given by `DiscountParams`. Earlier
+//! iterations, which were played with worse strategies, end up
+//! counting for less.
+//!
+//! Good exponents vary from game to game. `adaptive()` builds a
+//! matcher that picks between a handful of presets on its own,
+//! using a bandit that is rewarded for how little regret the
+//! strategies played under its chosen preset picked up. The bandit
+//! samples from its own seeded rng, see `with_seed`, so adaptive
+//! runs are reproducible.
+use ndarray::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use smallvec::SmallVec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::discount::DiscountParams;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::exp3::Exp3RegretMatcher;
+use crate::kernels;
+use crate::regret_minimizer::{
+    self, BanditMinimizer, MatcherState, RegretMinimizer, StateTransfer, UpdateOptions,
+};
+use crate::scalar::{self, Accumulator, Scalar};
+use crate::small::Inline;
+
+// How many updates each preset gets before the bandit is
+// told how it did and chooses again.
+const ADAPTIVE_EPOCH: usize = 10;
+const ADAPTIVE_GAMMA: Scalar = 0.1;
+const ADAPTIVE_SEED: u64 = 0x5EED;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct AdaptiveState {
+    presets: Vec<DiscountParams>,
+    bandit: Exp3RegretMatcher,
+    current: usize,
+    epoch_updates: usize,
+    // The undiscounted regret of each action over the current
+    // epoch, so presets are judged on what was played rather than
+    // on how hard they shrink the cumulative regret.
+    epoch_regret: Vec<Scalar>,
+    // The most regret the epoch could have picked up.
+    epoch_range: Scalar,
+    seed: u64,
+    // Seeds the rng for the bandit's next choice. Kept as a plain
+    // number so the matcher stays serializable.
+    rng_state: u64,
+}
+
+impl AdaptiveState {
+    fn new(
+        presets: Vec<DiscountParams>,
+        num_experts: usize,
+        seed: u64,
+    ) -> Result<Self, LittleError> {
+        let mut state = Self {
+            bandit: Exp3RegretMatcher::new(presets.len(), ADAPTIVE_GAMMA)?,
+            presets,
+            current: 0,
+            epoch_updates: 0,
+            epoch_regret: vec![0.0; num_experts],
+            epoch_range: 0.0,
+            seed,
+            rng_state: seed,
+        };
+        state.choose();
+        Ok(state)
+    }
+
+    fn observe(&mut self, rewards: ArrayView1<Scalar>, expected: Scalar, weight: Scalar) {
+        let mut lowest = Scalar::INFINITY;
+        let mut highest = Scalar::NEG_INFINITY;
+        for (e, r) in self.epoch_regret.iter_mut().zip(rewards) {
+            *e += weight * (r - expected);
+            lowest = lowest.min(*r);
+            highest = highest.max(*r);
+        }
+        self.epoch_range += weight * (highest - lowest);
+    }
+
+    // How well the epoch went, between 0 and 1. One minus the
+    // average regret per iteration relative to the most it could
+    // have been, which doesn't depend on the preset's discounting.
+    fn epoch_reward(&self) -> Scalar {
+        if self.epoch_range <= 0.0 {
+            return 0.5;
+        }
+        let regret = self
+            .epoch_regret
+            .iter()
+            .fold(0.0, |acc, r| Scalar::max(acc, *r));
+        1.0 - (regret / self.epoch_range).min(1.0)
+    }
+
+    // Let the bandit pick the preset for the next epoch.
+    fn choose(&mut self) -> DiscountParams {
+        let mut rng = StdRng::seed_from_u64(self.rng_state);
+        self.rng_state = rng.next_u64();
+        self.current = BanditMinimizer::next_action(&self.bandit, &mut rng);
+        self.presets[self.current]
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiscountedRegretMatcher {
+    params: DiscountParams,
+    cumulative_regret: CompensatedArray<Scalar>,
+    // The chance each expert has of being chosen
+    p: Inline<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average. None
+    // follows the gamma of the current discount params.
+    averaging: Option<AveragingScheme>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+    adaptive: Option<AdaptiveState>,
+}
+
+impl DiscountedRegretMatcher {
+    pub fn new_with_params(
+        num_experts: usize,
+        params: DiscountParams,
+    ) -> Result<Self, LittleError> {
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            params,
+            cumulative_regret: CompensatedArray::zeros(num_experts),
+            p: SmallVec::from_vec(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: None,
+            dist,
+            num_updates: 0,
+            adaptive: None,
+        })
+    }
+
+    /// DCFR with the parameters recommended by the paper.
+    pub fn recommended(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DiscountParams::recommended())
+    }
+
+    /// Linear CFR.
+    pub fn lcfr(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DiscountParams::lcfr())
+    }
+
+    /// DCFR that tunes its own parameters by choosing between
+    /// a few well known presets as it goes.
+    pub fn adaptive(num_experts: usize) -> Result<Self, LittleError> {
+        let presets = vec![
+            DiscountParams::recommended(),
+            DiscountParams::lcfr(),
+            DiscountParams::new(1.5, 0.5, 2.0)?,
+            DiscountParams::new(3.0, 0.0, 3.0)?,
+        ];
+        Self::adaptive_with_presets(num_experts, presets)
+    }
+
+    /// Like `adaptive` but choosing between the given presets.
+    pub fn adaptive_with_presets(
+        num_experts: usize,
+        presets: Vec<DiscountParams>,
+    ) -> Result<Self, LittleError> {
+        if presets.is_empty() {
+            return Err(LittleError::InvalidParameter(
+                "adaptive needs at least one preset",
+            ));
+        }
+        let state = AdaptiveState::new(presets, num_experts, ADAPTIVE_SEED)?;
+        let mut matcher = Self::new_with_params(num_experts, state.presets[state.current])?;
+        matcher.adaptive = Some(state);
+        Ok(matcher)
+    }
+
+    /// Seed the rng an adaptive matcher's bandit chooses presets
+    /// with, starting it over from its first choice. Does nothing
+    /// for a matcher with fixed parameters.
+    pub fn with_seed(mut self, seed: u64) -> Result<Self, LittleError> {
+        if let Some(state) = self.adaptive.as_mut() {
+            *state = AdaptiveState::new(state.presets.clone(), self.p.len(), seed)?;
+            self.params = state.presets[state.current];
+        }
+        Ok(self)
+    }
+
+    /// The parameters that will be used for the next update.
+    #[must_use]
+    pub fn params(&self) -> DiscountParams {
+        self.params
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.num_updates += 1;
+        let t = self.num_updates;
+
+        if options.contribute_to_average {
+            let averaging = RegretMinimizer::averaging(self);
+            averaging.accumulate_weighted(
+                t,
+                &mut self.sum_p,
+                ArrayView1::from(&self.p[..]),
+                options.average_weight,
+            );
+        }
+
+        let expected = kernels::dot_view(ArrayView1::from(&self.p[..]), reward_array);
+        if let Some(state) = self.adaptive.as_mut() {
+            state.observe(reward_array, expected, options.regret_weight);
+        }
+        let positive_factor = self.params.positive_factor(t);
+        let negative_factor = self.params.negative_factor(t);
+        for (i, r) in reward_array.iter().enumerate() {
+            self.cumulative_regret
+                .add(i, options.regret_weight * (r - expected));
+            let factor = if self.cumulative_regret.values()[i] >= 0.0 {
+                positive_factor
+            } else {
+                negative_factor
+            };
+            self.cumulative_regret.scale_at(i, factor);
+        }
+
+        regret_minimizer::regret_match(self.cumulative_regret.as_slice(), &mut self.p);
+        self.dist.set_weights(&self.p)?;
+        self.adapt()
+    }
+
+    fn adapt(&mut self) -> Result<(), LittleError> {
+        let Some(state) = self.adaptive.as_mut() else {
+            return Ok(());
+        };
+        state.epoch_updates += 1;
+        if state.epoch_updates < ADAPTIVE_EPOCH {
+            return Ok(());
+        }
+        let reward = state.epoch_reward();
+        state.bandit.update_sampled(state.current, reward)?;
+        state.epoch_updates = 0;
+        state.epoch_regret.fill(0.0);
+        state.epoch_range = 0.0;
+        self.params = state.choose();
+        Ok(())
+    }
+}
+
+impl RegretMinimizer for DiscountedRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::recommended(num_experts)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![
+            ("alpha", self.params.alpha),
+            ("beta", self.params.beta),
+            ("gamma", self.params.gamma),
+        ]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        RegretMinimizer::averaging(self).accumulate_strategy(
+            self.num_updates,
+            &mut self.sum_p,
+            strategy,
+            weight,
+        )
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        &self.p
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.values().to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = Some(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+            .unwrap_or(AveragingScheme::Discounted(self.params.gamma))
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        if let Some(state) = self.adaptive.as_mut() {
+            *state = AdaptiveState::new(state.presets.clone(), self.p.len(), state.seed)?;
+            self.params = state.presets[state.current];
+        }
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        regret_minimizer::fill_uniform(&mut self.p);
+        self.dist.set_weights(&self.p)?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.import_state(&MatcherState::from_strategy(strategy)?)?;
+        Ok(matcher)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.import_state(&MatcherState::from_regrets(regrets))?;
+        Ok(matcher)
+    }
+}
+
+impl StateTransfer for DiscountedRegretMatcher {
+    fn export_state(&self) -> MatcherState {
+        MatcherState {
+            num_updates: self.num_updates,
+            cumulative_regret: self.cumulative_regret.values().to_vec(),
+            cumulative_strategy: scalar::narrow(self.sum_p.values()),
+        }
+    }
+
+    fn import_state(&mut self, state: &MatcherState) -> Result<(), LittleError> {
+        regret_minimizer::check_state(state, self.p.len())?;
+        self.num_updates = state.num_updates;
+        self.cumulative_regret =
+            CompensatedArray::from(Array1::from(state.cumulative_regret.clone()));
+        self.sum_p = CompensatedArray::from(scalar::widen(&state.cumulative_strategy));
+        regret_minimizer::regret_match(self.cumulative_regret.as_slice(), &mut self.p);
+        self.dist.set_weights(&self.p)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_rps(
+        mut one: DiscountedRegretMatcher,
+        mut two: DiscountedRegretMatcher,
+    ) -> Vec<Scalar> {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        for _i in 0..2_000 {
+            let r1 = payoff.dot(&ArrayView1::from(two.current_strategy()));
+            let r2 = payoff.dot(&ArrayView1::from(one.current_strategy()));
+            one.update_regret(r1.as_slice().unwrap()).unwrap();
+            two.update_regret(r2.as_slice().unwrap()).unwrap();
+        }
+        one.best_weight()
+    }
+
+    #[test]
+    fn test_rps_recommended() {
+        let w = solve_rps(
+            DiscountedRegretMatcher::recommended(3).unwrap(),
+            DiscountedRegretMatcher::recommended(3).unwrap(),
+        );
+        for v in w {
+            assert!((v - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_rps_adaptive() {
+        let w = solve_rps(
+            DiscountedRegretMatcher::adaptive(3).unwrap(),
+            DiscountedRegretMatcher::adaptive(3).unwrap(),
+        );
+        for v in w {
+            assert!((v - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_prefers_better_preset() {
+        // Throwing away almost all regret after every iteration just
+        // chases the last reward, which this pattern punishes. It
+        // also keeps the cumulative regret tiny, so judging by that
+        // would pick it.
+        let forgetful = DiscountParams::new(-2.0, -2.0, 2.0).unwrap();
+        let presets = vec![forgetful, DiscountParams::recommended()];
+        let mut m = DiscountedRegretMatcher::adaptive_with_presets(2, presets).unwrap();
+        let mut recommended = 0;
+        for i in 0..4_000 {
+            let rewards = if i % 3 == 2 { [0.0, 1.0] } else { [1.0, 0.0] };
+            m.update_regret(&rewards).unwrap();
+            if i >= 2_000 && m.params() == DiscountParams::recommended() {
+                recommended += 1;
+            }
+        }
+        assert!(recommended > 1_500, "{recommended}");
+    }
+
+    #[test]
+    fn test_skip_average() {
+        let mut m = DiscountedRegretMatcher::recommended(2).unwrap();
+        m.update_regret(&[0.0, 0.0]).unwrap();
+        let skip = UpdateOptions {
+            contribute_to_average: false,
+            ..UpdateOptions::default()
+        };
+        m.update_regret_with_options(&[1.0, 0.0], skip).unwrap();
+        m.update_regret_with_options(&[1.0, 0.0], skip).unwrap();
+        // The regrets moved but the average only saw the first update.
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+        assert_eq!(m.best_weight(), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut m = DiscountedRegretMatcher::recommended(2).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.soft_reset().unwrap();
+        assert_eq!(m.current_strategy(), &[0.5, 0.5]);
+        // The average still remembers favoring the first action.
+        assert!(m.best_weight()[0] > 0.5);
+        m.reset().unwrap();
+        assert_eq!(m.best_weight(), vec![0.5, 0.5]);
+        assert_eq!(m.export_state(), MatcherState::from_regrets(&[0.0, 0.0]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut m = DiscountedRegretMatcher::adaptive(3).unwrap();
+        m.update_regret(&[1.0, 0.0, 0.5]).unwrap();
+        let json = serde_json::to_string(&m).unwrap();
+        let mut loaded: DiscountedRegretMatcher = serde_json::from_str(&json).unwrap();
+        m.update_regret(&[0.0, 1.0, 0.5]).unwrap();
+        loaded.update_regret(&[0.0, 1.0, 0.5]).unwrap();
+        assert_eq!(loaded.export_state(), m.export_state());
+        assert_eq!(loaded.current_strategy(), m.current_strategy());
+    }
+
+    #[test]
+    fn test_empty_presets() {
+        assert!(DiscountedRegretMatcher::adaptive_with_presets(3, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_reward_length() {
+        let mut m = DiscountedRegretMatcher::recommended(3).unwrap();
+        assert!(m.update_regret(&[1.0, 0.0]).is_err());
+        assert!(m.update_regret(&[1.0, 0.0, 0.0, 0.0]).is_err());
+        assert_eq!(m.num_updates(), 0);
+        assert_eq!(m.cumulative_regrets(), vec![0.0; 3]);
+    }
+}
@@ -0,0 +1,100 @@
+#![allow(clippy::cast_precision_loss)]
+//! Discounting schedules from Brown and Sandholm's
+//! "Solving Imperfect-Information Games via Discounted Regret
+//! Minimization". After iteration `t` positive regrets are
+//! multiplied by `t^alpha / (t^alpha + 1)`, negative regrets by
+//! `t^beta / (t^beta + 1)` and the average strategy by
+//! `(t / (t + 1))^gamma`.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiscountParams {
+    pub alpha: Scalar,
+    pub beta: Scalar,
+    pub gamma: Scalar,
+}
+
+impl DiscountParams {
+    pub fn new(alpha: Scalar, beta: Scalar, gamma: Scalar) -> Result<Self, LittleError> {
+        if !(alpha.is_finite() && beta.is_finite() && gamma.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "discount exponents must be finite",
+            ));
+        }
+        if gamma < 0.0 {
+            return Err(LittleError::InvalidParameter("gamma must not be negative"));
+        }
+        Ok(Self { alpha, beta, gamma })
+    }
+
+    /// The parameters the paper found to work best across games.
+    #[must_use]
+    pub fn recommended() -> Self {
+        Self {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 2.0,
+        }
+    }
+
+    /// Linear CFR. Iteration `t` is weighted by `t`.
+    #[must_use]
+    pub fn lcfr() -> Self {
+        Self {
+            alpha: 1.0,
+            beta: 1.0,
+            gamma: 1.0,
+        }
+    }
+
+    /// `t^exponent / (t^exponent + 1)`, the multiplier applied
+    /// to regrets after iteration `t`.
+    #[must_use]
+    pub fn discount_factor(t: usize, exponent: Scalar) -> Scalar {
+        let scaled = (t as Scalar).powf(exponent);
+        scaled / (scaled + 1.0)
+    }
+
+    /// Multiplier for positive regrets after iteration `t`.
+    #[must_use]
+    pub fn positive_factor(&self, t: usize) -> Scalar {
+        Self::discount_factor(t, self.alpha)
+    }
+
+    /// Multiplier for negative regrets after iteration `t`.
+    #[must_use]
+    pub fn negative_factor(&self, t: usize) -> Scalar {
+        Self::discount_factor(t, self.beta)
+    }
+
+    /// Multiplier for the cumulative strategy after iteration `t`.
+    #[must_use]
+    pub fn strategy_factor(&self, t: usize) -> Scalar {
+        (t as Scalar / (t as Scalar + 1.0)).powf(self.gamma)
+    }
+}
+
+impl Default for DiscountParams {
+    fn default() -> Self {
+        Self::recommended()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factors() {
+        let params = DiscountParams::lcfr();
+        assert_eq!(params.positive_factor(1), 0.5);
+        assert_eq!(params.negative_factor(3), 0.75);
+        assert_eq!(params.strategy_factor(1), 0.5);
+        assert_eq!(DiscountParams::discount_factor(4, 0.0), 0.5);
+    }
+}
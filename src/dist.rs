@@ -0,0 +1,193 @@
+#![allow(clippy::cast_precision_loss)]
+//! The distribution actions are sampled from. With only a few
+//! experts a linear walk over the cumulative weights is fastest and
+//! needs no setup. Past that an alias table keeps sampling constant
+//! time no matter how many experts there are. Building the table
+//! costs far more than an update, so it's only built the first time
+//! an action is sampled after the weights change. Until then only
+//! the weights are kept, inline for small decisions, which is also
+//! what gets serialized.
+use once_cell::sync::OnceCell;
+use rand::distributions::Distribution;
+use rand::Rng;
+use rand_distr::{WeightedAliasIndex, WeightedError};
+use smallvec::SmallVec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+use crate::small::Inline;
+
+/// Distributions over fewer experts than this sample by walking
+/// the cumulative weights instead of building an alias table.
+pub const CUMSUM_MAX_EXPERTS: usize = 16;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Vec<Scalar>", into = "Vec<Scalar>")
+)]
+pub struct StrategyDist {
+    weights: Inline<Scalar>,
+    total: Scalar,
+    // Empty whenever the weights have changed since the last sample.
+    alias: OnceCell<WeightedAliasIndex<Scalar>>,
+}
+
+// The same checks `WeightedAliasIndex::new` makes, so that once the
+// weights are accepted building the table can't fail. Returns the
+// total weight.
+fn check_weights(weights: &[Scalar]) -> Result<Scalar, WeightedError> {
+    if weights.is_empty() {
+        return Err(WeightedError::NoItem);
+    }
+    if weights.len() > u32::MAX as usize {
+        return Err(WeightedError::TooMany);
+    }
+    let max = Scalar::MAX / weights.len() as Scalar;
+    if !weights.iter().all(|w| (0.0..=max).contains(w)) {
+        return Err(WeightedError::InvalidWeight);
+    }
+    let total = weights.iter().sum::<Scalar>();
+    if total == 0.0 {
+        return Err(WeightedError::AllWeightsZero);
+    }
+    Ok(total)
+}
+
+// Rounding can leave the target just past the last positive
+// weight, in which case that's the one picked.
+fn walk<R: Rng + ?Sized>(weights: &[Scalar], total: Scalar, rng: &mut R) -> usize {
+    #[cfg(feature = "profiling")]
+    crate::profiling::record(|s| s.walk_samples += 1);
+    let mut target = rng.gen::<Scalar>() * total;
+    let mut last = 0;
+    for (i, w) in weights.iter().enumerate() {
+        if *w > 0.0 {
+            if target < *w {
+                return i;
+            }
+            target -= w;
+            last = i;
+        }
+    }
+    last
+}
+
+/// Sample an index in proportion to `weights` without building
+/// anything. Linear in the number of weights, which is the right
+/// trade when a strategy is only sampled once.
+pub fn sample_weights<R: Rng + ?Sized>(weights: &[Scalar], rng: &mut R) -> usize {
+    walk(weights, weights.iter().sum(), rng)
+}
+
+impl StrategyDist {
+    /// A distribution sampling each index in proportion to its
+    /// weight.
+    pub fn new(weights: Vec<Scalar>) -> Result<Self, LittleError> {
+        let total = check_weights(&weights)?;
+        Ok(Self {
+            weights: SmallVec::from_vec(weights),
+            total,
+            alias: OnceCell::new(),
+        })
+    }
+
+    /// Replace the weights, reusing the existing allocation. The
+    /// alias table is rebuilt on the next sample.
+    pub fn set_weights(&mut self, weights: &[Scalar]) -> Result<(), LittleError> {
+        self.total = check_weights(weights)?;
+        self.weights.clear();
+        self.weights.extend_from_slice(weights);
+        self.alias.take();
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn weights(&self) -> &[Scalar] {
+        &self.weights
+    }
+
+    fn alias(&self) -> &WeightedAliasIndex<Scalar> {
+        self.alias.get_or_init(|| {
+            #[cfg(feature = "profiling")]
+            crate::profiling::record(|s| s.dist_rebuilds += 1);
+            WeightedAliasIndex::new(self.weights.to_vec()).expect("weights were already checked")
+        })
+    }
+}
+
+impl Distribution<usize> for StrategyDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let action = if self.weights.len() < CUMSUM_MAX_EXPERTS {
+            walk(&self.weights, self.total, rng)
+        } else {
+            #[cfg(feature = "profiling")]
+            crate::profiling::record(|s| s.alias_samples += 1);
+            self.alias().sample(rng)
+        };
+        #[cfg(feature = "tracing")]
+        tracing::trace!(action, "sampled action");
+        action
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<Scalar>> for StrategyDist {
+    type Error = LittleError;
+
+    fn try_from(weights: Vec<Scalar>) -> Result<Self, Self::Error> {
+        Self::new(weights)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<StrategyDist> for Vec<Scalar> {
+    fn from(dist: StrategyDist) -> Self {
+        dist.weights.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_lazy_rebuild() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut weights = vec![0.0; CUMSUM_MAX_EXPERTS];
+        weights[0] = 1.0;
+        let mut dist = StrategyDist::new(weights.clone()).unwrap();
+        assert!(dist.alias.get().is_none());
+        assert_eq!(dist.sample(&mut rng), 0);
+        assert!(dist.alias.get().is_some());
+
+        weights.swap(0, 3);
+        dist.set_weights(&weights).unwrap();
+        assert!(dist.alias.get().is_none());
+        assert_eq!(dist.sample(&mut rng), 3);
+
+        assert!(dist.set_weights(&[0.0, 0.0]).is_err());
+        assert!(StrategyDist::new(vec![-1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_cumsum() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let dist = StrategyDist::new(vec![0.0, 1.0, 0.0, 3.0]).unwrap();
+        let mut counts = [0; 4];
+        for _i in 0..4_000 {
+            counts[dist.sample(&mut rng)] += 1;
+        }
+        assert!(dist.alias.get().is_none());
+        assert_eq!(counts[0] + counts[2], 0);
+        assert!((900..1_100).contains(&counts[1]));
+    }
+}
@@ -0,0 +1,253 @@
+#![allow(clippy::cast_precision_loss)]
+//! Restart a regret minimizer when the environment changes.
+//! The reward the wrapped minimizer expects to earn each round
+//! is fed to a Page-Hinkley test. When the test decides the mean
+//! has shifted the minimizer is reset and starts over,
+//! rather than slowly unlearning everything it knew.
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+
+const DEFAULT_DELTA: Scalar = 0.005;
+const DEFAULT_LAMBDA: Scalar = 50.0;
+
+/// Two sided Page-Hinkley change detector.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PageHinkley {
+    // Magnitude of change that is tolerated without alarm.
+    delta: Scalar,
+    // Alarm threshold.
+    lambda: Scalar,
+    mean: Scalar,
+    count: usize,
+    // Cumulative deviations for detecting an increase and a decrease.
+    up: Scalar,
+    min_up: Scalar,
+    down: Scalar,
+    max_down: Scalar,
+}
+
+impl PageHinkley {
+    pub fn new(delta: Scalar, lambda: Scalar) -> Result<Self, LittleError> {
+        if !(delta >= 0.0 && delta.is_finite()) {
+            return Err(LittleError::InvalidParameter("delta must not be negative"));
+        }
+        if !(lambda > 0.0 && lambda.is_finite()) {
+            return Err(LittleError::InvalidParameter("lambda must be positive"));
+        }
+        Ok(Self {
+            delta,
+            lambda,
+            mean: 0.0,
+            count: 0,
+            up: 0.0,
+            min_up: 0.0,
+            down: 0.0,
+            max_down: 0.0,
+        })
+    }
+
+    /// Add an observation. Returns true if a change was detected.
+    pub fn observe(&mut self, x: Scalar) -> bool {
+        self.count += 1;
+        self.mean += (x - self.mean) / self.count as Scalar;
+
+        self.up += x - self.mean - self.delta;
+        self.min_up = self.min_up.min(self.up);
+        self.down += x - self.mean + self.delta;
+        self.max_down = self.max_down.max(self.down);
+
+        self.up - self.min_up > self.lambda || self.max_down - self.down > self.lambda
+    }
+
+    /// Forget everything seen so far.
+    pub fn reset(&mut self) {
+        self.mean = 0.0;
+        self.count = 0;
+        self.up = 0.0;
+        self.min_up = 0.0;
+        self.down = 0.0;
+        self.max_down = 0.0;
+    }
+}
+
+impl Default for PageHinkley {
+    fn default() -> Self {
+        Self::new(DEFAULT_DELTA, DEFAULT_LAMBDA).unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DriftRestart<M: RegretMinimizer> {
+    inner: M,
+    detector: PageHinkley,
+    num_restarts: usize,
+}
+
+impl<M: RegretMinimizer> DriftRestart<M> {
+    #[must_use]
+    pub fn new_with_params(inner: M, detector: PageHinkley) -> Self {
+        Self {
+            inner,
+            detector,
+            num_restarts: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// How many times a change was detected and the inner
+    /// minimizer was started over.
+    #[must_use]
+    pub fn num_restarts(&self) -> usize {
+        self.num_restarts
+    }
+}
+
+impl<M: RegretMinimizer> RegretMinimizer for DriftRestart<M> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(
+            M::new(num_experts)?,
+            PageHinkley::default(),
+        ))
+    }
+
+    fn num_experts(&self) -> usize {
+        self.inner.num_experts()
+    }
+
+    /// Updates since the last restart.
+    fn num_updates(&self) -> usize {
+        self.inner.num_updates()
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        self.inner.hyperparameters()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.inner.next_action(rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.inner.num_experts())?;
+        let expected: Scalar = self
+            .inner
+            .current_strategy()
+            .iter()
+            .zip(rewards)
+            .map(|(p, r)| p * r)
+            .sum();
+        if self.detector.observe(expected) {
+            self.inner.reset()?;
+            self.detector.reset();
+            self.num_restarts += 1;
+        }
+        self.inner.update_regret_with_options(rewards, options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.inner.accumulate_average(strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.inner.current_strategy()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.inner.cumulative_regrets()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.inner = self.inner.with_averaging(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.inner.averaging()
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.detector.reset();
+        self.num_restarts = 0;
+        self.inner.reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.detector.reset();
+        self.inner.soft_reset()
+    }
+
+    /// A restart after drift still starts from scratch.
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(
+            M::warm_start_from_strategy(strategy)?,
+            PageHinkley::default(),
+        ))
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(
+            M::warm_start_from_regrets(regrets)?,
+            PageHinkley::default(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+
+    #[test]
+    fn test_page_hinkley_detects_shift() {
+        let mut ph = PageHinkley::new(0.01, 5.0).unwrap();
+        for _i in 0..200 {
+            assert!(!ph.observe(0.0));
+        }
+        let detected = (0..200).any(|_| ph.observe(1.0));
+        assert!(detected);
+    }
+
+    #[test]
+    fn test_restarts_on_switch() {
+        let mut m: DriftRestart<RegretMatcher> = DriftRestart::new_with_params(
+            RegretMatcher::new(2).unwrap(),
+            PageHinkley::new(0.01, 5.0).unwrap(),
+        );
+        for _i in 0..500 {
+            m.update_regret(&[1.0, 0.0]).unwrap();
+        }
+        assert_eq!(m.num_restarts(), 0);
+        for _i in 0..50 {
+            m.update_regret(&[0.0, 1.0]).unwrap();
+        }
+        assert!(m.num_restarts() > 0);
+        assert!(m.current_strategy()[1] > 0.9);
+    }
+}
@@ -6,6 +6,46 @@ pub enum LittleError {
     #[error("error with weigths")]
     Weights(#[from] WeightedError),
 
+    #[error("action {action} is out of range for {num_experts} experts")]
+    ActionOutOfRange { action: usize, num_experts: usize },
+
+    #[error("invalid parameter: {0}")]
+    InvalidParameter(&'static str),
+
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "checkpoint")]
+    #[error("error encoding checkpoint")]
+    Encoding(#[from] bincode::Error),
+
+    #[cfg(feature = "arrow")]
+    #[error("arrow error")]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    #[cfg(feature = "arrow")]
+    #[error("parquet error")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[cfg(feature = "plot")]
+    #[error("plotting error: {0}")]
+    Plot(std::string::String),
+
+    #[cfg(feature = "mmap")]
+    #[error("invalid strategy store: {0}")]
+    InvalidStore(&'static str),
+
+    #[cfg(feature = "lp")]
+    #[error("linear program error")]
+    Lp(#[from] minilp::Error),
+
+    #[cfg(feature = "candle")]
+    #[error("candle error")]
+    Candle(#[from] candle_core::Error),
+
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(&'static str),
+
     #[error("unknown little-sorry error")]
     Unknown,
 }
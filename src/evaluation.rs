@@ -0,0 +1,185 @@
+#![allow(clippy::cast_precision_loss)]
+//! How well one strategy does against another in a matrix game,
+//! either exactly from the strategies or estimated from sampled
+//! play. Throughout `payoff[[a, b]]` is the reward for playing `a`
+//! when the opponent plays `b`.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist;
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+fn check_shapes(
+    payoff: ArrayView2<Scalar>,
+    strategy_a: &[Scalar],
+    strategy_b: &[Scalar],
+) -> Result<(), LittleError> {
+    if payoff.dim() != (strategy_a.len(), strategy_b.len()) {
+        return Err(LittleError::InvalidParameter(
+            "payoff must have a row per action of a and a column per action of b",
+        ));
+    }
+    Ok(())
+}
+
+/// The expected reward for playing `strategy_a` against
+/// `strategy_b`.
+pub fn expected_value(
+    payoff: ArrayView2<Scalar>,
+    strategy_a: &[Scalar],
+    strategy_b: &[Scalar],
+) -> Result<Scalar, LittleError> {
+    check_shapes(payoff, strategy_a, strategy_b)?;
+    Ok(ArrayView1::from(strategy_a).dot(&payoff.dot(&ArrayView1::from(strategy_b))))
+}
+
+/// The variance of the reward from a single round of
+/// `strategy_a` against `strategy_b`.
+pub fn variance(
+    payoff: ArrayView2<Scalar>,
+    strategy_a: &[Scalar],
+    strategy_b: &[Scalar],
+) -> Result<Scalar, LittleError> {
+    let mean = expected_value(payoff, strategy_a, strategy_b)?;
+    let squared = payoff.mapv(|x| (x - mean) * (x - mean));
+    expected_value(squared.view(), strategy_a, strategy_b)
+}
+
+/// Running mean and variance of sampled rewards, using Welford's
+/// method so long runs don't lose precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SampleStats {
+    count: usize,
+    mean: Scalar,
+    // Sum of squared differences from the mean.
+    m2: Scalar,
+}
+
+impl SampleStats {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, x: Scalar) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as Scalar;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Combine with the samples in `other`, as if they'd all been
+    /// pushed here.
+    pub fn merge(&mut self, other: &SampleStats) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = other.count as Scalar / count as Scalar;
+        self.mean += delta * weight;
+        self.m2 += other.m2 + delta * delta * self.count as Scalar * weight;
+        self.count = count;
+    }
+
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Scalar {
+        self.mean
+    }
+
+    /// The unbiased sample variance, zero with fewer than two
+    /// samples.
+    #[must_use]
+    pub fn variance(&self) -> Scalar {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as Scalar
+        }
+    }
+
+    /// The standard error of the mean.
+    #[must_use]
+    pub fn standard_error(&self) -> Scalar {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.variance() / self.count as Scalar).sqrt()
+        }
+    }
+}
+
+/// Play `rounds` sampled rounds of `strategy_a` against
+/// `strategy_b` and collect the rewards.
+pub fn sample_play<R: Rng>(
+    payoff: ArrayView2<Scalar>,
+    strategy_a: &[Scalar],
+    strategy_b: &[Scalar],
+    rounds: usize,
+    rng: &mut R,
+) -> Result<SampleStats, LittleError> {
+    check_shapes(payoff, strategy_a, strategy_b)?;
+    let mut stats = SampleStats::new();
+    for _i in 0..rounds {
+        let a = dist::sample_weights(strategy_a, rng);
+        let b = dist::sample_weights(strategy_b, rng);
+        stats.push(payoff[[a, b]]);
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_exact_values() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let uniform = [1.0 / 3.0; 3];
+        assert!(
+            expected_value(payoff.view(), &uniform, &uniform)
+                .unwrap()
+                .abs()
+                < 1e-6
+        );
+        // Paper against rock always wins.
+        let ev = expected_value(payoff.view(), &[0.0, 1.0, 0.0], &[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(ev, 1.0);
+        // Against uniform two thirds of rounds are decided.
+        let var = variance(payoff.view(), &[1.0, 0.0, 0.0], &uniform).unwrap();
+        assert!((var - 2.0 / 3.0).abs() < 1e-6);
+        assert!(expected_value(payoff.view(), &[1.0, 0.0], &uniform).is_err());
+    }
+
+    #[test]
+    fn test_sampled_play_matches() {
+        let payoff = array![[3.0, 0.0], [5.0, 1.0]];
+        let (a, b) = ([0.25, 0.75], [0.5, 0.5]);
+        let mut rng = StdRng::seed_from_u64(7);
+        let stats = sample_play(payoff.view(), &a, &b, 20_000, &mut rng).unwrap();
+        let ev = expected_value(payoff.view(), &a, &b).unwrap();
+        let var = variance(payoff.view(), &a, &b).unwrap();
+        assert_eq!(stats.count(), 20_000);
+        assert!((stats.mean() - ev).abs() < 4.0 * stats.standard_error());
+        assert!((stats.variance() - var).abs() < 0.05 * var);
+        assert!((stats.standard_error() - (var / 20_000.0).sqrt()).abs() < 1e-3);
+
+        let more = sample_play(payoff.view(), &a, &b, 1_000, &mut rng).unwrap();
+        let mut merged = stats;
+        merged.merge(&more);
+        assert_eq!(merged.count(), 21_000);
+        assert!((merged.variance() - var).abs() < 0.05 * var);
+    }
+}
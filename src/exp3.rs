@@ -0,0 +1,188 @@
+#![allow(clippy::cast_precision_loss)]
+//! EXP3 for the adversarial bandit setting. Unlike the full
+//! information matchers only the reward of the action that
+//! was actually played is known each round.
+use ndarray::prelude::*;
+use rand::distributions::Distribution;
+use rand::{thread_rng, Rng};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::{self, Scalar};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Exp3RegretMatcher {
+    // Exploration rate. Every action gets at least gamma / K probability.
+    gamma: Scalar,
+    // Log of the exponential weights. Kept in log space so that
+    // long runs don't overflow.
+    log_weights: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl Exp3RegretMatcher {
+    /// Create a new EXP3 matcher with the exploration rate `gamma`.
+    /// `gamma` must be in `(0, 1]`.
+    pub fn new(num_experts: usize, gamma: Scalar) -> Result<Self, LittleError> {
+        if !(gamma > 0.0 && gamma <= 1.0) {
+            return Err(LittleError::InvalidParameter("gamma must be in (0, 1]"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            gamma,
+            log_weights: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// Create a matcher with the exploration rate that minimizes
+    /// the regret bound for a known horizon.
+    pub fn with_horizon(num_experts: usize, horizon: usize) -> Result<Self, LittleError> {
+        let k = num_experts as Scalar;
+        let gamma = Scalar::min(
+            1.0,
+            ((k * k.ln().max(1.0)) / ((scalar::consts::E - 1.0) * horizon.max(1) as Scalar)).sqrt(),
+        );
+        Self::new(num_experts, gamma)
+    }
+
+    /// A matcher with exploration rate `gamma` that starts out
+    /// playing `strategy`, mixed with the exploration, as if it had
+    /// already been played once.
+    pub fn warm_start_from_strategy(
+        strategy: &[Scalar],
+        gamma: Scalar,
+    ) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::new(strategy.len(), gamma)?;
+        matcher.log_weights = Array1::from(regret_minimizer::log_strategy(&strategy));
+        matcher.play_log_weights()?;
+        matcher.sum_p = Array1::from(strategy);
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    /// A matcher with exploration rate `gamma` whose log weights
+    /// start out as `regrets` scaled the way estimated rewards are.
+    pub fn warm_start_from_regrets(regrets: &[Scalar], gamma: Scalar) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len(), gamma)?;
+        let scale = gamma / regrets.len() as Scalar;
+        matcher.log_weights = regrets.iter().map(|r| r * scale).collect();
+        matcher.play_log_weights()?;
+        Ok(matcher)
+    }
+
+    pub fn next_action(&self) -> usize {
+        self.dist.sample(&mut thread_rng())
+    }
+
+    /// Update with bandit feedback. `reward` is the reward received
+    /// for playing `action` and should be in `[0, 1]`.
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        let num_experts = self.p.len();
+        regret_minimizer::check_action(action, num_experts)?;
+        // Importance weight the reward so that the estimate
+        // is unbiased for every action, not just the sampled one.
+        let estimated_reward = reward / self.p[action];
+        self.log_weights[action] += self.gamma * estimated_reward / num_experts as Scalar;
+        self.play_log_weights()?;
+        self.sum_p += &self.p;
+        self.num_updates += 1;
+        Ok(())
+    }
+
+    fn play_log_weights(&mut self) -> Result<(), LittleError> {
+        regret_minimizer::softmax(
+            self.log_weights.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        // Mix in uniform exploration so that no action's
+        // probability (and so no estimate) blows up.
+        let explore = self.gamma / self.p.len() as Scalar;
+        self.p.mapv_inplace(|w| (1.0 - self.gamma) * w + explore);
+        self.dist.set_weights(self.p.as_slice().unwrap())
+    }
+
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return self.p.to_vec();
+        }
+        (self.sum_p.clone() / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for Exp3RegretMatcher {
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_gamma() {
+        assert!(Exp3RegretMatcher::new(3, 0.0).is_err());
+        assert!(Exp3RegretMatcher::new(3, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut m = Exp3RegretMatcher::new(3, 0.1).unwrap();
+        assert!(m.update_sampled(3, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let m = Exp3RegretMatcher::warm_start_from_strategy(&[3.0, 1.0], 0.1).unwrap();
+        assert_eq!(m.best_weight(), vec![0.75, 0.25]);
+        assert!((m.p[0] - (0.9 * 0.75 + 0.05)).abs() < 1e-5);
+        assert!(Exp3RegretMatcher::warm_start_from_strategy(&[0.0, 0.0], 0.1).is_err());
+        let m = Exp3RegretMatcher::warm_start_from_regrets(&[10.0, 0.0], 0.1).unwrap();
+        assert!(m.p[0] > m.p[1]);
+        assert_eq!(m.num_updates, 0);
+    }
+
+    #[test]
+    fn test_finds_best_arm() {
+        let mut m = Exp3RegretMatcher::with_horizon(3, 5_000).unwrap();
+        for _i in 0..5_000 {
+            let a = m.next_action();
+            let reward = if a == 2 { 1.0 } else { 0.0 };
+            m.update_sampled(a, reward).unwrap();
+        }
+        let w = m.best_weight();
+        assert!(w[2] > w[0]);
+        assert!(w[2] > w[1]);
+    }
+}
@@ -0,0 +1,183 @@
+#![allow(clippy::cast_precision_loss)]
+//! EXP3-IX. EXP3 without the explicit uniform exploration.
+//! Instead the importance weighted loss estimate is biased
+//! slightly downward by adding `gamma` to the denominator. This
+//! implicit exploration keeps the variance of the estimates in
+//! check and gives regret bounds that hold with high probability
+//! rather than only in expectation.
+use ndarray::prelude::*;
+use rand::distributions::Distribution;
+use rand::{thread_rng, Rng};
+
+use std::vec::Vec;
+
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone)]
+pub struct Exp3IxRegretMatcher {
+    // Learning rate
+    eta: Scalar,
+    // Implicit exploration. Added to the probability when
+    // importance weighting.
+    gamma: Scalar,
+    // Estimated total loss for each expert.
+    estimated_loss: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl Exp3IxRegretMatcher {
+    /// Create a new EXP3-IX matcher with learning rate `eta`
+    /// and implicit exploration `gamma`.
+    pub fn new(num_experts: usize, eta: Scalar, gamma: Scalar) -> Result<Self, LittleError> {
+        if !(eta > 0.0 && eta.is_finite()) {
+            return Err(LittleError::InvalidParameter("eta must be positive"));
+        }
+        if !(gamma >= 0.0 && gamma.is_finite()) {
+            return Err(LittleError::InvalidParameter("gamma must not be negative"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            eta,
+            gamma,
+            estimated_loss: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// Create a matcher with the parameters suggested by Neu (2015)
+    /// for a known horizon, `gamma = eta / 2`.
+    pub fn with_horizon(num_experts: usize, horizon: usize) -> Result<Self, LittleError> {
+        let k = num_experts as Scalar;
+        let eta = ((2.0 * k.ln().max(1.0)) / (k * horizon.max(1) as Scalar)).sqrt();
+        Self::new(num_experts, eta, eta / 2.0)
+    }
+
+    /// A matcher with learning rate `eta` and implicit exploration
+    /// `gamma` that starts out playing `strategy`, as if it had
+    /// already been played once.
+    pub fn warm_start_from_strategy(
+        strategy: &[Scalar],
+        eta: Scalar,
+        gamma: Scalar,
+    ) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::new(strategy.len(), eta, gamma)?;
+        matcher.estimated_loss = regret_minimizer::log_strategy(&strategy)
+            .into_iter()
+            .map(|l| -l / eta)
+            .collect();
+        matcher.play_losses()?;
+        matcher.sum_p = Array1::from(strategy);
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    /// A matcher with learning rate `eta` and implicit exploration
+    /// `gamma` whose estimated losses start out as `-regrets`.
+    pub fn warm_start_from_regrets(
+        regrets: &[Scalar],
+        eta: Scalar,
+        gamma: Scalar,
+    ) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len(), eta, gamma)?;
+        matcher.estimated_loss = regrets.iter().map(|r| -r).collect();
+        matcher.play_losses()?;
+        Ok(matcher)
+    }
+
+    pub fn next_action(&self) -> usize {
+        self.dist.sample(&mut thread_rng())
+    }
+
+    /// Update with bandit feedback. `reward` is the reward received
+    /// for playing `action` and should be in `[0, 1]`.
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.p.len())?;
+        self.sum_p += &self.p;
+        self.num_updates += 1;
+
+        // EXP3-IX is stated in terms of losses.
+        let loss = 1.0 - reward;
+        self.estimated_loss[action] += loss / (self.p[action] + self.gamma);
+        self.play_losses()
+    }
+
+    fn play_losses(&mut self) -> Result<(), LittleError> {
+        let logits = self.estimated_loss.mapv(|l| -self.eta * l);
+        regret_minimizer::softmax(logits.as_slice().unwrap(), self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())
+    }
+
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return self.p.to_vec();
+        }
+        (self.sum_p.clone() / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for Exp3IxRegretMatcher {
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_params() {
+        assert!(Exp3IxRegretMatcher::new(3, 0.0, 0.1).is_err());
+        assert!(Exp3IxRegretMatcher::new(3, 0.1, -0.1).is_err());
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let m = Exp3IxRegretMatcher::warm_start_from_strategy(&[3.0, 1.0], 0.5, 0.1).unwrap();
+        assert_eq!(m.best_weight(), vec![0.75, 0.25]);
+        assert!((m.p[0] - 0.75).abs() < 1e-5);
+        assert!(Exp3IxRegretMatcher::warm_start_from_strategy(&[-1.0, 2.0], 0.5, 0.1).is_err());
+        let m = Exp3IxRegretMatcher::warm_start_from_regrets(&[0.0, 4.0], 0.5, 0.1).unwrap();
+        assert!(m.p[1] > m.p[0]);
+        assert_eq!(m.num_updates, 0);
+    }
+
+    #[test]
+    fn test_finds_best_arm() {
+        let mut m = Exp3IxRegretMatcher::with_horizon(3, 5_000).unwrap();
+        for _i in 0..5_000 {
+            let a = m.next_action();
+            let reward = if a == 1 { 0.9 } else { 0.1 };
+            m.update_sampled(a, reward).unwrap();
+        }
+        let w = m.best_weight();
+        assert!(w[1] > w[0]);
+        assert!(w[1] > w[2]);
+    }
+}
@@ -0,0 +1,239 @@
+#![allow(clippy::cast_precision_loss)]
+//! EXP4. Each round a set of experts each give advice in the
+//! form of a distribution over actions. An action is sampled
+//! from the weighted mix of that advice and only its reward is
+//! seen. Weights are kept over the experts, not the actions, so
+//! the set of actions can mean something different every round.
+use ndarray::prelude::*;
+use rand::distributions::Distribution;
+use rand::{thread_rng, Rng};
+
+use std::vec::Vec;
+
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer;
+use crate::scalar::Scalar;
+
+// How far each expert's advice can be from summing to one.
+const ADVICE_TOLERANCE: Scalar = 1e-4;
+
+#[derive(Debug, Clone)]
+pub struct Exp4RegretMatcher {
+    num_actions: usize,
+    // Exploration rate over actions.
+    gamma: Scalar,
+    // Log of the weight of each expert.
+    log_weights: Array1<Scalar>,
+    // Normalized weight of each expert.
+    q: Array1<Scalar>,
+    sum_q: Array1<Scalar>,
+    // The advice for the current round and the resulting
+    // distribution over actions.
+    advice: Array2<Scalar>,
+    p: Array1<Scalar>,
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl Exp4RegretMatcher {
+    /// Create a matcher over `num_experts` advisors each giving
+    /// advice on `num_actions` actions. `gamma` must be in `(0, 1]`.
+    pub fn new(num_experts: usize, num_actions: usize, gamma: Scalar) -> Result<Self, LittleError> {
+        if !(gamma > 0.0 && gamma <= 1.0) {
+            return Err(LittleError::InvalidParameter("gamma must be in (0, 1]"));
+        }
+        let p = regret_minimizer::uniform(num_actions);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            num_actions,
+            gamma,
+            log_weights: Array1::zeros(num_experts),
+            q: Array1::from(regret_minimizer::uniform(num_experts)),
+            sum_q: Array1::zeros(num_experts),
+            advice: Array2::from_elem((num_experts, num_actions), 1.0 / num_actions as Scalar),
+            p: Array1::from(p),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// A matcher whose expert weights start out as `weights`, as
+    /// if they had already been used once.
+    pub fn warm_start_from_strategy(
+        weights: &[Scalar],
+        num_actions: usize,
+        gamma: Scalar,
+    ) -> Result<Self, LittleError> {
+        let weights = regret_minimizer::normalize_strategy(weights)?;
+        let mut matcher = Self::new(weights.len(), num_actions, gamma)?;
+        matcher.log_weights = Array1::from(regret_minimizer::log_strategy(&weights));
+        matcher.q = Array1::from(weights.clone());
+        matcher.sum_q = Array1::from(weights);
+        matcher.num_updates = 1;
+        matcher.mix()?;
+        Ok(matcher)
+    }
+
+    /// A matcher whose expert log weights start out as `regrets`
+    /// scaled the way estimated rewards are.
+    pub fn warm_start_from_regrets(
+        regrets: &[Scalar],
+        num_actions: usize,
+        gamma: Scalar,
+    ) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len(), num_actions, gamma)?;
+        let scale = gamma / num_actions as Scalar;
+        matcher.log_weights = regrets.iter().map(|r| r * scale).collect();
+        regret_minimizer::softmax(
+            matcher.log_weights.as_slice().unwrap(),
+            matcher.q.as_slice_mut().unwrap(),
+        );
+        matcher.mix()?;
+        Ok(matcher)
+    }
+
+    #[must_use]
+    pub fn num_experts(&self) -> usize {
+        self.q.len()
+    }
+
+    #[must_use]
+    pub fn num_actions(&self) -> usize {
+        self.num_actions
+    }
+
+    /// Set the advice for this round. Row `i` is expert `i`'s
+    /// distribution over actions.
+    pub fn set_advice(&mut self, advice: ArrayView2<Scalar>) -> Result<(), LittleError> {
+        if advice.dim() != self.advice.dim() {
+            return Err(LittleError::InvalidParameter(
+                "advice must be num_experts x num_actions",
+            ));
+        }
+        for row in advice.rows() {
+            if row.iter().any(|a| !(a.is_finite() && *a >= 0.0))
+                || (row.sum() - 1.0).abs() > ADVICE_TOLERANCE
+            {
+                return Err(LittleError::InvalidParameter(
+                    "each expert's advice must be a distribution over actions",
+                ));
+            }
+        }
+        self.advice.assign(&advice);
+        self.mix()
+    }
+
+    // Mix the advice by expert weight then add exploration.
+    fn mix(&mut self) -> Result<(), LittleError> {
+        let mixed = self.q.dot(&self.advice);
+        let explore = self.gamma / self.num_actions as Scalar;
+        self.p = mixed.mapv(|v| (1.0 - self.gamma) * v + explore);
+        self.dist.set_weights(self.p.as_slice().unwrap())
+    }
+
+    pub fn next_action(&self) -> usize {
+        self.dist.sample(&mut thread_rng())
+    }
+
+    pub fn next_action_with_rng<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    /// Update with bandit feedback for the advice set by the last
+    /// call to `set_advice`. `reward` should be in `[0, 1]`. Until
+    /// new advice is set the same advice is mixed with the updated
+    /// expert weights.
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.num_actions)?;
+        self.sum_q += &self.q;
+        self.num_updates += 1;
+
+        let estimated_reward = reward / self.p[action];
+        // Each expert is credited by how much of its advice
+        // went to the action that was played.
+        let scale = self.gamma / self.num_actions as Scalar * estimated_reward;
+        self.log_weights
+            .scaled_add(scale, &self.advice.column(action));
+        regret_minimizer::softmax(
+            self.log_weights.as_slice().unwrap(),
+            self.q.as_slice_mut().unwrap(),
+        );
+        self.mix()
+    }
+
+    /// The current weight of each expert.
+    #[must_use]
+    pub fn expert_weights(&self) -> Vec<Scalar> {
+        self.q.to_vec()
+    }
+
+    /// The average weight of each expert over all updates.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return self.q.to_vec();
+        }
+        (self.sum_q.clone() / self.num_updates as Scalar).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_advice_shape() {
+        let mut m = Exp4RegretMatcher::new(2, 3, 0.1).unwrap();
+        assert!(m.set_advice(Array2::zeros((3, 2)).view()).is_err());
+        // Each row has to be a distribution.
+        assert!(m.set_advice(Array2::zeros((2, 3)).view()).is_err());
+        let mut advice = Array2::from_elem((2, 3), 1.0 / 3.0);
+        advice[[1, 0]] = -1.0 / 3.0;
+        advice[[1, 1]] = 1.0;
+        assert!(m.set_advice(advice.view()).is_err());
+        advice[[1, 0]] = Scalar::NAN;
+        assert!(m.set_advice(advice.view()).is_err());
+    }
+
+    #[test]
+    fn test_update_remixes_advice() {
+        let mut m = Exp4RegretMatcher::new(2, 2, 0.1).unwrap();
+        m.set_advice(array![[1.0, 0.0], [0.0, 1.0]].view()).unwrap();
+        assert_eq!(m.p.to_vec(), vec![0.5, 0.5]);
+        m.update_sampled(0, 1.0).unwrap();
+        // Expert zero now has more weight, and so does its advice.
+        assert!(m.p[0] > 0.5);
+        let total: Scalar = m.p.sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let m = Exp4RegretMatcher::warm_start_from_strategy(&[1.0, 3.0], 2, 0.1).unwrap();
+        assert_eq!(m.expert_weights(), vec![0.25, 0.75]);
+        assert_eq!(m.best_weight(), vec![0.25, 0.75]);
+        assert!(Exp4RegretMatcher::warm_start_from_strategy(&[0.0, 0.0], 2, 0.1).is_err());
+        let m = Exp4RegretMatcher::warm_start_from_regrets(&[50.0, 0.0], 2, 0.1).unwrap();
+        assert!(m.expert_weights()[0] > m.expert_weights()[1]);
+        assert_eq!(m.num_updates, 0);
+    }
+
+    #[test]
+    fn test_trusts_good_expert() {
+        let mut m = Exp4RegretMatcher::new(2, 2, 0.1).unwrap();
+        for i in 0..5_000 {
+            // The best action changes every round. Expert zero
+            // always knows which one it is, expert one is always wrong.
+            let best = i % 2;
+            let mut advice = Array2::zeros((2, 2));
+            advice[[0, best]] = 1.0;
+            advice[[1, 1 - best]] = 1.0;
+            m.set_advice(advice.view()).unwrap();
+            let a = m.next_action();
+            let reward = if a == best { 1.0 } else { 0.0 };
+            m.update_sampled(a, reward).unwrap();
+        }
+        assert!(m.expert_weights()[0] > 0.9);
+    }
+}
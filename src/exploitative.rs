@@ -0,0 +1,144 @@
+//! Deploying a trained matcher against a particular opponent. The
+//! agent models the opponent from their actions and plays a mixture
+//! of the matcher's average strategy, which is safe, and the best
+//! response to the model, which wins the most if the opponent keeps
+//! playing the way they have been.
+use ndarray::prelude::*;
+use rand::Rng;
+
+use std::vec::Vec;
+
+use crate::best_response;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::opponent_model::OpponentModel;
+use crate::regret_matcher::RegretMatcher;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone)]
+pub struct ExploitativeAgent<M: RegretMinimizer = RegretMatcher> {
+    matcher: M,
+    // `payoff[[a, b]]` is the reward for playing `a` against `b`.
+    payoff: Array2<Scalar>,
+    model: OpponentModel,
+    // How much of the strategy is the best response.
+    exploitation: Scalar,
+}
+
+impl<M: RegretMinimizer> ExploitativeAgent<M> {
+    /// An agent that plays `matcher`'s average strategy until told
+    /// how much to exploit with `with_exploitation`.
+    pub fn new(matcher: M, payoff: Array2<Scalar>) -> Result<Self, LittleError> {
+        if payoff.nrows() != matcher.num_experts() || payoff.ncols() == 0 {
+            return Err(LittleError::InvalidParameter(
+                "payoff must have a row per expert and at least one column",
+            ));
+        }
+        Ok(Self {
+            matcher,
+            model: OpponentModel::new(payoff.ncols())?,
+            payoff,
+            exploitation: 0.0,
+        })
+    }
+
+    /// Model the opponent with `model` rather than plain
+    /// frequencies, for example to add decay or a prior.
+    pub fn with_model(mut self, model: OpponentModel) -> Result<Self, LittleError> {
+        if model.num_actions() != self.payoff.ncols() {
+            return Err(LittleError::InvalidParameter(
+                "model must have an action per payoff column",
+            ));
+        }
+        self.model = model;
+        Ok(self)
+    }
+
+    /// Play the best response `exploitation` of the time and the
+    /// average strategy the rest.
+    pub fn with_exploitation(mut self, exploitation: Scalar) -> Result<Self, LittleError> {
+        if !(0.0..=1.0).contains(&exploitation) {
+            return Err(LittleError::InvalidParameter(
+                "exploitation must be between 0 and 1",
+            ));
+        }
+        self.exploitation = exploitation;
+        Ok(self)
+    }
+
+    pub fn matcher(&self) -> &M {
+        &self.matcher
+    }
+
+    pub fn matcher_mut(&mut self) -> &mut M {
+        &mut self.matcher
+    }
+
+    /// Record that the opponent played `action`.
+    pub fn observe_opponent(&mut self, action: usize) -> Result<(), LittleError> {
+        self.model.observe(action)
+    }
+
+    pub fn opponent_model(&self) -> &OpponentModel {
+        &self.model
+    }
+
+    /// The best action against the opponent model and its
+    /// expected reward.
+    #[must_use]
+    pub fn best_response(&self) -> (usize, Scalar) {
+        best_response::best_response(self.payoff.view(), &self.model.strategy())
+    }
+
+    /// The mixture of the average strategy and the best response
+    /// that the agent plays.
+    #[must_use]
+    pub fn strategy(&self) -> Vec<Scalar> {
+        let (best, _) = self.best_response();
+        let mut strategy: Vec<Scalar> = self
+            .matcher
+            .best_weight()
+            .iter()
+            .map(|p| p * (1.0 - self.exploitation))
+            .collect();
+        strategy[best] += self.exploitation;
+        strategy
+    }
+
+    /// Sample an action from `strategy`.
+    pub fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        dist::sample_weights(&self.strategy(), rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::NormalFormGame;
+    use crate::rps::RPSRunner;
+
+    #[test]
+    fn test_exploits_rock() {
+        let mut runner = RPSRunner::new().unwrap();
+        for _i in 0..2_000 {
+            runner.run_one();
+            runner.update_regret().unwrap();
+        }
+        let payoff = NormalFormGame::rock_paper_scissors()
+            .payoff_one()
+            .to_owned();
+        let agent = ExploitativeAgent::new(runner.into_inner().matcher_one, payoff).unwrap();
+        let mut agent = agent.with_exploitation(0.5).unwrap();
+        for _i in 0..10 {
+            agent.observe_opponent(0).unwrap();
+        }
+        assert_eq!(agent.opponent_model().strategy(), vec![1.0, 0.0, 0.0]);
+        assert_eq!(agent.best_response(), (1, 1.0));
+        let strategy = agent.strategy();
+        assert!((strategy.iter().sum::<Scalar>() - 1.0).abs() < 1e-5);
+        assert!(strategy[1] > 0.6 && strategy[0] < 0.3);
+        assert!(agent.observe_opponent(3).is_err());
+        assert!(agent.with_exploitation(1.5).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Export training trajectories in a columnar format. Each record
+//! is one row holding the iteration, the strategies, the cumulative
+//! regrets and optionally the exploitability at that point, so a run
+//! can be loaded straight into pandas, polars or DuckDB. Floats are
+//! always written as `f64` so files look the same whatever `Scalar`
+//! is.
+// `f64::from` does nothing when `Scalar` is already `f64`.
+#![allow(clippy::useless_conversion)]
+use arrow_array::builder::{Float64Builder, ListBuilder};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// One row of a trajectory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryRecord {
+    pub iteration: u64,
+    pub exploitability: Option<Scalar>,
+    pub strategy: Vec<Scalar>,
+    pub average_strategy: Vec<Scalar>,
+    pub regrets: Vec<Scalar>,
+}
+
+impl TrajectoryRecord {
+    /// Snapshot `matcher` using its update count as the iteration.
+    pub fn from_minimizer<M: RegretMinimizer>(matcher: &M, exploitability: Option<Scalar>) -> Self {
+        Self {
+            iteration: matcher.num_updates() as u64,
+            exploitability,
+            strategy: matcher.current_strategy().to_vec(),
+            average_strategy: matcher.best_weight(),
+            regrets: matcher.cumulative_regrets(),
+        }
+    }
+}
+
+fn list_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::List(Arc::new(Field::new("item", DataType::Float64, true))),
+        false,
+    )
+}
+
+/// The schema every batch and file uses.
+#[must_use]
+pub fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("iteration", DataType::UInt64, false),
+        Field::new("exploitability", DataType::Float64, true),
+        list_field("strategy"),
+        list_field("average_strategy"),
+        list_field("regrets"),
+    ]))
+}
+
+fn list_column<'a>(rows: impl Iterator<Item = &'a Vec<Scalar>>) -> ArrayRef {
+    let mut builder = ListBuilder::new(Float64Builder::new());
+    for row in rows {
+        builder
+            .values()
+            .append_slice(&row.iter().map(|v| f64::from(*v)).collect::<Vec<_>>());
+        builder.append(true);
+    }
+    Arc::new(builder.finish())
+}
+
+/// Turn `records` into a single Arrow record batch.
+pub fn to_record_batch(records: &[TrajectoryRecord]) -> Result<RecordBatch, LittleError> {
+    let iteration = UInt64Array::from_iter_values(records.iter().map(|r| r.iteration));
+    let exploitability: Float64Array = records
+        .iter()
+        .map(|r| r.exploitability.map(f64::from))
+        .collect();
+    Ok(RecordBatch::try_new(
+        schema(),
+        vec![
+            Arc::new(iteration),
+            Arc::new(exploitability),
+            list_column(records.iter().map(|r| &r.strategy)),
+            list_column(records.iter().map(|r| &r.average_strategy)),
+            list_column(records.iter().map(|r| &r.regrets)),
+        ],
+    )?)
+}
+
+/// Streams records into a Parquet file, writing a row group each
+/// time `batch_size` records have been pushed.
+pub struct TrajectoryWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    buffer: Vec<TrajectoryRecord>,
+    batch_size: usize,
+}
+
+impl TrajectoryWriter<File> {
+    /// Create a Parquet file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, LittleError> {
+        Self::new(File::create(path)?)
+    }
+}
+
+impl<W: Write + Send> TrajectoryWriter<W> {
+    pub fn new(out: W) -> Result<Self, LittleError> {
+        Self::new_with_params(out, DEFAULT_BATCH_SIZE)
+    }
+
+    pub fn new_with_params(out: W, batch_size: usize) -> Result<Self, LittleError> {
+        if batch_size == 0 {
+            return Err(LittleError::InvalidParameter(
+                "batch size must be greater than 0",
+            ));
+        }
+        Ok(Self {
+            writer: ArrowWriter::try_new(out, schema(), None)?,
+            buffer: Vec::with_capacity(batch_size),
+            batch_size,
+        })
+    }
+
+    pub fn push(&mut self, record: TrajectoryRecord) -> Result<(), LittleError> {
+        self.buffer.push(record);
+        if self.buffer.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Record the current state of `matcher`.
+    pub fn push_minimizer<M: RegretMinimizer>(
+        &mut self,
+        matcher: &M,
+        exploitability: Option<Scalar>,
+    ) -> Result<(), LittleError> {
+        self.push(TrajectoryRecord::from_minimizer(matcher, exploitability))
+    }
+
+    /// Write out anything buffered as its own row group.
+    pub fn flush(&mut self) -> Result<(), LittleError> {
+        if !self.buffer.is_empty() {
+            self.writer.write(&to_record_batch(&self.buffer)?)?;
+            self.writer.flush()?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    /// Flush and write the Parquet footer. The file isn't readable
+    /// until this has been called.
+    pub fn close(mut self) -> Result<W, LittleError> {
+        self.flush()?;
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_record_batch() {
+        let mut m = RegretMatcher::new(3).unwrap();
+        let mut records = vec![TrajectoryRecord::from_minimizer(&m, None)];
+        m.update_regret(&[1.0, 0.0, 0.5]).unwrap();
+        records.push(TrajectoryRecord::from_minimizer(&m, Some(0.25)));
+
+        let batch = to_record_batch(&records).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 5);
+        assert_eq!(batch.column(1).null_count(), 1);
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let mut m = RegretMatcher::new(2).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("little-sorry-{}.parquet", std::process::id()));
+        let mut writer =
+            TrajectoryWriter::new_with_params(File::create(&path).unwrap(), 3).unwrap();
+        for _i in 0..10 {
+            m.update_regret(&[1.0, 0.0]).unwrap();
+            writer.push_minimizer(&m, None).unwrap();
+        }
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 10);
+        assert_eq!(reader.metadata().num_row_groups(), 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
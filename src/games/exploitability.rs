@@ -0,0 +1,227 @@
+#![allow(clippy::cast_precision_loss)]
+//! How far a policy for a `Game` is from an equilibrium. A best
+//! responder sees the whole policy but only its own information, so
+//! at each of its information states it picks the action with the
+//! highest value summed over the histories it can't tell apart,
+//! weighted by how likely everyone else is to reach them.
+//!
+//! Policies map `information_state_string` to a strategy over the
+//! state's `legal_actions`, like `GameCfr::average_policy`. States
+//! missing from the policy are played uniformly.
+use std::collections::HashMap;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::games::game::{Action, Game, Player, State};
+use crate::scalar::Scalar;
+
+pub type Policy = HashMap<String, Vec<Scalar>>;
+
+fn strategy<S: State>(policy: &Policy, state: &S, actor: usize) -> Vec<Scalar> {
+    let n = state.legal_actions().len();
+    policy
+        .get(&state.information_state_string(actor))
+        .cloned()
+        .unwrap_or_else(|| vec![1.0 / n as Scalar; n])
+}
+
+/// What each player expects to win when everyone plays `policy`.
+pub fn policy_value<G: Game>(game: &G, policy: &Policy) -> Vec<Scalar> {
+    state_value(&game.new_initial_state(), policy)
+}
+
+/// What each player expects to win from `state` on when everyone
+/// plays `policy`.
+pub fn state_value<S: State>(state: &S, policy: &Policy) -> Vec<Scalar> {
+    let children: Vec<(Action, Scalar)> = match state.current_player() {
+        Player::Terminal => return state.returns(),
+        Player::Chance => state.chance_outcomes(),
+        Player::Id(actor) => state
+            .legal_actions()
+            .into_iter()
+            .zip(strategy(policy, state, actor))
+            .collect(),
+    };
+    let mut value = Vec::new();
+    for (action, p) in children {
+        let child = state_value(&state.child(action), policy);
+        value.resize(child.len(), 0.0);
+        for (v, c) in value.iter_mut().zip(child) {
+            *v += p * c;
+        }
+    }
+    value
+}
+
+struct BestResponse<'a, S> {
+    player: usize,
+    policy: &'a Policy,
+    // Each of the responder's information states, with every
+    // history in it and the chance of everyone else reaching it.
+    infosets: HashMap<String, Vec<(Vec<Action>, S, Scalar)>>,
+    values: HashMap<Vec<Action>, Scalar>,
+    actions: HashMap<String, Action>,
+}
+
+impl<S: State> BestResponse<'_, S> {
+    fn collect(&mut self, state: &S, path: &mut Vec<Action>, reach: Scalar) {
+        let children: Vec<(Action, Scalar)> = match state.current_player() {
+            Player::Terminal => return,
+            Player::Chance => state.chance_outcomes(),
+            Player::Id(actor) if actor == self.player => {
+                self.infosets
+                    .entry(state.information_state_string(actor))
+                    .or_default()
+                    .push((path.clone(), state.clone(), reach));
+                state
+                    .legal_actions()
+                    .into_iter()
+                    .map(|a| (a, 1.0))
+                    .collect()
+            }
+            Player::Id(actor) => state
+                .legal_actions()
+                .into_iter()
+                .zip(strategy(self.policy, state, actor))
+                .collect(),
+        };
+        for (action, p) in children {
+            path.push(action);
+            self.collect(&state.child(action), path, reach * p);
+            path.pop();
+        }
+    }
+
+    fn value(&mut self, state: &S, path: &mut Vec<Action>) -> Scalar {
+        if let Some(v) = self.values.get(path.as_slice()) {
+            return *v;
+        }
+        let children: Vec<(Action, Scalar)> = match state.current_player() {
+            Player::Terminal => return state.returns()[self.player],
+            Player::Chance => state.chance_outcomes(),
+            Player::Id(actor) if actor == self.player => {
+                vec![(
+                    self.best_action(&state.information_state_string(actor)),
+                    1.0,
+                )]
+            }
+            Player::Id(actor) => state
+                .legal_actions()
+                .into_iter()
+                .zip(strategy(self.policy, state, actor))
+                .collect(),
+        };
+        let mut value = 0.0;
+        for (action, p) in children {
+            path.push(action);
+            value += p * self.value(&state.child(action), path);
+            path.pop();
+        }
+        self.values.insert(path.clone(), value);
+        value
+    }
+
+    fn best_action(&mut self, key: &str) -> Action {
+        if let Some(a) = self.actions.get(key) {
+            return *a;
+        }
+        let histories = self.infosets[key].clone();
+        let mut best = (Scalar::NEG_INFINITY, 0);
+        for action in histories[0].1.legal_actions() {
+            let mut total = 0.0;
+            for (path, state, reach) in &histories {
+                let mut path = path.clone();
+                path.push(action);
+                total += reach * self.value(&state.child(action), &mut path);
+            }
+            if total > best.0 {
+                best = (total, action);
+            }
+        }
+        self.actions.insert(key.into(), best.1);
+        best.1
+    }
+}
+
+/// A best response for `player` to everyone else playing `policy`:
+/// what it wins, and the action it takes at each of `player`'s
+/// information states.
+pub fn best_response<G: Game>(
+    game: &G,
+    policy: &Policy,
+    player: usize,
+) -> (Scalar, HashMap<String, Action>) {
+    let root = game.new_initial_state();
+    let mut br = BestResponse {
+        player,
+        policy,
+        infosets: HashMap::new(),
+        values: HashMap::new(),
+        actions: HashMap::new(),
+    };
+    br.collect(&root, &mut Vec::new(), 1.0);
+    let value = br.value(&root, &mut Vec::new());
+    // Fill in the infosets the response itself never reaches.
+    let keys: Vec<String> = br.infosets.keys().cloned().collect();
+    for key in keys {
+        br.best_action(&key);
+    }
+    (value, br.actions)
+}
+
+/// What `player` wins by best responding to everyone else playing
+/// `policy`.
+pub fn best_response_value<G: Game>(game: &G, policy: &Policy, player: usize) -> Scalar {
+    best_response(game, policy, player).0
+}
+
+/// How much each player gains by switching to a best response while
+/// everyone else keeps playing `policy`.
+pub fn player_exploitability<G: Game>(game: &G, policy: &Policy) -> Vec<Scalar> {
+    let values = policy_value(game, policy);
+    (0..game.num_players())
+        .map(|p| best_response_value(game, policy, p) - values[p])
+        .collect()
+}
+
+/// The average over players of how much each gains by switching
+/// to a best response. Zero exactly at a Nash equilibrium.
+pub fn exploitability<G: Game>(game: &G, policy: &Policy) -> Scalar {
+    let gains = player_exploitability(game, policy);
+    gains.iter().sum::<Scalar>() / gains.len() as Scalar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::{GameCfr, Goofspiel};
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_best_response_to_uniform() {
+        let game = Goofspiel::new(2).unwrap();
+        let (value, actions) = best_response(&game, &Policy::new(), 0);
+        // Bidding the card that matches the first prize wins it half
+        // the time, and then loses the other prize for a net of 1.
+        assert!((value - 0.5).abs() < 1e-6);
+        assert_eq!(actions["p0 prizes [1] bids []"], 1);
+        assert_eq!(actions["p0 prizes [0] bids []"], 0);
+        let gains = player_exploitability(&game, &Policy::new());
+        assert!((gains[0] - 0.5).abs() < 1e-6 && (gains[1] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_exploitability_falls() {
+        let game = Goofspiel::new(3).unwrap();
+        let uniform = exploitability(&game, &Policy::new());
+        assert!(uniform > 0.5);
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(game);
+        cfr.run(200).unwrap();
+        let trained = exploitability(&game, &cfr.average_policy());
+        assert!(trained < 0.1 * uniform, "{trained}");
+        let values = policy_value(&game, &cfr.average_policy());
+        // The game is symmetric, so it's worth nothing to either.
+        assert!(values[0].abs() < 0.01);
+        assert!((values[0] + values[1]).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,158 @@
+//! Small textbook games with known equilibria, to check that an
+//! algorithm converges to the right answer rather than just
+//! settling down. Each fixture pairs a game with an analytic
+//! solution and the payoff each player gets there.
+use ndarray::prelude::*;
+
+use std::vec::Vec;
+
+use crate::games::NormalFormGame;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub game: NormalFormGame,
+    /// An equilibrium of the game. When there are several this is
+    /// the mixed one.
+    pub strategy_one: Vec<Scalar>,
+    pub strategy_two: Vec<Scalar>,
+    /// What each player expects to win at the equilibrium.
+    pub value_one: Scalar,
+    pub value_two: Scalar,
+}
+
+/// Both players pick heads or tails and player one wins when they
+/// match. Both should play each side half the time.
+#[must_use]
+pub fn matching_pennies() -> Fixture {
+    Fixture {
+        name: "matching_pennies",
+        game: NormalFormGame::zero_sum(array![[1.0, -1.0], [-1.0, 1.0]]).unwrap(),
+        strategy_one: vec![0.5, 0.5],
+        strategy_two: vec![0.5, 0.5],
+        value_one: 0.0,
+        value_two: 0.0,
+    }
+}
+
+/// A coordination game where player one prefers the first outcome
+/// and player two the second. It's general sum, and besides the two
+/// pure equilibria there's a mixed one where each player picks
+/// their favourite 3/5 of the time.
+#[must_use]
+pub fn battle_of_the_sexes() -> Fixture {
+    Fixture {
+        name: "battle_of_the_sexes",
+        game: NormalFormGame::new(
+            array![[3.0, 0.0], [0.0, 2.0]],
+            array![[2.0, 0.0], [0.0, 3.0]],
+        )
+        .unwrap(),
+        strategy_one: vec![0.6, 0.4],
+        strategy_two: vec![0.4, 0.6],
+        value_one: 1.2,
+        value_two: 1.2,
+    }
+}
+
+/// Rock paper scissors where winning with rock pays 2, so paper is
+/// played half the time.
+#[must_use]
+pub fn biased_rock_paper_scissors() -> Fixture {
+    Fixture {
+        name: "biased_rock_paper_scissors",
+        game: NormalFormGame::zero_sum(array![
+            [0.0, -1.0, 2.0],
+            [1.0, 0.0, -1.0],
+            [-2.0, 1.0, 0.0]
+        ])
+        .unwrap(),
+        strategy_one: vec![0.25, 0.5, 0.25],
+        strategy_two: vec![0.25, 0.5, 0.25],
+        value_one: 0.0,
+        value_two: 0.0,
+    }
+}
+
+/// Matching pennies where both players also have a third action
+/// that's strictly dominated, so it should never be played.
+#[must_use]
+pub fn dominated_pennies() -> Fixture {
+    Fixture {
+        name: "dominated_pennies",
+        game: NormalFormGame::zero_sum(array![
+            [1.0, -1.0, 2.0],
+            [-1.0, 1.0, 2.0],
+            [-2.0, -2.0, 0.0]
+        ])
+        .unwrap(),
+        strategy_one: vec![0.5, 0.5, 0.0],
+        strategy_two: vec![0.5, 0.5, 0.0],
+        value_one: 0.0,
+        value_two: 0.0,
+    }
+}
+
+/// Every fixture.
+#[must_use]
+pub fn all() -> Vec<Fixture> {
+    vec![
+        matching_pennies(),
+        battle_of_the_sexes(),
+        biased_rock_paper_scissors(),
+        dominated_pennies(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::games::NormalFormRunner;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_solutions() {
+        for f in all() {
+            let gap = f.game.nash_gap(&f.strategy_one, &f.strategy_two).unwrap();
+            assert!(gap < 1e-5, "{}", f.name);
+            let value_one = f.game.expected_rewards_one(&f.strategy_two);
+            let value_two = f.game.expected_rewards_two(&f.strategy_one);
+            let one = ArrayView1::from(&f.strategy_one).dot(&value_one);
+            let two = ArrayView1::from(&f.strategy_two).dot(&value_two);
+            assert!((one - f.value_one).abs() < 1e-5, "{}", f.name);
+            assert!((two - f.value_two).abs() < 1e-5, "{}", f.name);
+        }
+    }
+
+    #[test]
+    fn test_dominated_action() {
+        // Each player's third action is worse than their first
+        // against everything the other player can do.
+        let f = dominated_pennies();
+        let one = f.game.payoff_one();
+        let two = f.game.payoff_two();
+        for j in 0..3 {
+            assert!(one[[2, j]] < one[[0, j]]);
+            assert!(two[[j, 2]] < two[[j, 0]]);
+        }
+    }
+
+    #[test]
+    fn test_zero_sum_convergence() {
+        for f in all().into_iter().filter(|f| f.game.is_zero_sum()) {
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut runner =
+                NormalFormRunner::<DiscountedRegretMatcher>::new(f.game.clone()).unwrap();
+            for _i in 0..10_000 {
+                runner.run_one_with_rng(&mut rng);
+                runner.update_regret().unwrap();
+            }
+            for (p, q) in runner.best_weight().iter().zip(&f.strategy_one) {
+                assert!((p - q).abs() < 0.05, "{}", f.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! A description of turn based games that mirrors OpenSpiel's
+//! `Game` and `State` so that game definitions can be ported over
+//! method for method. Chance is a player that picks from a fixed
+//! distribution and each real player only sees their own
+//! information state, which is how imperfect information is
+//! modeled.
+use std::string::String;
+use std::vec::Vec;
+
+use crate::scalar::Scalar;
+
+/// An action is an index into the game's distinct actions.
+pub type Action = usize;
+
+/// Who acts at a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    /// One of the players, numbered from zero.
+    Id(usize),
+    Chance,
+    /// The game is over.
+    Terminal,
+}
+
+pub trait Game {
+    type State: State;
+
+    fn num_players(&self) -> usize;
+
+    /// How many actions there are in total, across every state.
+    fn num_distinct_actions(&self) -> usize;
+
+    fn new_initial_state(&self) -> Self::State;
+}
+
+pub trait State: Clone {
+    fn current_player(&self) -> Player;
+
+    /// The actions the current player may take, in increasing
+    /// order. Empty at chance and terminal states.
+    fn legal_actions(&self) -> Vec<Action>;
+
+    /// Each possible chance outcome with its probability. Only
+    /// called at chance states.
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)>;
+
+    fn apply_action(&mut self, action: Action);
+
+    /// What each player won. Only called at terminal states.
+    fn returns(&self) -> Vec<Scalar>;
+
+    /// Everything `player` knows at this state. Two states with the
+    /// same string must have the same legal actions.
+    fn information_state_string(&self, player: usize) -> String;
+
+    /// A fixed length encoding of what `player` knows, for
+    /// training function approximators. Games that don't provide
+    /// one return an empty vector.
+    fn information_state_tensor(&self, _player: usize) -> Vec<Scalar> {
+        Vec::new()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.current_player() == Player::Terminal
+    }
+
+    fn is_chance_node(&self) -> bool {
+        self.current_player() == Player::Chance
+    }
+
+    /// The state after `action`, leaving this one alone.
+    #[must_use]
+    fn child(&self, action: Action) -> Self {
+        let mut child = self.clone();
+        child.apply_action(action);
+        child
+    }
+}
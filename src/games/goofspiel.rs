@@ -0,0 +1,203 @@
+//! Goofspiel, the game of pure strategy. Each player holds the
+//! cards 1 to `num_cards` and a shuffled deck of prize cards with
+//! the same values is turned over one at a time. Both players bid a
+//! card for each prize and the higher bid wins it, while a tie
+//! discards it. Bids are revealed afterwards and every card is only
+//! played once. The return is the difference in prize points won.
+//!
+//! The bids are simultaneous. Player one bids first but player two
+//! doesn't see that bid until the round is over, which is how the
+//! `Game` interface expresses simultaneous moves. Train with
+//! `GameCfr`, which keeps a matcher per decision point:
+//!
+//! ```
+//! use little_sorry::games::goofspiel::Goofspiel;
+//! use little_sorry::games::GameCfr;
+//! use little_sorry::vanilla_cfr::VanillaCfrRegretMatcher;
+//!
+//! let game = Goofspiel::new(3).unwrap();
+//! let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(game);
+//! cfr.run(10).unwrap();
+//! ```
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::{Action, Game, Player, State};
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Goofspiel {
+    num_cards: usize,
+}
+
+impl Goofspiel {
+    pub fn new(num_cards: usize) -> Result<Self, LittleError> {
+        if num_cards == 0 {
+            return Err(LittleError::InvalidParameter(
+                "num_cards must be greater than 0",
+            ));
+        }
+        Ok(Self { num_cards })
+    }
+
+    #[must_use]
+    pub fn num_cards(&self) -> usize {
+        self.num_cards
+    }
+}
+
+impl Game for Goofspiel {
+    type State = GoofspielState;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    /// Action `a` bids, or turns over as the prize, the card worth
+    /// `a + 1`.
+    fn num_distinct_actions(&self) -> usize {
+        self.num_cards
+    }
+
+    fn new_initial_state(&self) -> GoofspielState {
+        GoofspielState {
+            num_cards: self.num_cards,
+            prizes: Vec::with_capacity(self.num_cards),
+            bids: Vec::with_capacity(self.num_cards),
+            pending_bid: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoofspielState {
+    num_cards: usize,
+    // Prizes turned over so far, including the current one.
+    prizes: Vec<Action>,
+    // Both bids of every finished round.
+    bids: Vec<[Action; 2]>,
+    // Player one's bid this round, hidden from player two.
+    pending_bid: Option<Action>,
+}
+
+impl GoofspielState {
+    /// The prize being bid on, if one has been turned over.
+    #[must_use]
+    pub fn current_prize(&self) -> Option<Action> {
+        (self.prizes.len() > self.bids.len()).then(|| self.prizes[self.bids.len()])
+    }
+
+    /// The prize points each player has won so far.
+    #[must_use]
+    pub fn points(&self) -> [Scalar; 2] {
+        let mut points = [0.0; 2];
+        for (prize, [one, two]) in self.prizes.iter().zip(&self.bids) {
+            let value = (prize + 1) as Scalar;
+            match one.cmp(two) {
+                std::cmp::Ordering::Greater => points[0] += value,
+                std::cmp::Ordering::Less => points[1] += value,
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        points
+    }
+}
+
+impl State for GoofspielState {
+    fn current_player(&self) -> Player {
+        if self.bids.len() == self.num_cards {
+            Player::Terminal
+        } else if self.prizes.len() == self.bids.len() {
+            Player::Chance
+        } else if self.pending_bid.is_none() {
+            Player::Id(0)
+        } else {
+            Player::Id(1)
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        let Player::Id(player) = self.current_player() else {
+            return Vec::new();
+        };
+        (0..self.num_cards)
+            .filter(|card| !self.bids.iter().any(|bids| bids[player] == *card))
+            .collect()
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        let remaining = self.num_cards - self.prizes.len();
+        (0..self.num_cards)
+            .filter(|card| !self.prizes.contains(card))
+            .map(|card| (card, 1.0 / remaining as Scalar))
+            .collect()
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        match self.current_player() {
+            Player::Chance => self.prizes.push(action),
+            Player::Id(0) => self.pending_bid = Some(action),
+            Player::Id(_) => {
+                let one = self.pending_bid.take().unwrap();
+                self.bids.push([one, action]);
+            }
+            Player::Terminal => panic!("the game is over"),
+        }
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        let [one, two] = self.points();
+        vec![one - two, two - one]
+    }
+
+    fn information_state_string(&self, player: usize) -> String {
+        // Everything but player one's pending bid is public.
+        format!("p{player} prizes {:?} bids {:?}", self.prizes, self.bids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::GameCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_play_through() {
+        let mut state = Goofspiel::new(2).unwrap().new_initial_state();
+        assert!(state.is_chance_node());
+        state.apply_action(1);
+        assert_eq!(state.current_prize(), Some(1));
+        assert_eq!(state.current_player(), Player::Id(0));
+        let before = state.information_state_string(1);
+        state.apply_action(1);
+        // Player two can't see the bid.
+        assert_eq!(state.information_state_string(1), before);
+        state.apply_action(0);
+        assert_eq!(state.chance_outcomes(), vec![(0, 1.0)]);
+        state.apply_action(0);
+        assert_eq!(state.legal_actions(), vec![0]);
+        state.apply_action(0);
+        state.apply_action(1);
+        assert!(state.is_terminal());
+        // Player one won the 2 and player two the 1.
+        assert_eq!(state.returns(), vec![1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_bids_high_for_the_big_prize() {
+        // With two cards it's dominant to bid the card matching the
+        // first prize.
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Goofspiel::new(2).unwrap());
+        cfr.run(100).unwrap();
+        let policy = cfr.average_policy();
+        for player in 0..2 {
+            for prize in 0..2 {
+                let key = format!("p{player} prizes [{prize}] bids []");
+                let bid = policy[&key].iter().position(|p| *p > 0.9);
+                assert_eq!(bid, Some(prize), "{key}");
+            }
+        }
+    }
+}
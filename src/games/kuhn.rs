@@ -0,0 +1,179 @@
+//! Kuhn poker, the smallest interesting poker game. There are three
+//! cards, jack, queen and king, and each player antes 1 and is dealt
+//! one. Player one checks or bets 1, and after a check player two
+//! may check or bet. Facing a bet a player folds or calls, and if
+//! nobody folds the higher card takes the pot.
+//!
+//! Its equilibria are known exactly. For any `alpha` in `[0, 1/3]`
+//! player one bets a jack `alpha` of the time, a king `3 * alpha`,
+//! never bets a queen and calls with a queen `alpha + 1/3` of the
+//! time. Player two always bets or calls a king, bets a jack after a
+//! check `1/3` of the time and calls with a queen `1/3` of the time.
+//! Player one expects to lose `1/18` a hand.
+use std::collections::HashMap;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::{Action, Game, Player, State};
+use crate::games::GameCfr;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+/// Check, or fold when facing a bet.
+pub const PASS: Action = 0;
+/// Bet, or call when facing a bet.
+pub const BET: Action = 1;
+
+/// What player one expects to win at any equilibrium.
+pub const GAME_VALUE: Scalar = -1.0 / 18.0;
+
+const CARDS: [char; 3] = ['J', 'Q', 'K'];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Kuhn;
+
+impl Game for Kuhn {
+    type State = KuhnState;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    fn num_distinct_actions(&self) -> usize {
+        3
+    }
+
+    fn new_initial_state(&self) -> KuhnState {
+        KuhnState {
+            cards: Vec::with_capacity(2),
+            history: Vec::with_capacity(3),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KuhnState {
+    cards: Vec<usize>,
+    history: Vec<Action>,
+}
+
+impl State for KuhnState {
+    fn current_player(&self) -> Player {
+        if self.cards.len() < 2 {
+            return Player::Chance;
+        }
+        match self.history.as_slice() {
+            [] | [PASS] | [BET] | [PASS, BET] => Player::Id(self.history.len() % 2),
+            _ => Player::Terminal,
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        match self.current_player() {
+            Player::Id(_) => vec![PASS, BET],
+            _ => Vec::new(),
+        }
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        let left: Vec<usize> = (0..3).filter(|c| !self.cards.contains(c)).collect();
+        let p = 1.0 / left.len() as Scalar;
+        left.into_iter().map(|c| (c, p)).collect()
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        if self.is_chance_node() {
+            self.cards.push(action);
+        } else {
+            self.history.push(action);
+        }
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        let showdown = |pot: Scalar| {
+            if self.cards[0] > self.cards[1] {
+                vec![pot, -pot]
+            } else {
+                vec![-pot, pot]
+            }
+        };
+        match self.history.as_slice() {
+            [PASS, PASS] => showdown(1.0),
+            [BET, PASS] => vec![1.0, -1.0],
+            [PASS, BET, PASS] => vec![-1.0, 1.0],
+            _ => showdown(2.0),
+        }
+    }
+
+    /// The player's card and then the actions so far, e.g. `Qpb`
+    /// for a queen facing a bet after checking.
+    fn information_state_string(&self, player: usize) -> String {
+        let mut key = String::from(CARDS[self.cards[player]]);
+        key.extend(
+            self.history
+                .iter()
+                .map(|a| if *a == PASS { 'p' } else { 'b' }),
+        );
+        key
+    }
+}
+
+/// Every information state, player one's first.
+#[must_use]
+pub fn infosets() -> Vec<String> {
+    let mut keys = Vec::with_capacity(12);
+    for history in ["", "pb", "p", "b"] {
+        for card in CARDS {
+            keys.push(format!("{card}{history}"));
+        }
+    }
+    keys
+}
+
+/// Run CFR with `M` at every information state for `iterations`
+/// iterations and return the average strategy at each, as the
+/// chance of passing and then of betting.
+pub fn solve<M: RegretMinimizer>(
+    iterations: usize,
+) -> Result<HashMap<String, Vec<Scalar>>, LittleError> {
+    let mut cfr = GameCfr::<Kuhn, M>::new(Kuhn);
+    cfr.run(iterations)?;
+    Ok(cfr.average_policy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::{exploitability, policy_value};
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_equilibrium() {
+        let policy = solve::<VanillaCfrRegretMatcher>(5_000).unwrap();
+        let mut keys: Vec<&String> = policy.keys().collect();
+        keys.sort();
+        let mut expected = infosets();
+        expected.sort();
+        assert_eq!(keys, expected.iter().collect::<Vec<_>>());
+
+        let bet = |key: &str| policy[key][1];
+        let alpha = bet("J");
+        assert!((0.0..=1.0 / 3.0 + 0.01).contains(&alpha));
+        let tolerance = 0.05;
+        assert!((bet("K") - 3.0 * alpha).abs() < tolerance);
+        assert!((bet("Qpb") - alpha - 1.0 / 3.0).abs() < tolerance);
+        assert!((bet("Jp") - 1.0 / 3.0).abs() < tolerance);
+        assert!((bet("Qb") - 1.0 / 3.0).abs() < tolerance);
+        for always in ["Kpb", "Kp", "Kb"] {
+            assert!(bet(always) > 1.0 - tolerance, "{always}");
+        }
+        for never in ["Q", "Jpb", "Qp", "Jb"] {
+            assert!(bet(never) < tolerance, "{never}");
+        }
+
+        let value = policy_value(&Kuhn, &policy)[0];
+        assert!((value - GAME_VALUE).abs() < 0.005);
+        assert!(exploitability(&Kuhn, &policy) < 0.005);
+    }
+}
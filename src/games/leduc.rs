@@ -0,0 +1,250 @@
+//! Leduc hold'em, a two round poker game small enough to solve
+//! exactly but big enough to tell algorithms apart. The deck is two
+//! each of jack, queen and king. Each player antes 1 and is dealt a
+//! card, there's a round of betting, a public card is turned over
+//! and there's a second round. Bets are 2 in the first round and 4
+//! in the second, with at most two raises a round. At showdown
+//! pairing the public card wins, then the higher card, and equal
+//! cards split the pot.
+use std::string::String;
+use std::vec::Vec;
+
+use crate::games::game::{Action, Game, Player, State};
+use crate::scalar::Scalar;
+
+pub const FOLD: Action = 0;
+/// Check, or call when facing a bet.
+pub const CALL: Action = 1;
+/// Bet, or raise when facing a bet.
+pub const RAISE: Action = 2;
+
+const RANKS: [char; 3] = ['J', 'Q', 'K'];
+const MAX_RAISES: usize = 2;
+// Check, raise, raise and call.
+const MAX_ROUND_ACTIONS: usize = MAX_RAISES + 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Leduc;
+
+impl Game for Leduc {
+    type State = LeducState;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    /// The betting actions. Chance deals cards `0..6`, where card
+    /// `c` has rank `c / 2`.
+    fn num_distinct_actions(&self) -> usize {
+        6
+    }
+
+    fn new_initial_state(&self) -> LeducState {
+        LeducState {
+            cards: Vec::with_capacity(3),
+            rounds: [Vec::new(), Vec::new()],
+            contributions: [1.0; 2],
+            folded: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeducState {
+    // Player one's, player two's and then the public card.
+    cards: Vec<Action>,
+    // The betting in each round.
+    rounds: [Vec<Action>; 2],
+    contributions: [Scalar; 2],
+    folded: Option<usize>,
+}
+
+impl LeducState {
+    /// The betting round, zero before the public card is dealt and
+    /// one after.
+    #[must_use]
+    pub fn round(&self) -> usize {
+        usize::from(self.cards.len() == 3)
+    }
+
+    fn num_raises(&self) -> usize {
+        let round = &self.rounds[self.round()];
+        round.iter().filter(|a| **a == RAISE).count()
+    }
+
+    fn facing_bet(&self) -> bool {
+        self.contributions[0] != self.contributions[1]
+    }
+
+    fn rank(card: Action) -> usize {
+        card / 2
+    }
+
+    // A call ends the round unless it's the opening check.
+    fn round_over(round: &[Action]) -> bool {
+        round.len() > 1 && round.last() == Some(&CALL)
+    }
+}
+
+impl State for LeducState {
+    fn current_player(&self) -> Player {
+        let round = &self.rounds[self.round()];
+        if self.folded.is_some() {
+            Player::Terminal
+        } else if self.cards.len() < 2 || (self.cards.len() == 2 && Self::round_over(round)) {
+            Player::Chance
+        } else if Self::round_over(round) {
+            Player::Terminal
+        } else {
+            Player::Id(round.len() % 2)
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        if !matches!(self.current_player(), Player::Id(_)) {
+            return Vec::new();
+        }
+        let mut actions = Vec::with_capacity(3);
+        if self.facing_bet() {
+            actions.push(FOLD);
+        }
+        actions.push(CALL);
+        if self.num_raises() < MAX_RAISES {
+            actions.push(RAISE);
+        }
+        actions
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        let p = 1.0 / (6 - self.cards.len()) as Scalar;
+        (0..6)
+            .filter(|c| !self.cards.contains(c))
+            .map(|c| (c, p))
+            .collect()
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        let Player::Id(player) = self.current_player() else {
+            self.cards.push(action);
+            return;
+        };
+        let round = self.round();
+        self.rounds[round].push(action);
+        let most = self.contributions[0].max(self.contributions[1]);
+        match action {
+            FOLD => self.folded = Some(player),
+            CALL => self.contributions[player] = most,
+            _ => self.contributions[player] = most + [2.0, 4.0][round],
+        }
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        let winner = match self.folded {
+            Some(player) => Some(1 - player),
+            None => {
+                let public = Self::rank(self.cards[2]);
+                let strength = |p: usize| {
+                    let rank = Self::rank(self.cards[p]);
+                    if rank == public {
+                        RANKS.len() + rank
+                    } else {
+                        rank
+                    }
+                };
+                match strength(0).cmp(&strength(1)) {
+                    std::cmp::Ordering::Greater => Some(0),
+                    std::cmp::Ordering::Less => Some(1),
+                    std::cmp::Ordering::Equal => None,
+                }
+            }
+        };
+        match winner {
+            Some(w) => {
+                let won = self.contributions[1 - w];
+                let mut returns = vec![-won; 2];
+                returns[w] = won;
+                returns
+            }
+            None => vec![0.0; 2],
+        }
+    }
+
+    /// The player's card, the public card once it's out and the
+    /// betting of each round, e.g. `KQ:rc/r`.
+    fn information_state_string(&self, player: usize) -> String {
+        let mut key = String::from(RANKS[Self::rank(self.cards[player])]);
+        if let Some(public) = self.cards.get(2) {
+            key.push(RANKS[Self::rank(*public)]);
+        }
+        key.push(':');
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                key.push('/');
+            }
+            key.extend(round.iter().map(|a| ['f', 'c', 'r'][*a]));
+        }
+        key
+    }
+
+    /// One hot encodings of the private card's rank, the public
+    /// card's rank and each action of both betting rounds.
+    fn information_state_tensor(&self, player: usize) -> Vec<Scalar> {
+        let mut tensor = vec![0.0; 2 * RANKS.len() + 2 * MAX_ROUND_ACTIONS * 3];
+        tensor[Self::rank(self.cards[player])] = 1.0;
+        if let Some(public) = self.cards.get(2) {
+            tensor[RANKS.len() + Self::rank(*public)] = 1.0;
+        }
+        for (r, round) in self.rounds.iter().enumerate() {
+            for (i, action) in round.iter().enumerate() {
+                tensor[2 * RANKS.len() + (r * MAX_ROUND_ACTIONS + i) * 3 + action] = 1.0;
+            }
+        }
+        tensor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::exploitability;
+    use crate::games::GameCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_hand() {
+        let mut state = Leduc.new_initial_state();
+        // Player one has a jack and player two a king.
+        state.apply_action(0);
+        state.apply_action(5);
+        state.apply_action(RAISE);
+        assert_eq!(state.legal_actions(), vec![FOLD, CALL, RAISE]);
+        state.apply_action(RAISE);
+        assert_eq!(state.legal_actions(), vec![FOLD, CALL]);
+        state.apply_action(CALL);
+        assert!(state.is_chance_node());
+        // The other jack pairs player one.
+        state.apply_action(1);
+        assert_eq!(state.information_state_string(0), "JJ:rrc/");
+        let tensor = state.information_state_tensor(0);
+        assert_eq!(tensor.len(), 30);
+        assert_eq!(tensor[..6], [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(tensor.iter().sum::<Scalar>(), 5.0);
+        state.apply_action(CALL);
+        state.apply_action(RAISE);
+        state.apply_action(CALL);
+        assert!(state.is_terminal());
+        assert_eq!(state.returns(), vec![9.0, -9.0]);
+    }
+
+    #[test]
+    fn test_exploitability_falls() {
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run(1).unwrap();
+        let start = exploitability(&Leduc, &cfr.average_policy());
+        cfr.run(49).unwrap();
+        let e = exploitability(&Leduc, &cfr.average_policy());
+        // 18 first round decisions and 270 after the public card.
+        assert_eq!(cfr.infosets().len(), 288);
+        assert!(e < 0.5 * start, "{start} {e}");
+    }
+}
@@ -0,0 +1,173 @@
+#![allow(clippy::cast_precision_loss)]
+//! Liar's Dice with a cup of dice for each of two players. Each
+//! player rolls in secret, then they take turns bidding that at
+//! least some quantity of dice, counting both cups, show some face.
+//! Every bid must be higher than the last, by quantity and then by
+//! face. Instead of bidding a player can call liar, and if the last
+//! bid was right its bidder wins, otherwise the caller does. There
+//! are no wild faces. The winner gets 1 and the loser -1.
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::{Action, Game, Player, State};
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiarsDice {
+    num_dice: usize,
+    num_faces: usize,
+}
+
+impl LiarsDice {
+    /// `num_dice` dice per player, each with `num_faces` faces.
+    pub fn new(num_dice: usize, num_faces: usize) -> Result<Self, LittleError> {
+        if num_dice == 0 || num_faces == 0 {
+            return Err(LittleError::InvalidParameter(
+                "num_dice and num_faces must be greater than 0",
+            ));
+        }
+        Ok(Self {
+            num_dice,
+            num_faces,
+        })
+    }
+
+    /// The action that calls liar rather than bidding.
+    #[must_use]
+    pub fn liar_action(&self) -> Action {
+        2 * self.num_dice * self.num_faces
+    }
+}
+
+impl Game for LiarsDice {
+    type State = LiarsDiceState;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    /// Bid `b` claims `b / num_faces + 1` dice showing face
+    /// `b % num_faces`, then the last action calls liar. Chance
+    /// rolls use the faces.
+    fn num_distinct_actions(&self) -> usize {
+        self.liar_action() + 1
+    }
+
+    fn new_initial_state(&self) -> LiarsDiceState {
+        LiarsDiceState {
+            game: *self,
+            dice: [Vec::new(), Vec::new()],
+            bids: Vec::new(),
+            called: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiarsDiceState {
+    game: LiarsDice,
+    dice: [Vec<usize>; 2],
+    bids: Vec<Action>,
+    called: bool,
+}
+
+impl LiarsDiceState {
+    // Whose turn it is once the dice are rolled.
+    fn bidder(&self) -> usize {
+        self.bids.len() % 2
+    }
+}
+
+impl State for LiarsDiceState {
+    fn current_player(&self) -> Player {
+        if self.called {
+            Player::Terminal
+        } else if self.dice[1].len() < self.game.num_dice {
+            Player::Chance
+        } else {
+            Player::Id(self.bidder())
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        if !matches!(self.current_player(), Player::Id(_)) {
+            return Vec::new();
+        }
+        let first = self.bids.last().map_or(0, |b| b + 1);
+        let mut actions: Vec<Action> = (first..self.game.liar_action()).collect();
+        if !self.bids.is_empty() {
+            actions.push(self.game.liar_action());
+        }
+        actions
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        let p = 1.0 / self.game.num_faces as Scalar;
+        (0..self.game.num_faces).map(|face| (face, p)).collect()
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        match self.current_player() {
+            Player::Chance => {
+                let cup = usize::from(self.dice[0].len() == self.game.num_dice);
+                self.dice[cup].push(action);
+            }
+            Player::Id(_) if action == self.game.liar_action() => self.called = true,
+            Player::Id(_) => self.bids.push(action),
+            Player::Terminal => panic!("the game is over"),
+        }
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        let bid = self.bids.last().unwrap();
+        let (quantity, face) = (bid / self.game.num_faces + 1, bid % self.game.num_faces);
+        let count = self.dice.iter().flatten().filter(|d| **d == face).count();
+        // The bidder is whoever didn't call.
+        let caller = self.bidder();
+        let caller_won = if count >= quantity { -1.0 } else { 1.0 };
+        let mut returns = vec![-caller_won; 2];
+        returns[caller] = caller_won;
+        returns
+    }
+
+    fn information_state_string(&self, player: usize) -> String {
+        format!(
+            "p{player} dice {:?} bids {:?}",
+            self.dice[player], self.bids
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::exploitability;
+    use crate::games::GameCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_calling_liar() {
+        let game = LiarsDice::new(1, 3).unwrap();
+        let mut state = game.new_initial_state();
+        state.apply_action(2);
+        state.apply_action(1);
+        assert_eq!(state.current_player(), Player::Id(0));
+        assert!(!state.legal_actions().contains(&game.liar_action()));
+        // Two dice showing 1, which is wrong.
+        state.apply_action(4);
+        assert_eq!(state.legal_actions(), vec![5, 6]);
+        state.apply_action(game.liar_action());
+        assert!(state.is_terminal());
+        assert_eq!(state.returns(), vec![-1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_solve() {
+        let game = LiarsDice::new(1, 3).unwrap();
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(game);
+        cfr.run(500).unwrap();
+        let e = exploitability(&game, &cfr.average_policy());
+        assert!(e < 0.02, "{e}");
+    }
+}
@@ -0,0 +1,20 @@
+//! Games to train the regret minimizers on and the interfaces
+//! used to describe them.
+pub mod exploitability;
+pub mod fixtures;
+pub mod game;
+pub mod goofspiel;
+#[cfg(feature = "kuhn")]
+pub mod kuhn;
+pub mod leduc;
+pub mod liars_dice;
+pub mod multi_player;
+pub mod normal_form;
+
+pub use self::game::{Action, Game, Player, State};
+pub use self::goofspiel::Goofspiel;
+pub use self::leduc::Leduc;
+pub use self::liars_dice::LiarsDice;
+pub use self::multi_player::MultiPlayerRunner;
+pub use self::normal_form::{NormalFormGame, NormalFormRunner};
+pub use crate::solver::GameCfr;
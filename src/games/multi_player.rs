@@ -0,0 +1,224 @@
+#![allow(clippy::cast_precision_loss)]
+//! Sampled self play for simultaneous move games with any number
+//! of players, each with their own regret minimizer. Payoffs come
+//! either from a tensor with an axis per player plus one for who is
+//! paid, or from a callback given everyone's action.
+//!
+//! With three or more players regret matching doesn't find a Nash
+//! equilibrium, but the empirical distribution of joint play it
+//! produces approaches a coarse correlated equilibrium. The runner
+//! keeps that distribution and `cce_gap` measures how far from one
+//! it is.
+use ndarray::prelude::*;
+use rand::{thread_rng, Rng};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+type PayoffFn = dyn Fn(&[usize]) -> Vec<Scalar> + Send + Sync;
+
+#[derive(Clone)]
+pub struct MultiPlayerRunner<M: RegretMinimizer> {
+    pub matchers: Vec<M>,
+    payoff: Arc<PayoffFn>,
+    pending_rewards: Vec<Vec<Scalar>>,
+    // How often each joint action has been played.
+    joint_counts: HashMap<Vec<usize>, u64>,
+    num_iterations: usize,
+}
+
+impl<M: RegretMinimizer + fmt::Debug> fmt::Debug for MultiPlayerRunner<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiPlayerRunner")
+            .field("matchers", &self.matchers)
+            .field("num_iterations", &self.num_iterations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M: RegretMinimizer> MultiPlayerRunner<M> {
+    /// A game where `payoff[[a_0, .., a_n-1, p]]` is what player `p`
+    /// wins when each player `i` plays `a_i`.
+    pub fn from_tensor(payoff: ArrayD<Scalar>) -> Result<Self, LittleError> {
+        let shape = payoff.shape();
+        let num_players = shape.len().saturating_sub(1);
+        if num_players == 0 || shape[num_players] != num_players {
+            return Err(LittleError::InvalidParameter(
+                "payoff needs an axis per player and a last axis of one payoff per player",
+            ));
+        }
+        let num_actions = shape[..num_players].to_vec();
+        Self::from_fn(num_actions, move |actions: &[usize]| {
+            let mut index = actions.to_vec();
+            index.push(0);
+            (0..actions.len())
+                .map(|p| {
+                    index[actions.len()] = p;
+                    payoff[IxDyn(&index)]
+                })
+                .collect()
+        })
+    }
+
+    /// A game where player `i` has `num_actions[i]` actions and
+    /// `payoff` returns every player's winnings for a joint action.
+    pub fn from_fn(
+        num_actions: Vec<usize>,
+        payoff: impl Fn(&[usize]) -> Vec<Scalar> + Send + Sync + 'static,
+    ) -> Result<Self, LittleError> {
+        let matchers = num_actions
+            .iter()
+            .map(|n| M::new(*n))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new_with_matchers(matchers, payoff)
+    }
+
+    /// Train already built matchers, one per player.
+    pub fn new_with_matchers(
+        matchers: Vec<M>,
+        payoff: impl Fn(&[usize]) -> Vec<Scalar> + Send + Sync + 'static,
+    ) -> Result<Self, LittleError> {
+        if matchers.len() < 2 {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least two players",
+            ));
+        }
+        Ok(Self {
+            pending_rewards: matchers
+                .iter()
+                .map(|m| vec![0.0; m.num_experts()])
+                .collect(),
+            matchers,
+            payoff: Arc::new(payoff),
+            joint_counts: HashMap::new(),
+            num_iterations: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn num_players(&self) -> usize {
+        self.matchers.len()
+    }
+
+    #[must_use]
+    pub fn num_iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    pub fn run_one(&mut self) {
+        self.run_one_with_rng(&mut thread_rng());
+    }
+
+    /// Sample an action for every player and queue up the rewards
+    /// each of their actions would have gotten against the others.
+    pub fn run_one_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        let actions: Vec<usize> = self.matchers.iter().map(|m| m.next_action(rng)).collect();
+        let mut deviation = actions.clone();
+        for (p, pending) in self.pending_rewards.iter_mut().enumerate() {
+            for (a, reward) in pending.iter_mut().enumerate() {
+                deviation[p] = a;
+                *reward += (self.payoff)(&deviation)[p];
+            }
+            deviation[p] = actions[p];
+        }
+        *self.joint_counts.entry(actions).or_default() += 1;
+    }
+
+    /// Feed every matcher the rewards queued since the last update.
+    pub fn update_regret(&mut self) -> Result<(), LittleError> {
+        for (m, pending) in self.matchers.iter_mut().zip(&mut self.pending_rewards) {
+            m.update_regret(pending)?;
+            pending.fill(0.0);
+        }
+        self.num_iterations += 1;
+        Ok(())
+    }
+
+    /// Every player's average strategy.
+    #[must_use]
+    pub fn best_weights(&self) -> Vec<Vec<Scalar>> {
+        self.matchers
+            .iter()
+            .map(RegretMinimizer::best_weight)
+            .collect()
+    }
+
+    /// The empirical distribution of joint play.
+    #[must_use]
+    pub fn joint_distribution(&self) -> HashMap<Vec<usize>, Scalar> {
+        let total: u64 = self.joint_counts.values().sum();
+        self.joint_counts
+            .iter()
+            .map(|(k, c)| (k.clone(), *c as Scalar / total as Scalar))
+            .collect()
+    }
+
+    /// The most any player gains, against the empirical joint
+    /// distribution, by committing to a single action in advance.
+    /// It's zero exactly at a coarse correlated equilibrium.
+    #[must_use]
+    pub fn cce_gap(&self) -> Scalar {
+        let joint = self.joint_distribution();
+        let mut gap: Scalar = 0.0;
+        for (p, m) in self.matchers.iter().enumerate() {
+            let mut deviations = vec![0.0; m.num_experts()];
+            let mut played = 0.0;
+            for (actions, prob) in &joint {
+                played += prob * (self.payoff)(actions)[p];
+                let mut deviation = actions.clone();
+                for (a, value) in deviations.iter_mut().enumerate() {
+                    deviation[p] = a;
+                    *value += prob * (self.payoff)(&deviation)[p];
+                }
+            }
+            for value in deviations {
+                gap = gap.max(value - played);
+            }
+        }
+        gap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_minority_game() {
+        // Three players each pick a side and whoever is alone wins.
+        let mut payoff = ArrayD::zeros(IxDyn(&[2, 2, 2, 3]));
+        for a in 0..2 {
+            for b in 0..2 {
+                for c in 0..2 {
+                    let actions = [a, b, c];
+                    for p in 0..3 {
+                        let same = actions.iter().filter(|x| **x == actions[p]).count();
+                        payoff[IxDyn(&[a, b, c, p])] = if same == 1 { 1.0 } else { 0.0 };
+                    }
+                }
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = MultiPlayerRunner::<RegretMatcher>::from_tensor(payoff).unwrap();
+        for _i in 0..10_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert_eq!(runner.num_players(), 3);
+        assert!(runner.cce_gap() < 0.05, "{}", runner.cce_gap());
+
+        assert!(
+            MultiPlayerRunner::<RegretMatcher>::from_tensor(ArrayD::zeros(IxDyn(&[2, 2, 3])))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,613 @@
+//! Two player normal form games given by payoff matrices, and a
+//! runner that trains a regret minimizer for each player by
+//! sampled self play. `RPSRunner` is this runner set up for rock
+//! paper scissors. Entry `[[a, b]]` of either matrix is the payoff
+//! when player one plays `a` and player two plays `b`.
+use ndarray::prelude::*;
+use rand::{thread_rng, Rng};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::best_response;
+use crate::convergence::{ConvergenceCheck, ConvergenceCriterion, ConvergenceReport};
+use crate::dist;
+use crate::errors::LittleError;
+use crate::kernels;
+use crate::regret_minimizer::{self, RegretMinimizer};
+use crate::scalar::Scalar;
+#[cfg(feature = "metrics")]
+use crate::telemetry::MatcherMetrics;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NormalFormGame {
+    payoff_one: Array2<Scalar>,
+    payoff_two: Array2<Scalar>,
+}
+
+impl NormalFormGame {
+    /// A general sum game with a payoff matrix for each player.
+    pub fn new(
+        payoff_one: Array2<Scalar>,
+        payoff_two: Array2<Scalar>,
+    ) -> Result<Self, LittleError> {
+        if payoff_one.is_empty() {
+            return Err(LittleError::InvalidParameter(
+                "payoff matrices must not be empty",
+            ));
+        }
+        if payoff_one.dim() != payoff_two.dim() {
+            return Err(LittleError::InvalidParameter(
+                "payoff matrices must have the same shape",
+            ));
+        }
+        Ok(Self {
+            payoff_one,
+            payoff_two,
+        })
+    }
+
+    /// A zero sum game where player two loses what player one wins.
+    pub fn zero_sum(payoff: Array2<Scalar>) -> Result<Self, LittleError> {
+        let payoff_two = payoff.mapv(|v| -v);
+        Self::new(payoff, payoff_two)
+    }
+
+    #[must_use]
+    pub fn rock_paper_scissors() -> Self {
+        Self::zero_sum(array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]]).unwrap()
+    }
+
+    /// The balanced generalization of rock paper scissors to an odd
+    /// number of actions, where every action beats half of the others
+    /// and loses to the other half. Action `a` beats `b` when `a - b`
+    /// is odd mod `n`, so `cyclic(3)` is rock paper scissors.
+    pub fn cyclic(n: usize) -> Result<Self, LittleError> {
+        if n < 3 || n.is_multiple_of(2) {
+            return Err(LittleError::InvalidParameter(
+                "cyclic games need an odd number of actions, at least 3",
+            ));
+        }
+        Self::zero_sum(Array2::from_shape_fn((n, n), |(a, b)| {
+            match (a + n - b) % n {
+                0 => 0.0,
+                d if d.is_multiple_of(2) => -1.0,
+                _ => 1.0,
+            }
+        }))
+    }
+
+    /// Rock, paper, scissors, Spock, lizard, in that order.
+    #[must_use]
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::cyclic(5).unwrap()
+    }
+
+    #[must_use]
+    pub fn num_actions_one(&self) -> usize {
+        self.payoff_one.nrows()
+    }
+
+    #[must_use]
+    pub fn num_actions_two(&self) -> usize {
+        self.payoff_one.ncols()
+    }
+
+    /// Whether player two always loses what player one wins.
+    #[must_use]
+    pub fn is_zero_sum(&self) -> bool {
+        self.payoff_one
+            .iter()
+            .zip(&self.payoff_two)
+            .all(|(a, b)| a + b == 0.0)
+    }
+
+    #[must_use]
+    pub fn payoff_one(&self) -> ArrayView2<'_, Scalar> {
+        self.payoff_one.view()
+    }
+
+    #[must_use]
+    pub fn payoff_two(&self) -> ArrayView2<'_, Scalar> {
+        self.payoff_two.view()
+    }
+
+    /// Player one's reward for each action when player two plays
+    /// `action_two`.
+    #[must_use]
+    pub fn rewards_one(&self, action_two: usize) -> ArrayView1<'_, Scalar> {
+        self.payoff_one.column(action_two)
+    }
+
+    /// Player two's reward for each action when player one plays
+    /// `action_one`.
+    #[must_use]
+    pub fn rewards_two(&self, action_one: usize) -> ArrayView1<'_, Scalar> {
+        self.payoff_two.row(action_one)
+    }
+
+    /// Player one's expected reward for each action against player
+    /// two's mixed `strategy_two`.
+    #[must_use]
+    pub fn expected_rewards_one(&self, strategy_two: &[Scalar]) -> Array1<Scalar> {
+        self.payoff_one.dot(&ArrayView1::from(strategy_two))
+    }
+
+    /// Player two's expected reward for each action against player
+    /// one's mixed `strategy_one`.
+    #[must_use]
+    pub fn expected_rewards_two(&self, strategy_one: &[Scalar]) -> Array1<Scalar> {
+        self.payoff_two.t().dot(&ArrayView1::from(strategy_one))
+    }
+
+    /// How much the two players could gain in total by each
+    /// switching to a best response against the other. It's zero
+    /// exactly when the strategies are a Nash equilibrium, and for
+    /// zero sum games it's the duality gap.
+    pub fn nash_gap(
+        &self,
+        strategy_one: &[Scalar],
+        strategy_two: &[Scalar],
+    ) -> Result<Scalar, LittleError> {
+        if strategy_one.len() != self.num_actions_one()
+            || strategy_two.len() != self.num_actions_two()
+        {
+            return Err(LittleError::InvalidParameter(
+                "strategies must have one entry per action",
+            ));
+        }
+        let gain = |payoff: ArrayView2<Scalar>, strategy: &[Scalar], opponent: &[Scalar]| {
+            let values = best_response::action_values(payoff, opponent);
+            let best = values[regret_minimizer::argmax(&values)];
+            best - kernels::dot(&values, strategy)
+        };
+        Ok(gain(self.payoff_one.view(), strategy_one, strategy_two)
+            + gain(self.payoff_two.t(), strategy_two, strategy_one))
+    }
+
+    /// The equilibrium where both players mix over every action,
+    /// found by making each player indifferent between all of the
+    /// other's actions. Square games like biased rock paper scissors
+    /// usually have one; it's an error if this game doesn't.
+    pub fn fully_mixed_equilibrium(&self) -> Result<(Vec<Scalar>, Vec<Scalar>), LittleError> {
+        if self.num_actions_one() != self.num_actions_two() {
+            return Err(LittleError::InvalidParameter(
+                "fully mixed equilibria need a square game",
+            ));
+        }
+        let strategy_one = indifference_strategy(self.payoff_two.t());
+        let strategy_two = indifference_strategy(self.payoff_one.view());
+        strategy_one
+            .zip(strategy_two)
+            .ok_or(LittleError::InvalidParameter(
+                "game has no fully mixed equilibrium",
+            ))
+    }
+}
+
+// The mixed strategy that makes every row of `payoff` pay the same,
+// found by solving `payoff * s = v * 1` with `s` summing to one.
+// `None` if the system is singular or `s` isn't a strategy.
+fn indifference_strategy(payoff: ArrayView2<Scalar>) -> Option<Vec<Scalar>> {
+    let n = payoff.nrows();
+    // Unknowns are s and then v.
+    let mut system = Array2::<Scalar>::zeros((n + 1, n + 2));
+    system.slice_mut(s![..n, ..n]).assign(&payoff);
+    system.slice_mut(s![..n, n]).fill(-1.0);
+    system.slice_mut(s![n, ..n]).fill(1.0);
+    system[[n, n + 1]] = 1.0;
+
+    // Gauss-Jordan elimination with partial pivoting.
+    for col in 0..=n {
+        let pivot = (col..=n)
+            .max_by(|a, b| system[[*a, col]].abs().total_cmp(&system[[*b, col]].abs()))
+            .unwrap();
+        if system[[pivot, col]].abs() < 1e-6 {
+            return None;
+        }
+        for j in 0..n + 2 {
+            system.swap([col, j], [pivot, j]);
+        }
+        let scale = system[[col, col]];
+        system.row_mut(col).mapv_inplace(|v| v / scale);
+        let pivot_row = system.row(col).to_owned();
+        for row in 0..=n {
+            if row != col {
+                let factor = system[[row, col]];
+                system.row_mut(row).scaled_add(-factor, &pivot_row);
+            }
+        }
+    }
+    let strategy: Vec<Scalar> = system.slice(s![..n, n + 1]).to_vec();
+    strategy.iter().all(|p| *p >= 0.0).then_some(strategy)
+}
+
+// Who player two is in a runner.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) enum Opponent {
+    // matcher_two, learning alongside player one.
+    Learning,
+    // A best response to matcher_one's average strategy (CFR-BR).
+    BestResponse,
+    // A mixed strategy that never changes.
+    Fixed(Vec<Scalar>),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NormalFormRunner<M1: RegretMinimizer, M2: RegretMinimizer = M1> {
+    game: NormalFormGame,
+    pub matcher_one: M1,
+    pub matcher_two: M2,
+    pending_reward_one: Array1<Scalar>,
+    pending_reward_two: Array1<Scalar>,
+    // Unless learning, player two ignores matcher_two.
+    opponent: Opponent,
+    // When set the rewards are the expected rewards against the
+    // opponent's current mixed strategy rather than against a
+    // sampled action.
+    full_feedback: bool,
+    num_iterations: usize,
+    // Record the exploitability after this many updates.
+    track_every: Option<usize>,
+    exploitability_series: Vec<(usize, Scalar)>,
+}
+
+impl<M1: RegretMinimizer, M2: RegretMinimizer> NormalFormRunner<M1, M2> {
+    pub fn new(game: NormalFormGame) -> Result<Self, LittleError> {
+        let matcher_one = M1::new(game.num_actions_one())?;
+        let matcher_two = M2::new(game.num_actions_two())?;
+        Self::new_with_matchers(game, matcher_one, matcher_two)
+    }
+
+    /// Train already built matchers, which can be different
+    /// algorithms or configured differently.
+    pub fn new_with_matchers(
+        game: NormalFormGame,
+        matcher_one: M1,
+        matcher_two: M2,
+    ) -> Result<Self, LittleError> {
+        if matcher_one.num_experts() != game.num_actions_one()
+            || matcher_two.num_experts() != game.num_actions_two()
+        {
+            return Err(LittleError::InvalidParameter(
+                "matchers must have one expert per action",
+            ));
+        }
+        Ok(Self {
+            matcher_one,
+            matcher_two,
+            pending_reward_one: Array1::zeros(game.num_actions_one()),
+            pending_reward_two: Array1::zeros(game.num_actions_two()),
+            game,
+            opponent: Opponent::Learning,
+            full_feedback: false,
+            num_iterations: 0,
+            track_every: None,
+            exploitability_series: Vec::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn game(&self) -> &NormalFormGame {
+        &self.game
+    }
+
+    /// Update against the opponent's whole mixed strategy instead
+    /// of a sampled action. There's no sampling noise so matrix
+    /// games converge far faster, at the cost of every action's
+    /// reward being computed each round.
+    #[must_use]
+    pub fn with_full_feedback(mut self) -> Self {
+        self.full_feedback = true;
+        self
+    }
+
+    pub fn run_one(&mut self) {
+        self.run_one_with_rng(&mut thread_rng());
+    }
+
+    /// Sample an action for each player and queue up the rewards
+    /// every action would have gotten against it.
+    pub fn run_one_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        if self.full_feedback {
+            match &self.opponent {
+                Opponent::Learning => {
+                    self.pending_reward_one += &self
+                        .game
+                        .expected_rewards_one(self.matcher_two.current_strategy());
+                }
+                Opponent::BestResponse => {
+                    let response = self.best_response_two().0;
+                    self.pending_reward_one += &self.game.rewards_one(response);
+                }
+                Opponent::Fixed(strategy) => {
+                    self.pending_reward_one += &self.game.expected_rewards_one(strategy);
+                }
+            }
+            self.pending_reward_two += &self
+                .game
+                .expected_rewards_two(self.matcher_one.current_strategy());
+            return;
+        }
+        let a1 = self.matcher_one.next_action(rng);
+        let a2 = match &self.opponent {
+            Opponent::Learning => self.matcher_two.next_action(rng),
+            Opponent::BestResponse => self.best_response_two().0,
+            Opponent::Fixed(strategy) => dist::sample_weights(strategy, rng),
+        };
+        self.pending_reward_one += &self.game.rewards_one(a2);
+        self.pending_reward_two += &self.game.rewards_two(a1);
+    }
+
+    /// Train until `criterion` is met, checking it every
+    /// `check_every` iterations, or until `max_iterations` have run.
+    pub fn run_until_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        criterion: ConvergenceCriterion,
+        check_every: usize,
+        max_iterations: usize,
+    ) -> Result<ConvergenceReport, LittleError> {
+        if check_every == 0 {
+            return Err(LittleError::InvalidParameter(
+                "check_every must be greater than 0",
+            ));
+        }
+        let mut check = ConvergenceCheck::new(criterion)?;
+        let mut exploitability = self.exploitability();
+        for i in 1..=max_iterations {
+            self.run_one_with_rng(rng);
+            self.update_regret()?;
+            if i.is_multiple_of(check_every) || i == max_iterations {
+                exploitability = self.exploitability();
+                // Only trained players' strategies can change.
+                let mut strategy = self.matcher_one.best_weight();
+                if self.opponent == Opponent::Learning {
+                    strategy.extend(self.matcher_two.best_weight());
+                }
+                if check.check(exploitability, &strategy) {
+                    return Ok(ConvergenceReport {
+                        iterations: i,
+                        exploitability,
+                        converged: true,
+                    });
+                }
+            }
+        }
+        Ok(ConvergenceReport {
+            iterations: max_iterations,
+            exploitability,
+            converged: false,
+        })
+    }
+
+    /// Feed both matchers the rewards queued since the last update.
+    pub fn update_regret(&mut self) -> Result<(), LittleError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("normal_form_update", opponent = ?self.opponent).entered();
+        self.matcher_one
+            .update_regret(self.pending_reward_one.as_slice().unwrap())?;
+        if self.opponent == Opponent::Learning {
+            self.matcher_two
+                .update_regret(self.pending_reward_two.as_slice().unwrap())?;
+        }
+        self.pending_reward_one.fill(0.0);
+        self.pending_reward_two.fill(0.0);
+        self.num_iterations += 1;
+        if let Some(every) = self.track_every {
+            if self.num_iterations.is_multiple_of(every) {
+                let exploitability = self.exploitability();
+                self.exploitability_series
+                    .push((self.num_iterations, exploitability));
+            }
+        }
+        Ok(())
+    }
+
+    /// Have player two play `strategy` from now on instead of
+    /// learning. Only player one is trained.
+    pub fn set_fixed_opponent(&mut self, strategy: &[Scalar]) -> Result<(), LittleError> {
+        if strategy.len() != self.game.num_actions_two() {
+            return Err(LittleError::InvalidParameter(
+                "strategy must have one entry per action",
+            ));
+        }
+        self.opponent = Opponent::Fixed(regret_minimizer::normalize_strategy(strategy)?);
+        Ok(())
+    }
+
+    /// Record `exploitability()` every `every` updates.
+    pub fn track_exploitability(&mut self, every: usize) -> Result<(), LittleError> {
+        if every == 0 {
+            return Err(LittleError::InvalidParameter(
+                "every must be greater than 0",
+            ));
+        }
+        self.track_every = Some(every);
+        Ok(())
+    }
+
+    /// The recorded `(update, exploitability)` pairs, oldest first.
+    #[must_use]
+    pub fn exploitability_series(&self) -> &[(usize, Scalar)] {
+        &self.exploitability_series
+    }
+
+    /// Have player two best respond to player one's average
+    /// strategy from now on instead of learning.
+    pub fn set_best_response_opponent(&mut self) {
+        self.opponent = Opponent::BestResponse;
+    }
+
+    /// Player two's best response to player one's average
+    /// strategy and how much it wins on average.
+    #[must_use]
+    pub fn best_response_two(&self) -> (usize, Scalar) {
+        best_response::best_response(self.game.payoff_two().t(), &self.matcher_one.best_weight())
+    }
+
+    /// The Nash gap of the two players' average strategies, where
+    /// a fixed opponent's strategy is its average. When player two
+    /// best responds it's how much player one's average loses to
+    /// that response.
+    #[must_use]
+    pub fn exploitability(&self) -> Scalar {
+        let strategy_two = match &self.opponent {
+            Opponent::Learning => self.matcher_two.best_weight(),
+            Opponent::BestResponse => return self.best_response_two().1,
+            Opponent::Fixed(strategy) => strategy.clone(),
+        };
+        self.game
+            .nash_gap(&self.matcher_one.best_weight(), &strategy_two)
+            .expect("strategies have one entry per action")
+    }
+
+    /// Player one's average strategy.
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        self.matcher_one.best_weight()
+    }
+
+    /// Report player one's matcher and how exploitable its
+    /// average strategy is.
+    #[cfg(feature = "metrics")]
+    pub fn observe_metrics(&self, metrics: &mut MatcherMetrics) {
+        metrics.observe(&self.matcher_one);
+        metrics.observe_exploitability(self.best_response_two().1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_prisoners_dilemma() {
+        // Cooperate then defect. Defecting is dominant for both.
+        let game = NormalFormGame::new(
+            array![[-1.0, -3.0], [0.0, -2.0]],
+            array![[-1.0, 0.0], [-3.0, -2.0]],
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = NormalFormRunner::<RegretMatcher>::new(game).unwrap();
+        for _i in 0..1_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert!(runner.best_weight()[1] > 0.95);
+        assert!(runner.matcher_two.best_weight()[1] > 0.95);
+        assert!(runner.exploitability() < 0.1);
+    }
+
+    #[test]
+    fn test_fixed_opponent() {
+        // Matching pennies, where a biased opponent is exploited by
+        // always matching its favourite.
+        let game = NormalFormGame::zero_sum(array![[1.0, -1.0], [-1.0, 1.0]]).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = NormalFormRunner::<RegretMatcher>::new(game).unwrap();
+        runner.set_fixed_opponent(&[3.0, 1.0]).unwrap();
+        for _i in 0..1_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert!(runner.best_weight()[0] > 0.9);
+        assert_eq!(runner.matcher_two.num_updates(), 0);
+        assert!(runner.set_fixed_opponent(&[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_run_until_fixed_opponent() {
+        let game = NormalFormGame::zero_sum(array![[1.0, -1.0], [-1.0, 1.0]]).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = NormalFormRunner::<RegretMatcher>::new(game).unwrap();
+        runner.set_fixed_opponent(&[3.0, 1.0]).unwrap();
+        let report = runner
+            .run_until_with_rng(
+                &mut rng,
+                ConvergenceCriterion::StrategyChange(0.01),
+                100,
+                10_000,
+            )
+            .unwrap();
+        assert!(report.converged);
+        assert!(runner.best_weight()[0] > 0.9);
+        assert_eq!(runner.matcher_two.num_updates(), 0);
+    }
+
+    #[test]
+    fn test_cyclic() {
+        assert_eq!(
+            NormalFormGame::cyclic(3).unwrap(),
+            NormalFormGame::rock_paper_scissors()
+        );
+        let rpsls = NormalFormGame::rock_paper_scissors_lizard_spock();
+        // Rock crushes lizard and Spock vaporizes rock.
+        assert_eq!(rpsls.payoff_one()[[0, 4]], 1.0);
+        assert_eq!(rpsls.payoff_one()[[0, 3]], -1.0);
+        assert!(rpsls
+            .payoff_one()
+            .rows()
+            .into_iter()
+            .all(|r| r.sum() == 0.0));
+        assert!(NormalFormGame::cyclic(4).is_err());
+    }
+
+    #[test]
+    fn test_fully_mixed_equilibrium() {
+        // Winning with rock pays 2.
+        let game =
+            NormalFormGame::zero_sum(array![[0.0, -1.0, 2.0], [1.0, 0.0, -1.0], [-2.0, 1.0, 0.0]])
+                .unwrap();
+        let (one, two) = game.fully_mixed_equilibrium().unwrap();
+        for (p, expected) in one
+            .iter()
+            .chain(&two)
+            .zip([0.25, 0.5, 0.25, 0.25, 0.5, 0.25])
+        {
+            assert!((p - expected).abs() < 1e-5);
+        }
+        assert!(game.nash_gap(&one, &two).unwrap() < 1e-5);
+
+        // Defecting dominates, so cooperating is never mixed in.
+        let dilemma = NormalFormGame::new(
+            array![[3.0, 0.0], [5.0, 1.0]],
+            array![[3.0, 5.0], [0.0, 1.0]],
+        )
+        .unwrap();
+        assert!(dilemma.fully_mixed_equilibrium().is_err());
+    }
+
+    #[test]
+    fn test_shapes() {
+        let game = NormalFormGame::zero_sum(array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]).unwrap();
+        assert_eq!(game.num_actions_one(), 2);
+        assert_eq!(game.num_actions_two(), 3);
+        assert_eq!(game.rewards_two(1), array![-4.0, -5.0, -6.0]);
+        assert_eq!(
+            game.expected_rewards_two(&[0.5, 0.5]),
+            array![-2.5, -3.5, -4.5]
+        );
+        assert!(NormalFormGame::new(Array2::zeros((2, 2)), Array2::zeros((2, 3))).is_err());
+    }
+
+    #[test]
+    fn test_nash_gap() {
+        let game = NormalFormGame::rock_paper_scissors();
+        let uniform = [1.0 / 3.0; 3];
+        assert!(game.nash_gap(&uniform, &uniform).unwrap().abs() < 1e-6);
+        // Paper beats pure rock for a gain of one, and rock's best
+        // reply to uniform gains nothing.
+        assert!((game.nash_gap(&[1.0, 0.0, 0.0], &uniform).unwrap() - 1.0).abs() < 1e-6);
+        assert!(game.nash_gap(&uniform, &[0.5, 0.5]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+#![allow(clippy::cast_precision_loss)]
+//! Plain CFR regret matching with the cumulative regrets and the
+//! average strategy stored as 16 bit floats. Everything is computed
+//! in `Scalar` and only rounded when stored, which shrinks the
+//! learned state of a matcher by half. That matters when a solver
+//! holds hundreds of millions of them.
+//!
+//! f16 has about three significant digits, so two tricks keep
+//! rounding in check. The regrets share one `Scalar` scale chosen
+//! so the largest is one, which keeps them all in the range f16 is
+//! precise in and means they can never overflow. The average is
+//! kept as a running mean rather than a sum so it stays between
+//! zero and one. Late in a long solve an update can be too small to
+//! move the mean, so linear averaging, which weighs recent
+//! strategies more, is the default.
+use half::f16;
+use rand::Rng;
+use smallvec::SmallVec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::Compensated;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{Accumulator, Scalar};
+use crate::small::Inline;
+
+#[cfg(not(feature = "f64"))]
+fn encode(v: Scalar) -> f16 {
+    f16::from_f32(v)
+}
+#[cfg(feature = "f64")]
+fn encode(v: Scalar) -> f16 {
+    f16::from_f64(v)
+}
+
+#[cfg(not(feature = "f64"))]
+fn decode(v: f16) -> Scalar {
+    v.to_f32()
+}
+#[cfg(feature = "f64")]
+fn decode(v: f16) -> Scalar {
+    v.to_f64()
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HalfRegretMatcher {
+    // The chance each expert has of being chosen. This is what's
+    // played so it's kept at full precision.
+    p: Inline<Scalar>,
+    // Cumulative regret divided by `regret_scale`.
+    regrets: Inline<f16>,
+    regret_scale: Scalar,
+    // The weighted mean of the strategies played so far.
+    average: Inline<f16>,
+    // The total weight behind `average`.
+    average_weight: Compensated<Accumulator>,
+    averaging: AveragingScheme,
+    num_updates: usize,
+}
+
+impl HalfRegretMatcher {
+    // Add `strategy`, or the current one, to the average for the
+    // latest update.
+    fn accumulate(&mut self, strategy: Option<&[Scalar]>, weight: Scalar) {
+        let Some(step) =
+            self.averaging
+                .mean_step(self.num_updates, &mut self.average_weight, weight)
+        else {
+            return;
+        };
+        let step = step as Scalar;
+        let strategy = strategy.unwrap_or(&self.p);
+        for (a, p) in self.average.iter_mut().zip(strategy) {
+            let mean = decode(*a);
+            *a = encode(mean + step * (p - mean));
+        }
+    }
+}
+
+impl RegretMinimizer for HalfRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        if num_experts == 0 {
+            return Err(LittleError::InvalidParameter(
+                "num_experts must be greater than 0",
+            ));
+        }
+        Ok(Self {
+            p: SmallVec::from_elem(1.0 / num_experts as Scalar, num_experts),
+            regrets: SmallVec::from_elem(f16::ZERO, num_experts),
+            regret_scale: 1.0,
+            average: SmallVec::from_elem(f16::ZERO, num_experts),
+            average_weight: Compensated::new(0.0),
+            averaging: AveragingScheme::Linear,
+            num_updates: 0,
+        })
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        dist::sample_weights(&self.p, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.accumulate(None, options.average_weight);
+        }
+
+        // Work out the new regrets at full precision in `p`, which
+        // is about to be overwritten anyway.
+        let expected: Scalar = self.p.iter().zip(rewards).map(|(p, r)| p * r).sum();
+        let mut largest: Scalar = 0.0;
+        for ((p, stored), r) in self.p.iter_mut().zip(&self.regrets).zip(rewards) {
+            *p = decode(*stored) * self.regret_scale + options.regret_weight * (r - expected);
+            largest = largest.max(p.abs());
+        }
+        if largest > 0.0 {
+            self.regret_scale = largest;
+        }
+        for (stored, r) in self.regrets.iter_mut().zip(&self.p) {
+            *stored = encode(r / self.regret_scale);
+        }
+
+        // Regret matching on what was stored, so that a matcher
+        // loaded from its regrets plays the same strategy.
+        let mut positive_sum = 0.0;
+        for (p, stored) in self.p.iter_mut().zip(&self.regrets) {
+            *p = decode(*stored).max(0.0);
+            positive_sum += *p;
+        }
+        if positive_sum > 0.0 {
+            self.p.iter_mut().for_each(|p| *p /= positive_sum);
+        } else {
+            #[cfg(feature = "profiling")]
+            crate::profiling::record(|s| s.uniform_fallbacks += 1);
+            regret_minimizer::fill_uniform(&mut self.p);
+        }
+        Ok(())
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_strategy_len(strategy, self.p.len())?;
+        self.accumulate(Some(strategy), weight);
+        Ok(())
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        &self.p
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        let average: Vec<Scalar> = self.average.iter().map(|a| decode(*a)).collect();
+        let total: Scalar = average.iter().sum();
+        if self.average_weight.value() <= 0.0 || total <= 0.0 {
+            return self.p.to_vec();
+        }
+        // Rounding means the stored mean may not quite sum to one.
+        average.iter().map(|a| a / total).collect()
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.regrets
+            .iter()
+            .map(|r| decode(*r) * self.regret_scale)
+            .collect()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.average.fill(f16::ZERO);
+        self.average_weight = Compensated::new(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.regrets.fill(f16::ZERO);
+        self.regret_scale = 1.0;
+        regret_minimizer::fill_uniform(&mut self.p);
+        Ok(())
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        let largest = regrets.iter().fold(0.0, |acc: Scalar, r| acc.max(r.abs()));
+        if largest > 0.0 {
+            matcher.regret_scale = largest;
+        }
+        for (stored, r) in matcher.regrets.iter_mut().zip(regrets) {
+            *stored = encode(r / matcher.regret_scale);
+        }
+        regret_minimizer::regret_match(regrets, &mut matcher.p);
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::NormalFormGame;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    // Self play on rock paper scissors, returning the Nash gap of
+    // the average strategies.
+    fn exploitability<M: RegretMinimizer>(iterations: usize) -> Scalar {
+        let game = NormalFormGame::rock_paper_scissors();
+        let mut one = M::new(3).unwrap().with_averaging(AveragingScheme::Linear);
+        let mut two = M::new(3).unwrap().with_averaging(AveragingScheme::Linear);
+        one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        for _i in 0..iterations {
+            let r1 = game.expected_rewards_one(two.current_strategy());
+            let r2 = game.expected_rewards_two(one.current_strategy());
+            one.update_regret(r1.as_slice().unwrap()).unwrap();
+            two.update_regret(r2.as_slice().unwrap()).unwrap();
+        }
+        game.nash_gap(&one.best_weight(), &two.best_weight())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rps_accuracy() {
+        let full = exploitability::<VanillaCfrRegretMatcher>(5_000);
+        let half = exploitability::<HalfRegretMatcher>(5_000);
+        // Self play hasn't fully settled down at either precision,
+        // what matters is that halving the storage costs nothing
+        // noticeable.
+        assert!(half < 0.1);
+        assert!(half < full + 0.01);
+    }
+
+    #[test]
+    fn test_regret_scale() {
+        let mut m = HalfRegretMatcher::new(2).unwrap();
+        // Far past the largest f16 once summed.
+        for _i in 0..10 {
+            m.update_regret(&[100_000.0, 0.0]).unwrap();
+        }
+        let regrets = m.cumulative_regrets();
+        assert!(regrets.iter().all(|r| r.is_finite()));
+        assert!((regrets[1] / -950_000.0 - 1.0).abs() < 0.01);
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+    }
+}
@@ -0,0 +1,212 @@
+//! Record how a minimizer's strategies change over time. Every
+//! `every` updates a snapshot of the current and average strategy
+//! is saved. Only the most recent `capacity` snapshots are kept so
+//! memory stays bounded no matter how long the solve runs.
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+
+const DEFAULT_EVERY: usize = 1;
+const DEFAULT_CAPACITY: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StrategySnapshot {
+    /// How many updates had been made when this was taken.
+    pub update: usize,
+    pub current: Vec<Scalar>,
+    pub average: Vec<Scalar>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HistoryWrapper<M: RegretMinimizer> {
+    inner: M,
+    // Take a snapshot after this many updates.
+    every: usize,
+    capacity: usize,
+    // Updates since the last snapshot.
+    since_snapshot: usize,
+    history: VecDeque<StrategySnapshot>,
+}
+
+impl<M: RegretMinimizer> HistoryWrapper<M> {
+    /// Snapshot `inner` every `every` updates keeping at most
+    /// `capacity` of the most recent snapshots.
+    pub fn new_with_params(inner: M, every: usize, capacity: usize) -> Result<Self, LittleError> {
+        if every == 0 {
+            return Err(LittleError::InvalidParameter("every must be positive"));
+        }
+        if capacity == 0 {
+            return Err(LittleError::InvalidParameter("capacity must be positive"));
+        }
+        Ok(Self {
+            inner,
+            every,
+            capacity,
+            since_snapshot: 0,
+            history: VecDeque::with_capacity(capacity),
+        })
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// The recorded snapshots, oldest first.
+    #[must_use]
+    pub fn history(&self) -> &VecDeque<StrategySnapshot> {
+        &self.history
+    }
+
+    /// Forget all the recorded snapshots.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    fn record(&mut self) {
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(StrategySnapshot {
+            update: self.inner.num_updates(),
+            current: self.inner.current_strategy().to_vec(),
+            average: self.inner.best_weight(),
+        });
+    }
+}
+
+impl<M: RegretMinimizer> RegretMinimizer for HistoryWrapper<M> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(M::new(num_experts)?, DEFAULT_EVERY, DEFAULT_CAPACITY)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.inner.num_experts()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.inner.num_updates()
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        self.inner.hyperparameters()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.inner.next_action(rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.inner.update_regret_with_options(rewards, options)?;
+        self.since_snapshot += 1;
+        if self.since_snapshot >= self.every {
+            self.since_snapshot = 0;
+            self.record();
+        }
+        Ok(())
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.inner.accumulate_average(strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.inner.current_strategy()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.inner.cumulative_regrets()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.inner = self.inner.with_averaging(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.inner.averaging()
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.since_snapshot = 0;
+        self.history.clear();
+        self.inner.reset()
+    }
+
+    /// The history is kept, since the average strategy is.
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.inner.soft_reset()
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(
+            M::warm_start_from_strategy(strategy)?,
+            DEFAULT_EVERY,
+            DEFAULT_CAPACITY,
+        )
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(
+            M::warm_start_from_regrets(regrets)?,
+            DEFAULT_EVERY,
+            DEFAULT_CAPACITY,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_bounded_history() {
+        let inner = VanillaCfrRegretMatcher::new(2).unwrap();
+        let mut m = HistoryWrapper::new_with_params(inner, 2, 3).unwrap();
+        for _i in 0..10 {
+            m.update_regret(&[1.0, 0.0]).unwrap();
+        }
+        let updates: Vec<usize> = m.history().iter().map(|s| s.update).collect();
+        assert_eq!(updates, vec![6, 8, 10]);
+        assert_eq!(m.history()[2].current, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_counts_inner_updates() {
+        let mut inner = VanillaCfrRegretMatcher::new(2).unwrap();
+        for _i in 0..3 {
+            inner.update_regret(&[1.0, 0.0]).unwrap();
+        }
+        let mut m = HistoryWrapper::new_with_params(inner, 2, 3).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(m.num_updates(), 5);
+        assert_eq!(m.history()[0].update, 5);
+        m.reset().unwrap();
+        assert_eq!(m.num_updates(), 0);
+    }
+}
@@ -0,0 +1,215 @@
+//! The inner loops shared by the regret matching family. With the
+//! `simd` feature they run several lanes at a time using the wide
+//! crate, which picks the best instructions the target supports at
+//! compile time. Without it they're plain loops. Both give the same
+//! answer up to the order floating point sums are taken in.
+//!
+//! With the `blas` feature, dot products and axpy updates of at
+//! least `BLAS_MIN_LEN` entries go to BLAS instead, which pays off
+//! for matchers with thousands of actions. Shorter vectors always
+//! use the loops here since calling out to BLAS costs more than it
+//! saves. The feature doesn't pick a BLAS, so the final binary has
+//! to link one, e.g. through `blas-src`.
+use ndarray::ArrayView1;
+
+use crate::scalar::Scalar;
+
+#[cfg(all(feature = "simd", not(feature = "f64")))]
+type Lanes = wide::f32x8;
+#[cfg(all(feature = "simd", feature = "f64"))]
+type Lanes = wide::f64x4;
+
+#[cfg(feature = "simd")]
+const LANES: usize = std::mem::size_of::<Lanes>() / std::mem::size_of::<Scalar>();
+
+#[cfg(feature = "simd")]
+fn load(values: &[Scalar]) -> Lanes {
+    Lanes::from(<[Scalar; LANES]>::try_from(values).unwrap())
+}
+
+/// The dot product of two slices, stopping at the end of the
+/// shorter one.
+#[cfg(not(feature = "simd"))]
+#[must_use]
+pub fn dot(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// The dot product of two slices, stopping at the end of the
+/// shorter one.
+#[cfg(feature = "simd")]
+#[must_use]
+pub fn dot(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let split = a.len() - a.len() % LANES;
+    let mut acc = Lanes::splat(0.0);
+    for (x, y) in a[..split]
+        .chunks_exact(LANES)
+        .zip(b[..split].chunks_exact(LANES))
+    {
+        acc += load(x) * load(y);
+    }
+    let tail: Scalar = a[split..].iter().zip(&b[split..]).map(|(x, y)| x * y).sum();
+    acc.reduce_add() + tail
+}
+
+/// From this many entries on the `blas` feature sends dot products
+/// and axpy updates to BLAS.
+pub const BLAS_MIN_LEN: usize = 1024;
+
+/// `dot` for array views. With the `blas` feature very wide views
+/// go through ndarray, which calls BLAS. Views that aren't
+/// contiguous always do.
+#[must_use]
+pub fn dot_view(a: ArrayView1<Scalar>, b: ArrayView1<Scalar>) -> Scalar {
+    match (a.as_slice(), b.as_slice()) {
+        (Some(a), Some(b)) if !(cfg!(feature = "blas") && a.len() >= BLAS_MIN_LEN) => dot(a, b),
+        _ => a.dot(&b),
+    }
+}
+
+/// Add `alpha * x` to `y`, stopping at the end of the shorter
+/// slice. With the `blas` feature very wide slices go to BLAS.
+pub fn axpy(alpha: Scalar, x: &[Scalar], y: &mut [Scalar]) {
+    let len = x.len().min(y.len());
+    #[cfg(feature = "blas")]
+    if len >= BLAS_MIN_LEN {
+        if let Ok(n) = i32::try_from(len) {
+            blas_axpy(n, alpha, x, y);
+            return;
+        }
+    }
+    axpy_loop(alpha, &x[..len], &mut y[..len]);
+}
+
+#[cfg(all(feature = "blas", not(feature = "f64")))]
+fn blas_axpy(n: i32, alpha: Scalar, x: &[Scalar], y: &mut [Scalar]) {
+    // Safe since both slices have at least n entries.
+    unsafe { cblas_sys::cblas_saxpy(n, alpha, x.as_ptr(), 1, y.as_mut_ptr(), 1) }
+}
+
+#[cfg(all(feature = "blas", feature = "f64"))]
+fn blas_axpy(n: i32, alpha: Scalar, x: &[Scalar], y: &mut [Scalar]) {
+    // Safe since both slices have at least n entries.
+    unsafe { cblas_sys::cblas_daxpy(n, alpha, x.as_ptr(), 1, y.as_mut_ptr(), 1) }
+}
+
+#[cfg(not(feature = "simd"))]
+fn axpy_loop(alpha: Scalar, x: &[Scalar], y: &mut [Scalar]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += alpha * x;
+    }
+}
+
+#[cfg(feature = "simd")]
+fn axpy_loop(alpha: Scalar, x: &[Scalar], y: &mut [Scalar]) {
+    let split = x.len() - x.len() % LANES;
+    let a = Lanes::splat(alpha);
+    for (y, x) in y[..split]
+        .chunks_exact_mut(LANES)
+        .zip(x[..split].chunks_exact(LANES))
+    {
+        y.copy_from_slice(&(load(y) + a * load(x)).to_array());
+    }
+    for (y, x) in y[split..].iter_mut().zip(&x[split..]) {
+        *y += alpha * x;
+    }
+}
+
+/// Write the positive part of `regrets` into `out` and return its
+/// sum.
+#[cfg(not(feature = "simd"))]
+fn clamp_positive(regrets: &[Scalar], out: &mut [Scalar]) -> Scalar {
+    let mut sum = 0.0;
+    for (o, r) in out.iter_mut().zip(regrets) {
+        *o = Scalar::max(0.0, *r);
+        sum += *o;
+    }
+    sum
+}
+
+#[cfg(feature = "simd")]
+fn clamp_positive(regrets: &[Scalar], out: &mut [Scalar]) -> Scalar {
+    let split = regrets.len() - regrets.len() % LANES;
+    let zero = Lanes::splat(0.0);
+    let mut acc = zero;
+    for (o, r) in out[..split]
+        .chunks_exact_mut(LANES)
+        .zip(regrets[..split].chunks_exact(LANES))
+    {
+        let v = load(r).max(zero);
+        o.copy_from_slice(&v.to_array());
+        acc += v;
+    }
+    let mut tail = 0.0;
+    for (o, r) in out[split..].iter_mut().zip(&regrets[split..]) {
+        *o = Scalar::max(0.0, *r);
+        tail += *o;
+    }
+    acc.reduce_add() + tail
+}
+
+/// Divide every value by `divisor`.
+#[cfg(not(feature = "simd"))]
+fn divide(values: &mut [Scalar], divisor: Scalar) {
+    values.iter_mut().for_each(|v| *v /= divisor);
+}
+
+#[cfg(feature = "simd")]
+fn divide(values: &mut [Scalar], divisor: Scalar) {
+    let split = values.len() - values.len() % LANES;
+    let d = Lanes::splat(divisor);
+    for chunk in values[..split].chunks_exact_mut(LANES) {
+        chunk.copy_from_slice(&(load(chunk) / d).to_array());
+    }
+    values[split..].iter_mut().for_each(|v| *v /= divisor);
+}
+
+/// Regret matching: play each action in proportion to its positive
+/// regret, or uniformly when no regret is positive.
+pub fn regret_match(regrets: &[Scalar], out: &mut [Scalar]) {
+    let positive_sum = clamp_positive(regrets, out);
+    if positive_sum > 0.0 {
+        divide(out, positive_sum);
+    } else {
+        #[cfg(feature = "profiling")]
+        crate::profiling::record(|s| s.uniform_fallbacks += 1);
+        out.fill(1.0 / out.len() as Scalar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels() {
+        // Long enough to fill some lanes and leave a tail.
+        let a: Vec<Scalar> = (0..13).map(|i| i as Scalar - 6.0).collect();
+        let b: Vec<Scalar> = (0..13).map(|i| 0.5 * i as Scalar).collect();
+        let expected: Scalar = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        assert!((dot(&a, &b) - expected).abs() < 1e-4);
+        // Both the scalar and SIMD paths stop at the shorter slice.
+        assert_eq!(dot(&a[..3], &b), dot(&a[..3], &b[..3]));
+        assert_eq!(dot(&a, &b[..3]), dot(&a[..3], &b[..3]));
+
+        let mut y = b.clone();
+        axpy(2.0, &a, &mut y);
+        for ((y, x), b) in y.iter().zip(&a).zip(&b) {
+            assert_eq!(*y, b + 2.0 * x);
+        }
+        let mut short = vec![1.0; 3];
+        axpy(1.0, &a, &mut short);
+        assert_eq!(short, vec![-5.0, -4.0, -3.0]);
+
+        let mut out = vec![0.0; 13];
+        regret_match(&a, &mut out);
+        let positive: Scalar = a.iter().filter(|r| **r > 0.0).sum();
+        for (o, r) in out.iter().zip(&a) {
+            assert!((o - r.max(0.0) / positive).abs() < 1e-6);
+        }
+        regret_match(&[-1.0; 9], &mut out[..9]);
+        assert_eq!(out[0], 1.0 / 9.0);
+    }
+}
@@ -0,0 +1,342 @@
+#![allow(clippy::cast_precision_loss)]
+//! Discounted CFR with the discounts applied lazily.
+//!
+//! A straight implementation of DCFR multiplies every regret and
+//! every entry of the cumulative strategy each iteration. Those
+//! multipliers only depend on the iteration and the sign of the
+//! regret, so instead we keep one running scale for positive
+//! regrets, one for negative regrets and one for the strategy sum.
+//! Stored values are in units of those scales. Regret matching
+//! only needs the ratio between positive regrets and averaging
+//! only needs the ratio between strategy sums, so the scales
+//! cancel out and never have to be applied to read a strategy.
+//! They are folded back in when a scale gets too small to be
+//! represented accurately or when the real regrets are asked for.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::discount::DiscountParams;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::kernels;
+use crate::regret_minimizer::{self, MatcherState, RegretMinimizer, StateTransfer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+// Below this a scale is folded into the stored values.
+const MIN_SCALE: Scalar = 1e-20;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LazyDcfrRegretMatcher {
+    params: DiscountParams,
+    // Regrets in units of positive_scale or negative_scale
+    // depending on their sign.
+    scaled_regret: Array1<Scalar>,
+    positive_scale: Scalar,
+    negative_scale: Scalar,
+    // Cumulative strategy in units of strategy_scale.
+    scaled_sum_p: CompensatedArray<Accumulator>,
+    strategy_scale: Accumulator,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl LazyDcfrRegretMatcher {
+    pub fn new_with_params(
+        num_experts: usize,
+        params: DiscountParams,
+    ) -> Result<Self, LittleError> {
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            params,
+            scaled_regret: Array1::zeros(num_experts),
+            positive_scale: 1.0,
+            negative_scale: 1.0,
+            scaled_sum_p: CompensatedArray::zeros(num_experts),
+            strategy_scale: 1.0,
+            averaging: AveragingScheme::Discounted(params.gamma),
+            p: Array1::from(p),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn params(&self) -> DiscountParams {
+        self.params
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.num_updates += 1;
+        let t = self.num_updates;
+
+        if options.contribute_to_average {
+            self.accumulate_strategy(t, None, options.average_weight);
+        }
+
+        // The scales the regrets will be in once this iteration's
+        // discount has been applied.
+        let next_positive = self.positive_scale * self.params.positive_factor(t);
+        let next_negative = self.negative_scale * self.params.negative_factor(t);
+
+        let expected = kernels::dot_view(self.p.view(), reward_array);
+        for (v, r) in self.scaled_regret.iter_mut().zip(reward_array.iter()) {
+            let old_scale = if *v >= 0.0 {
+                self.positive_scale
+            } else {
+                self.negative_scale
+            };
+            let regret = *v * old_scale + options.regret_weight * (r - expected);
+            // This iteration's discount is applied by moving on to
+            // the next scale, so store in units of the current one.
+            let new_scale = if regret >= 0.0 {
+                self.positive_scale
+            } else {
+                self.negative_scale
+            };
+            *v = regret / new_scale;
+        }
+        self.positive_scale = next_positive;
+        self.negative_scale = next_negative;
+        self.fold_small_scales();
+
+        // All positive entries share a scale so it cancels out here.
+        regret_minimizer::regret_match(
+            self.scaled_regret.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    // Discount by moving the scale, then accumulate the strategy
+    // that was played, the current one unless given, in units of
+    // the new scale.
+    fn accumulate_strategy(&mut self, t: usize, strategy: Option<&[Scalar]>, extra: Scalar) {
+        let decay = self.averaging.decay(t) as Accumulator;
+        if decay == 0.0 {
+            self.scaled_sum_p.fill(0.0);
+            self.strategy_scale = 1.0;
+        } else {
+            self.strategy_scale *= decay;
+        }
+        let weight = (self.averaging.weight(t) * extra) as Accumulator / self.strategy_scale;
+        let strategy = strategy.unwrap_or(self.p.as_slice().unwrap());
+        self.scaled_sum_p
+            .scaled_add(weight, ArrayView1::from(strategy));
+    }
+
+    fn fold_small_scales(&mut self) {
+        if self.positive_scale < MIN_SCALE || self.negative_scale < MIN_SCALE {
+            let (pos, neg) = (self.positive_scale, self.negative_scale);
+            self.scaled_regret
+                .mapv_inplace(|v| if v >= 0.0 { v * pos } else { v * neg });
+            self.positive_scale = 1.0;
+            self.negative_scale = 1.0;
+        }
+        if self.strategy_scale < MIN_SCALE as Accumulator {
+            self.scaled_sum_p.scale(self.strategy_scale);
+            self.strategy_scale = 1.0;
+        }
+    }
+
+    /// The discounted cumulative regrets with all the deferred
+    /// discounts applied.
+    #[must_use]
+    pub fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.scaled_regret
+            .iter()
+            .map(|v| {
+                if *v >= 0.0 {
+                    v * self.positive_scale
+                } else {
+                    v * self.negative_scale
+                }
+            })
+            .collect()
+    }
+}
+
+impl RegretMinimizer for LazyDcfrRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DiscountParams::recommended())
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![
+            ("alpha", self.params.alpha),
+            ("beta", self.params.beta),
+            ("gamma", self.params.gamma),
+        ]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_strategy_len(strategy, self.p.len())?;
+        self.accumulate_strategy(self.num_updates, Some(strategy), weight);
+        self.fold_small_scales();
+        Ok(())
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        // The strategy scale cancels when normalizing.
+        AveragingScheme::average(self.scaled_sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        Self::cumulative_regrets(self)
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.scaled_sum_p.fill(0.0);
+        self.strategy_scale = 1.0;
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.scaled_regret.fill(0.0);
+        self.positive_scale = 1.0;
+        self.negative_scale = 1.0;
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.import_state(&MatcherState::from_strategy(strategy)?)?;
+        Ok(matcher)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.import_state(&MatcherState::from_regrets(regrets))?;
+        Ok(matcher)
+    }
+}
+
+impl StateTransfer for LazyDcfrRegretMatcher {
+    fn export_state(&self) -> MatcherState {
+        MatcherState {
+            num_updates: self.num_updates,
+            cumulative_regret: self.cumulative_regrets(),
+            cumulative_strategy: scalar::narrow(
+                (&self.scaled_sum_p.values() * self.strategy_scale).view(),
+            ),
+        }
+    }
+
+    fn import_state(&mut self, state: &MatcherState) -> Result<(), LittleError> {
+        regret_minimizer::check_state(state, self.p.len())?;
+        // Discounting picks up at the imported iteration count.
+        self.num_updates = state.num_updates;
+        self.scaled_regret = Array1::from(state.cumulative_regret.clone());
+        self.positive_scale = 1.0;
+        self.negative_scale = 1.0;
+        self.scaled_sum_p = CompensatedArray::from(scalar::widen(&state.cumulative_strategy));
+        self.strategy_scale = 1.0;
+        regret_minimizer::regret_match(
+            self.scaled_regret.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_eager_dcfr() {
+        let params = DiscountParams::recommended();
+        let mut lazy = LazyDcfrRegretMatcher::new_with_params(3, params).unwrap();
+
+        // Straightforward DCFR to compare against.
+        let mut regret = [0.0; 3];
+        let mut sum_p = [0.0; 3];
+        let mut p = [1.0 / 3.0; 3];
+
+        let rewards = [[1.0, 0.0, -1.0], [-2.0, 1.0, 0.5], [0.0, 0.0, 3.0]];
+        for t in 1..=300 {
+            let r = rewards[t % 3];
+            lazy.update_regret(&r).unwrap();
+
+            let expected: Scalar = p.iter().zip(&r).map(|(a, b)| a * b).sum();
+            for i in 0..3 {
+                sum_p[i] = (sum_p[i] + p[i]) * params.strategy_factor(t);
+                regret[i] += r[i] - expected;
+                regret[i] *= if regret[i] >= 0.0 {
+                    params.positive_factor(t)
+                } else {
+                    params.negative_factor(t)
+                };
+            }
+            regret_minimizer::regret_match(&regret, &mut p);
+
+            for (a, b) in lazy.cumulative_regrets().iter().zip(&regret) {
+                assert!((a - b).abs() < 1e-3 * b.abs().max(1.0));
+            }
+        }
+        let total: Scalar = sum_p.iter().sum();
+        for (a, b) in lazy.best_weight().iter().zip(&sum_p) {
+            assert!((a - b / total).abs() < 1e-4);
+        }
+    }
+}
@@ -1,7 +1,116 @@
 #![deny(clippy::all)]
 
+pub mod agent;
+pub mod arena;
+pub mod averaging;
+pub mod bandit;
+pub mod bank;
+pub mod best_response;
+pub mod blackwell;
+pub mod builder;
+pub mod cfr_br;
+#[cfg(feature = "checkpoint")]
+pub mod checkpoint;
+pub mod compensated;
+pub mod convergence;
+pub mod dcfr;
+pub mod discount;
+pub mod dist;
+pub mod drift;
 pub mod errors;
+pub mod evaluation;
+pub mod exp3;
+pub mod exp3_ix;
+pub mod exp4;
+pub mod exploitative;
+#[cfg(feature = "arrow")]
+pub mod export;
+pub mod games;
+#[cfg(feature = "half")]
+pub mod half_precision;
+pub mod history;
+pub mod kernels;
+pub mod lazy_dcfr;
+pub mod meta;
+#[cfg(feature = "mmap")]
+pub mod mmap_store;
+pub mod mwu;
+pub mod omd;
+pub mod opponent_model;
+pub mod optimistic_hedge;
+#[cfg(feature = "plot")]
+pub mod plot;
+#[cfg(feature = "rs-poker")]
+pub mod poker;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod refresh;
+pub mod regret_bound;
 pub mod regret_matcher;
+pub mod regret_minimizer;
+pub mod report;
 pub mod rps;
+pub mod scalar;
+pub mod scheduled;
+pub mod small;
+pub mod smooth;
+pub mod solver;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+pub mod tsallis_inf;
+pub mod vanilla_cfr;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod windowed;
+pub mod workspace;
 
+pub use self::agent::{Agent, FixedAgent};
+pub use self::arena::{Arena, ArenaReport};
+pub use self::averaging::AveragingScheme;
+pub use self::bank::MatcherBank;
+pub use self::blackwell::BlackwellMatcher;
+pub use self::builder::MatcherBuilder;
+pub use self::cfr_br::CfrBrRegretMatcher;
+#[cfg(feature = "checkpoint")]
+pub use self::checkpoint::Checkpoint;
+pub use self::convergence::{ConvergenceCriterion, ConvergenceReport};
+pub use self::dcfr::DiscountedRegretMatcher;
+pub use self::discount::DiscountParams;
+pub use self::drift::DriftRestart;
+pub use self::evaluation::SampleStats;
+pub use self::exp3::Exp3RegretMatcher;
+pub use self::exp3_ix::Exp3IxRegretMatcher;
+pub use self::exp4::Exp4RegretMatcher;
+pub use self::exploitative::ExploitativeAgent;
+#[cfg(feature = "arrow")]
+pub use self::export::{TrajectoryRecord, TrajectoryWriter};
+#[cfg(feature = "half")]
+pub use self::half_precision::HalfRegretMatcher;
+pub use self::history::{HistoryWrapper, StrategySnapshot};
+pub use self::lazy_dcfr::LazyDcfrRegretMatcher;
+pub use self::meta::MetaRegretMatcher;
+#[cfg(feature = "mmap")]
+pub use self::mmap_store::{MmapStrategyStore, StoredEntry};
+pub use self::mwu::MwuRegretMatcher;
+pub use self::omd::OmdRegretMatcher;
+pub use self::opponent_model::OpponentModel;
+pub use self::optimistic_hedge::OptimisticHedgeRegretMatcher;
+#[cfg(feature = "profiling")]
+pub use self::profiling::{ProfileStats, Profiled};
+pub use self::refresh::RefreshWrapper;
+pub use self::regret_bound::{RegretBound, RegretBoundTracker};
 pub use self::regret_matcher::RegretMatcher;
+pub use self::regret_minimizer::{
+    BanditMinimizer, DynRegretMinimizer, MatcherState, RegretMinimizer, StateTransfer, TieBreak,
+    UpdateOptions,
+};
+pub use self::report::MatcherReport;
+pub use self::scalar::{Accumulator, Scalar};
+pub use self::scheduled::ScheduledRegretMatcher;
+pub use self::smooth::SmoothRegretMatcher;
+#[cfg(feature = "metrics")]
+pub use self::telemetry::MatcherMetrics;
+pub use self::tsallis_inf::TsallisInfRegretMatcher;
+pub use self::vanilla_cfr::VanillaCfrRegretMatcher;
+pub use self::windowed::WindowedRegretMatcher;
+pub use self::workspace::Workspace;
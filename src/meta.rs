@@ -0,0 +1,313 @@
+#![allow(clippy::cast_precision_loss)]
+//! An ensemble of regret minimizers. Every member sees the same
+//! rewards and a Hedge learner on top decides how much to trust
+//! each one based on how well its strategy would have done. The
+//! strategy played is the Hedge weighted mix of the members'
+//! strategies, so the ensemble does about as well as whichever
+//! member turns out to be best for the problem at hand.
+use ndarray::prelude::*;
+use rand::Rng;
+
+use std::fmt::Debug;
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::discount::DiscountParams;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::lazy_dcfr::LazyDcfrRegretMatcher;
+use crate::optimistic_hedge::OptimisticHedgeRegretMatcher;
+use crate::regret_minimizer::{
+    self, DynRegretMinimizer, MatcherState, RegretMinimizer, StateTransfer, UpdateOptions,
+};
+use crate::scalar::{Accumulator, Scalar};
+use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+#[derive(Debug, Clone)]
+pub struct MetaRegretMatcher {
+    num_experts: usize,
+    members: Vec<Box<dyn DynRegretMinimizer>>,
+    // Total reward each member's strategy would have earned.
+    member_reward: Array1<Scalar>,
+    // Hedge weights over the members.
+    q: Array1<Scalar>,
+    // The mixed strategy over actions.
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // Regret of the mixed strategy against each action.
+    cumulative_regret: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl MetaRegretMatcher {
+    /// An ensemble with no members yet. Add some with `with_member`.
+    pub fn empty(num_experts: usize) -> Result<Self, LittleError> {
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            num_experts,
+            members: Vec::new(),
+            member_reward: Array1::zeros(0),
+            q: Array1::zeros(0),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_regret: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// Add a member to the ensemble. All the members start out
+    /// trusted equally, so add them before the first update.
+    pub fn with_member<M: RegretMinimizer + Debug + 'static>(
+        mut self,
+        member: M,
+    ) -> Result<Self, LittleError> {
+        if member.num_experts() != self.num_experts {
+            return Err(LittleError::InvalidParameter(
+                "members must have the same number of experts",
+            ));
+        }
+        self.members.push(Box::new(member));
+        let k = self.members.len();
+        self.member_reward = Array1::zeros(k);
+        self.q = Array1::from(regret_minimizer::uniform(k));
+        self.mix()?;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn num_members(&self) -> usize {
+        self.members.len()
+    }
+
+    /// How much the top level Hedge currently trusts each member.
+    #[must_use]
+    pub fn member_weights(&self) -> Vec<Scalar> {
+        self.q.to_vec()
+    }
+
+    // The default members with the regret matching ones starting
+    // from `state`.
+    fn warm_start_members(
+        state: &MatcherState,
+        hedge: OptimisticHedgeRegretMatcher,
+    ) -> Result<Self, LittleError> {
+        let num_experts = state.cumulative_regret.len();
+        let mut vanilla = VanillaCfrRegretMatcher::new(num_experts)?;
+        vanilla.import_state(state)?;
+        let mut dcfr =
+            LazyDcfrRegretMatcher::new_with_params(num_experts, DiscountParams::recommended())?;
+        dcfr.import_state(state)?;
+        let mut lcfr = LazyDcfrRegretMatcher::new_with_params(num_experts, DiscountParams::lcfr())?;
+        lcfr.import_state(state)?;
+        let mut meta = Self::empty(num_experts)?
+            .with_member(vanilla)?
+            .with_member(dcfr)?
+            .with_member(lcfr)?
+            .with_member(hedge)?;
+        meta.cumulative_regret = Array1::from(state.cumulative_regret.clone());
+        Ok(meta)
+    }
+
+    // Trust every member equally again.
+    fn restart_hedge(&mut self) -> Result<(), LittleError> {
+        self.member_reward.fill(0.0);
+        regret_minimizer::fill_uniform(self.q.as_slice_mut().unwrap());
+        self.mix()
+    }
+
+    // Set the played strategy to the weighted mix of members.
+    fn mix(&mut self) -> Result<(), LittleError> {
+        self.p.fill(0.0);
+        for (member, q) in self.members.iter().zip(self.q.iter()) {
+            self.p
+                .scaled_add(*q, &ArrayView1::from(member.current_strategy_dyn()));
+        }
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+}
+
+impl RegretMinimizer for MetaRegretMatcher {
+    /// An ensemble of vanilla CFR, DCFR, linear CFR and
+    /// optimistic Hedge.
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::empty(num_experts)?
+            .with_member(VanillaCfrRegretMatcher::new(num_experts)?)?
+            .with_member(LazyDcfrRegretMatcher::new_with_params(
+                num_experts,
+                DiscountParams::recommended(),
+            )?)?
+            .with_member(LazyDcfrRegretMatcher::new_with_params(
+                num_experts,
+                DiscountParams::lcfr(),
+            )?)?
+            .with_member(OptimisticHedgeRegretMatcher::new(num_experts)?)
+    }
+
+    /// The default members, each warm started from `strategy`.
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let state = MatcherState::from_strategy(strategy)?;
+        Self::warm_start_members(
+            &state,
+            OptimisticHedgeRegretMatcher::warm_start_from_strategy(strategy)?,
+        )
+    }
+
+    /// The default members, each warm started from `regrets`.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let state = MatcherState::from_regrets(regrets);
+        Self::warm_start_members(
+            &state,
+            OptimisticHedgeRegretMatcher::warm_start_from_regrets(regrets)?,
+        )
+    }
+
+    fn num_experts(&self) -> usize {
+        self.num_experts
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        if self.members.is_empty() {
+            return Err(LittleError::InvalidParameter(
+                "ensemble needs at least one member",
+            ));
+        }
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        let rewards_view = ArrayView1::from(rewards);
+        let weight = options.regret_weight;
+        let expected = self.p.dot(&rewards_view);
+        self.cumulative_regret.scaled_add(weight, &rewards_view);
+        self.cumulative_regret -= weight * expected;
+        let member_options = UpdateOptions {
+            regret_weight: weight,
+            ..UpdateOptions::default()
+        };
+        for (i, member) in self.members.iter_mut().enumerate() {
+            self.member_reward[i] +=
+                weight * ArrayView1::from(member.current_strategy_dyn()).dot(&rewards_view);
+            member.update_regret_with_options_dyn(rewards, member_options)?;
+        }
+
+        // Anytime Hedge learning rate.
+        let k = self.members.len() as Scalar;
+        let eta = (8.0 * k.ln().max(1.0) / self.num_updates as Scalar).sqrt();
+        let logits = &self.member_reward * eta;
+        regret_minimizer::softmax(logits.as_slice().unwrap(), self.q.as_slice_mut().unwrap());
+        self.mix()
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        for member in &mut self.members {
+            member.reset_dyn()?;
+        }
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.cumulative_regret.fill(0.0);
+        self.restart_hedge()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        for member in &mut self.members {
+            member.soft_reset_dyn()?;
+        }
+        self.restart_hedge()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_members() {
+        let m = MetaRegretMatcher::new(3).unwrap();
+        assert_eq!(m.num_members(), 4);
+        let total: Scalar = m.member_weights().iter().sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let m = MetaRegretMatcher::warm_start_from_strategy(&[0.0, 1.0, 0.0]).unwrap();
+        assert_eq!(m.num_members(), 4);
+        assert!(m.current_strategy()[1] > 0.99);
+    }
+
+    #[test]
+    fn test_mismatched_member() {
+        let m = MetaRegretMatcher::empty(3).unwrap();
+        assert!(m
+            .with_member(VanillaCfrRegretMatcher::new(2).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_finds_dominant_action() {
+        let mut m = MetaRegretMatcher::new(3).unwrap();
+        for _i in 0..500 {
+            m.update_regret(&[0.0, 1.0, 0.0]).unwrap();
+        }
+        assert!(m.current_strategy()[1] > 0.95);
+    }
+}
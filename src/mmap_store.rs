@@ -0,0 +1,354 @@
+// `f64::from` does nothing when `Scalar` is already `f64`.
+#![allow(clippy::cast_possible_truncation, clippy::useless_conversion)]
+//! Regrets and strategy sums kept in a memory mapped file, for
+//! solves with more information sets than fit in memory. Infosets
+//! are addressed by a dense id and every one has the same number of
+//! actions, so entry `id` lives at a fixed offset. The most recently
+//! used entries are kept in RAM and only written back to the map
+//! when they're evicted or the store is flushed, leaving it to the
+//! operating system to page the rest in and out. The file is:
+//!
+//! * 8 bytes of magic, `LSRYSTOR`
+//! * the number of actions as a little endian `u64`
+//! * the number of infosets as a little endian `u64`
+//! * for each infoset its regrets and then its strategy sum, all
+//!   as little endian `f64`
+use memmap2::MmapMut;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer;
+use crate::scalar::{Accumulator, Scalar};
+
+const MAGIC: &[u8; 8] = b"LSRYSTOR";
+const HEADER_LEN: usize = 24;
+const VALUE_LEN: usize = 8;
+
+// The bytes taken by the header and `num_infosets` entries, which
+// is also where entry `num_infosets` starts. Sizes read from a
+// corrupt header can overflow, which means the store is invalid.
+fn store_len(num_infosets: usize, num_experts: usize) -> Result<usize, LittleError> {
+    num_experts
+        .checked_mul(2 * VALUE_LEN)
+        .and_then(|entry_len| entry_len.checked_mul(num_infosets))
+        .and_then(|entries_len| entries_len.checked_add(HEADER_LEN))
+        .ok_or(LittleError::InvalidStore("too large"))
+}
+
+/// The learned state of one infoset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEntry {
+    pub regrets: Vec<Scalar>,
+    pub strategy_sum: Vec<Accumulator>,
+}
+
+#[derive(Debug)]
+struct CachedEntry {
+    entry: StoredEntry,
+    last_used: u64,
+    dirty: bool,
+}
+
+#[derive(Debug)]
+pub struct MmapStrategyStore {
+    map: MmapMut,
+    num_experts: usize,
+    num_infosets: usize,
+    cache_capacity: usize,
+    cache: HashMap<u64, CachedEntry>,
+    // Cached ids by when they were last used, oldest first.
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+}
+
+impl MmapStrategyStore {
+    /// Create a store at `path` with room for `num_infosets` infosets
+    /// of `num_experts` actions each, keeping up to `cache_capacity`
+    /// of them in memory. Any existing file is overwritten.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        num_infosets: usize,
+        num_experts: usize,
+        cache_capacity: usize,
+    ) -> Result<Self, LittleError> {
+        if num_experts == 0 {
+            return Err(LittleError::InvalidParameter(
+                "num_experts must be greater than 0",
+            ));
+        }
+        let len = store_len(num_infosets, num_experts)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        // Safety: the file was just created for this store. Nothing
+        // else is expected to modify it while it's mapped.
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[0..8].copy_from_slice(MAGIC);
+        map[8..16].copy_from_slice(&(num_experts as u64).to_le_bytes());
+        map[16..24].copy_from_slice(&(num_infosets as u64).to_le_bytes());
+        Self::from_map(map, num_infosets, num_experts, cache_capacity)
+    }
+
+    /// Open a store written by `create`.
+    pub fn open<P: AsRef<Path>>(path: P, cache_capacity: usize) -> Result<Self, LittleError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        // Safety: as in `create`, the file belongs to this store.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        if map.len() < HEADER_LEN || &map[0..8] != MAGIC {
+            return Err(LittleError::InvalidStore("not a strategy store"));
+        }
+        let num_experts = u64::from_le_bytes(map[8..16].try_into().unwrap()) as usize;
+        let num_infosets = u64::from_le_bytes(map[16..24].try_into().unwrap()) as usize;
+        if map.len() != store_len(num_infosets, num_experts)? {
+            return Err(LittleError::InvalidStore("truncated"));
+        }
+        Self::from_map(map, num_infosets, num_experts, cache_capacity)
+    }
+
+    fn from_map(
+        map: MmapMut,
+        num_infosets: usize,
+        num_experts: usize,
+        cache_capacity: usize,
+    ) -> Result<Self, LittleError> {
+        if cache_capacity == 0 {
+            return Err(LittleError::InvalidParameter(
+                "cache_capacity must be greater than 0",
+            ));
+        }
+        Ok(Self {
+            map,
+            num_experts,
+            num_infosets,
+            cache_capacity,
+            cache: HashMap::with_capacity(cache_capacity),
+            recency: BTreeMap::new(),
+            clock: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn num_experts(&self) -> usize {
+        self.num_experts
+    }
+
+    #[must_use]
+    pub fn num_infosets(&self) -> usize {
+        self.num_infosets
+    }
+
+    /// How many entries are currently held in memory.
+    #[must_use]
+    pub fn num_cached(&self) -> usize {
+        self.cache.len()
+    }
+
+    fn offset(&self, id: u64) -> usize {
+        // Ids are checked against `num_infosets`, whose entries were
+        // already found to fit.
+        store_len(id as usize, self.num_experts).expect("id is in range")
+    }
+
+    fn read(&self, id: u64) -> StoredEntry {
+        let start = self.offset(id);
+        let mut values = self.map[start..start + 2 * self.num_experts * VALUE_LEN]
+            .chunks_exact(VALUE_LEN)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()));
+        StoredEntry {
+            regrets: values
+                .by_ref()
+                .take(self.num_experts)
+                .map(|v| v as Scalar)
+                .collect(),
+            strategy_sum: values.map(|v| v as Accumulator).collect(),
+        }
+    }
+
+    fn write(&mut self, id: u64, entry: &StoredEntry) {
+        let start = self.offset(id);
+        let values = entry
+            .regrets
+            .iter()
+            .map(|v| f64::from(*v))
+            .chain(entry.strategy_sum.iter().map(|v| f64::from(*v)));
+        let bytes = &mut self.map[start..start + 2 * self.num_experts * VALUE_LEN];
+        for (chunk, v) in bytes.chunks_exact_mut(VALUE_LEN).zip(values) {
+            chunk.copy_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let Some((_, id)) = self.recency.pop_first() else {
+            return;
+        };
+        if let Some(cached) = self.cache.remove(&id) {
+            if cached.dirty {
+                self.write(id, &cached.entry);
+            }
+        }
+    }
+
+    fn cached(&mut self, id: u64) -> Result<&mut CachedEntry, LittleError> {
+        if id as usize >= self.num_infosets {
+            return Err(LittleError::InvalidParameter("infoset id is out of range"));
+        }
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(cached) = self.cache.get_mut(&id) {
+            self.recency.remove(&cached.last_used);
+            cached.last_used = clock;
+        } else {
+            if self.cache.len() == self.cache_capacity {
+                self.evict_oldest();
+            }
+            let entry = self.read(id);
+            self.cache.insert(
+                id,
+                CachedEntry {
+                    entry,
+                    last_used: clock,
+                    dirty: false,
+                },
+            );
+        }
+        self.recency.insert(clock, id);
+        Ok(self.cache.get_mut(&id).unwrap())
+    }
+
+    /// The state of infoset `id`, loading it into memory if needed.
+    pub fn get(&mut self, id: u64) -> Result<&StoredEntry, LittleError> {
+        Ok(&self.cached(id)?.entry)
+    }
+
+    /// Mutable access to infoset `id`. The entry is written back to
+    /// the file when it's evicted or on `flush`.
+    pub fn get_mut(&mut self, id: u64) -> Result<&mut StoredEntry, LittleError> {
+        let cached = self.cached(id)?;
+        cached.dirty = true;
+        Ok(&mut cached.entry)
+    }
+
+    /// The strategy regret matching plays at infoset `id`.
+    pub fn current_strategy(&mut self, id: u64) -> Result<Vec<Scalar>, LittleError> {
+        let mut p = vec![0.0; self.num_experts];
+        regret_minimizer::regret_match(&self.get(id)?.regrets, &mut p);
+        Ok(p)
+    }
+
+    /// The normalized strategy sum of infoset `id`, or the current
+    /// strategy if nothing has been added to it yet.
+    pub fn average_strategy(&mut self, id: u64) -> Result<Vec<Scalar>, LittleError> {
+        let sum = &self.get(id)?.strategy_sum;
+        let total: Accumulator = sum.iter().sum();
+        if total <= 0.0 {
+            return self.current_strategy(id);
+        }
+        Ok(sum.iter().map(|s| (s / total) as Scalar).collect())
+    }
+
+    /// A vanilla CFR update of infoset `id`, with the strategy added
+    /// to the sum scaled by `average_weight`.
+    pub fn update_regret(
+        &mut self,
+        id: u64,
+        rewards: &[Scalar],
+        average_weight: Scalar,
+    ) -> Result<(), LittleError> {
+        if rewards.len() != self.num_experts {
+            return Err(LittleError::InvalidParameter(
+                "rewards must have one entry per expert",
+            ));
+        }
+        let p = self.current_strategy(id)?;
+        let entry = self.get_mut(id)?;
+        let expected: Scalar = p.iter().zip(rewards).map(|(p, r)| p * r).sum();
+        for (regret, r) in entry.regrets.iter_mut().zip(rewards) {
+            *regret += r - expected;
+        }
+        for (s, p) in entry.strategy_sum.iter_mut().zip(&p) {
+            *s += (average_weight * p) as Accumulator;
+        }
+        Ok(())
+    }
+
+    /// Write every modified entry back and flush the map to disk.
+    pub fn flush(&mut self) -> Result<(), LittleError> {
+        let dirty: Vec<(u64, StoredEntry)> = self
+            .cache
+            .iter_mut()
+            .filter(|(_, c)| c.dirty)
+            .map(|(id, c)| {
+                c.dirty = false;
+                (*id, c.entry.clone())
+            })
+            .collect();
+        for (id, entry) in &dirty {
+            self.write(*id, entry);
+        }
+        self.map.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for MmapStrategyStore {
+    fn drop(&mut self) {
+        // Errors can't be reported from here; call `flush` first to
+        // see them.
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persists_evicted_entries() {
+        let path = std::env::temp_dir().join(format!("little-sorry-{}.store", std::process::id()));
+        {
+            let mut store = MmapStrategyStore::create(&path, 4, 2, 2).unwrap();
+            for id in 0..4 {
+                store.update_regret(id, &[1.0, 0.0], 1.0).unwrap();
+            }
+            assert_eq!(store.num_cached(), 2);
+            store.update_regret(0, &[0.0, 3.0], 1.0).unwrap();
+            assert!(store.get(4).is_err());
+        }
+        let mut store = MmapStrategyStore::open(&path, 1).unwrap();
+        assert_eq!(store.num_infosets(), 4);
+        assert_eq!(store.get(0).unwrap().regrets, vec![0.5, 2.5]);
+        assert_eq!(store.average_strategy(0).unwrap(), vec![0.75, 0.25]);
+        assert_eq!(store.current_strategy(3).unwrap(), vec![1.0, 0.0]);
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_overflowing_sizes() {
+        let path = std::env::temp_dir().join(format!(
+            "little-sorry-{}-overflow.store",
+            std::process::id()
+        ));
+        assert!(matches!(
+            MmapStrategyStore::create(&path, usize::MAX, 2, 1),
+            Err(LittleError::InvalidStore(_))
+        ));
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&u64::MAX.to_le_bytes());
+        header.extend_from_slice(&2_u64.to_le_bytes());
+        std::fs::write(&path, header).unwrap();
+        assert!(matches!(
+            MmapStrategyStore::open(&path, 1),
+            Err(LittleError::InvalidStore(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,283 @@
+#![allow(clippy::cast_precision_loss)]
+//! Multiplicative weights update. Every expert's weight is
+//! multiplied by `1 + epsilon * reward` each round. This is the
+//! textbook experts algorithm; rewards should be in `[-1, 1]`.
+//!
+//! When the number of rounds isn't known ahead of time the
+//! doubling trick can be used instead of a fixed epsilon. Play
+//! is split into epochs of length 1, 2, 4, ... and each epoch
+//! restarts with the epsilon tuned for its length.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+pub(crate) const DEFAULT_EPSILON: Scalar = 0.1;
+const MAX_EPSILON: Scalar = 0.5;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MwuRegretMatcher {
+    epsilon: Scalar,
+    doubling: bool,
+    // Length of the current doubling epoch, and how far into it we are.
+    epoch_len: usize,
+    epoch_updates: usize,
+    // Log of the weights so that long runs don't underflow.
+    log_weights: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // Regret against each expert. Only kept for reporting.
+    cumulative_regret: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl MwuRegretMatcher {
+    /// Create a matcher with a fixed `epsilon` in `(0, 0.5]`.
+    pub fn new_with_params(num_experts: usize, epsilon: Scalar) -> Result<Self, LittleError> {
+        if !(epsilon > 0.0 && epsilon <= MAX_EPSILON) {
+            return Err(LittleError::InvalidParameter("epsilon must be in (0, 0.5]"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            epsilon,
+            doubling: false,
+            epoch_len: 0,
+            epoch_updates: 0,
+            log_weights: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_regret: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// Create a matcher that uses the doubling trick so that
+    /// no horizon or epsilon needs to be picked.
+    pub fn with_doubling_trick(num_experts: usize) -> Result<Self, LittleError> {
+        let mut matcher = Self::new_with_params(num_experts, MAX_EPSILON)?;
+        matcher.doubling = true;
+        matcher.epoch_len = 1;
+        matcher.epsilon = Self::epoch_epsilon(num_experts, 1);
+        Ok(matcher)
+    }
+
+    // The epsilon that minimizes the regret bound over `len` rounds.
+    fn epoch_epsilon(num_experts: usize, len: usize) -> Scalar {
+        let ln_k = (num_experts as Scalar).ln().max(1.0);
+        Scalar::min(MAX_EPSILON, (ln_k / len as Scalar).sqrt())
+    }
+
+    // Replace the log weights and play the strategy they give.
+    fn set_log_weights(&mut self, log_weights: Vec<Scalar>) -> Result<(), LittleError> {
+        self.log_weights = Array1::from(log_weights);
+        regret_minimizer::softmax(
+            self.log_weights.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn epsilon(&self) -> Scalar {
+        self.epsilon
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        let num_experts = self.p.len();
+        // Check every reward before touching any state so a bad
+        // update leaves the matcher as it was.
+        if reward_array.iter().any(|r| 1.0 + self.epsilon * r <= 0.0) {
+            return Err(LittleError::InvalidParameter("rewards must be in [-1, 1]"));
+        }
+        let weight = options.regret_weight;
+        let expected = self.p.dot(&reward_array);
+        self.cumulative_regret.scaled_add(weight, &reward_array);
+        self.cumulative_regret -= weight * expected;
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        for (lw, r) in self.log_weights.iter_mut().zip(reward_array.iter()) {
+            *lw += weight * (1.0 + self.epsilon * r).ln();
+        }
+
+        if self.doubling {
+            self.epoch_updates += 1;
+            if self.epoch_updates == self.epoch_len {
+                // Start the next epoch from scratch with a smaller epsilon.
+                self.epoch_len *= 2;
+                self.epoch_updates = 0;
+                self.epsilon = Self::epoch_epsilon(num_experts, self.epoch_len);
+                self.log_weights.fill(0.0);
+            }
+        }
+
+        regret_minimizer::softmax(
+            self.log_weights.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+}
+
+impl RegretMinimizer for MwuRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DEFAULT_EPSILON)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![("epsilon", self.epsilon)]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        if self.doubling {
+            self.epoch_len = 1;
+            self.epsilon = Self::epoch_epsilon(self.p.len(), 1);
+        }
+        self.epoch_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        self.log_weights.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.set_log_weights(regret_minimizer::log_strategy(&strategy))?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    /// The regrets are scaled by epsilon, matching what small
+    /// rewards would have added to the log weights.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.cumulative_regret = Array1::from(regrets.to_vec());
+        let log_weights = regrets.iter().map(|r| r * matcher.epsilon).collect();
+        matcher.set_log_weights(log_weights)?;
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_params() {
+        assert!(MwuRegretMatcher::new_with_params(3, 0.0).is_err());
+        assert!(MwuRegretMatcher::new_with_params(3, 0.75).is_err());
+        let mut m = MwuRegretMatcher::new_with_params(3, 0.5).unwrap();
+        m.update_regret(&[0.5, 0.0, -0.5]).unwrap();
+        let before = m.clone();
+        assert!(m.update_regret(&[1.0, 0.0, -3.0]).is_err());
+        assert!(m.update_regret(&[1.0, 0.0]).is_err());
+        assert_eq!(m.num_updates(), before.num_updates());
+        assert_eq!(m.cumulative_regrets(), before.cumulative_regrets());
+        assert_eq!(m.current_strategy(), before.current_strategy());
+        assert_eq!(m.best_weight(), before.best_weight());
+        assert_eq!(m.log_weights, before.log_weights);
+    }
+
+    #[test]
+    fn test_doubling_epsilon_shrinks() {
+        let mut m = MwuRegretMatcher::with_doubling_trick(4).unwrap();
+        let start = m.epsilon();
+        for _i in 0..1_000 {
+            m.update_regret(&[1.0, 0.0, 0.0, 0.0]).unwrap();
+        }
+        assert!(m.epsilon() < start);
+        assert!(m.current_strategy()[0] > 0.9);
+    }
+}
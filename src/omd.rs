@@ -0,0 +1,277 @@
+#![allow(clippy::cast_precision_loss)]
+//! Online mirror descent on the simplex using the negative
+//! entropy regularizer. Each step moves the previous strategy
+//! multiplicatively towards the actions that did well, with
+//! the size of the step given by a schedule.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+/// How large each mirror descent step is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StepSize {
+    /// The same step size every update.
+    Constant(Scalar),
+    /// `eta / sqrt(t)` on the t'th update. This gives
+    /// sublinear regret without knowing the horizon.
+    InverseSqrt(Scalar),
+}
+
+impl StepSize {
+    /// The step size for update number `t`, starting at 1.
+    #[must_use]
+    pub fn at(&self, t: usize) -> Scalar {
+        match self {
+            Self::Constant(eta) => *eta,
+            Self::InverseSqrt(eta) => eta / (t.max(1) as Scalar).sqrt(),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let eta = match self {
+            Self::Constant(eta) | Self::InverseSqrt(eta) => *eta,
+        };
+        eta > 0.0 && eta.is_finite()
+    }
+}
+
+impl Default for StepSize {
+    fn default() -> Self {
+        Self::InverseSqrt(1.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OmdRegretMatcher {
+    step_size: StepSize,
+    // Log of the current strategy. Mirror descent with negative
+    // entropy is additive in log space.
+    log_p: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // Regret against each expert. Only kept for reporting.
+    cumulative_regret: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl OmdRegretMatcher {
+    pub fn new_with_params(num_experts: usize, step_size: StepSize) -> Result<Self, LittleError> {
+        if !step_size.is_valid() {
+            return Err(LittleError::InvalidParameter("step size must be positive"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            step_size,
+            log_p: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_regret: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        let weight = options.regret_weight;
+        let expected = self.p.dot(&reward_array);
+        self.cumulative_regret.scaled_add(weight, &reward_array);
+        self.cumulative_regret -= weight * expected;
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        let eta = self.step_size.at(self.num_updates);
+        self.log_p.scaled_add(eta * weight, &reward_array);
+        regret_minimizer::softmax(
+            self.log_p.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        // Renormalize the log strategy so it stays near zero
+        // rather than growing with the sum of rewards.
+        let max = self
+            .log_p
+            .fold(Scalar::NEG_INFINITY, |acc, v| Scalar::max(acc, *v));
+        self.log_p -= max;
+
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    // Replace the log weights and play the strategy they give.
+    fn set_log_p(&mut self, log_p: Vec<Scalar>) -> Result<(), LittleError> {
+        self.log_p = Array1::from(log_p);
+        regret_minimizer::softmax(
+            self.log_p.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn step_size(&self) -> StepSize {
+        self.step_size
+    }
+}
+
+impl RegretMinimizer for OmdRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, StepSize::default())
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        match self.step_size {
+            StepSize::Constant(eta) => vec![("constant_eta", eta)],
+            StepSize::InverseSqrt(eta) => vec![("inverse_sqrt_eta", eta)],
+        }
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        self.log_p.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.set_log_p(regret_minimizer::log_strategy(&strategy))?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    /// The regrets are used as the starting log weights.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.cumulative_regret = Array1::from(regrets.to_vec());
+        matcher.set_log_p(regrets.to_vec())?;
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_size() {
+        assert_eq!(StepSize::Constant(0.5).at(100), 0.5);
+        assert_eq!(StepSize::InverseSqrt(1.0).at(4), 0.5);
+        assert!(OmdRegretMatcher::new_with_params(3, StepSize::Constant(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_rps_average() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let mut one = OmdRegretMatcher::new(3).unwrap();
+        let mut two = OmdRegretMatcher::new(3).unwrap();
+        one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        for _i in 0..10_000 {
+            let r1 = payoff.dot(&ArrayView1::from(two.current_strategy()));
+            let r2 = payoff.dot(&ArrayView1::from(one.current_strategy()));
+            one.update_regret(r1.as_slice().unwrap()).unwrap();
+            two.update_regret(r2.as_slice().unwrap()).unwrap();
+        }
+        for w in one.best_weight() {
+            assert!((w - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_cumulative_regrets() {
+        let mut m = OmdRegretMatcher::new(2).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        // The first strategy was uniform so expected 0.5.
+        assert_eq!(m.cumulative_regrets(), vec![0.5, -0.5]);
+    }
+}
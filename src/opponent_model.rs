@@ -0,0 +1,200 @@
+#![allow(clippy::cast_precision_loss)]
+//! Estimates of how an opponent plays from the actions they've been
+//! seen taking. The estimate is the empirical frequency of each
+//! action, optionally with older observations decayed away so a
+//! changing opponent is followed, and smoothed by a symmetric
+//! Dirichlet prior so a handful of observations don't rule actions
+//! out.
+//!
+//! The model turns into rewards for a matcher through a payoff
+//! matrix, either the expected reward of every action against the
+//! estimate or the rewards against one action sampled from it.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::best_response;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpponentModel {
+    counts: Vec<Scalar>,
+    // What the counts are multiplied by before each observation.
+    decay: Scalar,
+    // Pseudo count added to every action.
+    prior: Scalar,
+    num_observations: usize,
+}
+
+impl OpponentModel {
+    /// Plain empirical frequencies over `num_actions` actions.
+    pub fn new(num_actions: usize) -> Result<Self, LittleError> {
+        if num_actions == 0 {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one action",
+            ));
+        }
+        Ok(Self {
+            counts: vec![0.0; num_actions],
+            decay: 1.0,
+            prior: 0.0,
+            num_observations: 0,
+        })
+    }
+
+    /// Multiply the weight of everything seen so far by `decay` on
+    /// each new observation. One keeps everything.
+    pub fn with_decay(mut self, decay: Scalar) -> Result<Self, LittleError> {
+        if !(decay > 0.0 && decay <= 1.0) {
+            return Err(LittleError::InvalidParameter("decay must be in (0, 1]"));
+        }
+        self.decay = decay;
+        Ok(self)
+    }
+
+    /// Add `prior` pseudo observations of every action.
+    pub fn with_prior(mut self, prior: Scalar) -> Result<Self, LittleError> {
+        if !(prior >= 0.0 && prior.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "prior must be finite and non-negative",
+            ));
+        }
+        self.prior = prior;
+        Ok(self)
+    }
+
+    pub fn num_actions(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn num_observations(&self) -> usize {
+        self.num_observations
+    }
+
+    /// Record that the opponent played `action`.
+    pub fn observe(&mut self, action: usize) -> Result<(), LittleError> {
+        if action >= self.counts.len() {
+            return Err(LittleError::ActionOutOfRange {
+                action,
+                num_experts: self.counts.len(),
+            });
+        }
+        for c in &mut self.counts {
+            *c *= self.decay;
+        }
+        self.counts[action] += 1.0;
+        self.num_observations += 1;
+        Ok(())
+    }
+
+    /// Forget every observation, keeping the decay and prior.
+    pub fn reset(&mut self) {
+        self.counts.fill(0.0);
+        self.num_observations = 0;
+    }
+
+    /// The estimated strategy, uniform when nothing has been seen
+    /// and there's no prior.
+    #[must_use]
+    pub fn strategy(&self) -> Vec<Scalar> {
+        let total: Scalar = self.counts.iter().map(|c| c + self.prior).sum();
+        if total > 0.0 {
+            self.counts
+                .iter()
+                .map(|c| (c + self.prior) / total)
+                .collect()
+        } else {
+            vec![1.0 / self.counts.len() as Scalar; self.counts.len()]
+        }
+    }
+
+    /// The expected reward of every action against the estimate,
+    /// where `payoff[[a, b]]` is the reward for `a` against `b`.
+    /// This can be passed straight to `update_regret`.
+    pub fn expected_rewards(&self, payoff: ArrayView2<Scalar>) -> Result<Vec<Scalar>, LittleError> {
+        self.check_payoff(payoff)?;
+        Ok(best_response::action_values(payoff, &self.strategy()))
+    }
+
+    /// The rewards of every action against one opponent action
+    /// sampled from the estimate.
+    pub fn sample_rewards<R: Rng>(
+        &self,
+        payoff: ArrayView2<Scalar>,
+        rng: &mut R,
+    ) -> Result<Vec<Scalar>, LittleError> {
+        self.check_payoff(payoff)?;
+        let action = dist::sample_weights(&self.strategy(), rng);
+        Ok(payoff.column(action).to_vec())
+    }
+
+    /// The best action against the estimate and its expected reward.
+    pub fn best_response(
+        &self,
+        payoff: ArrayView2<Scalar>,
+    ) -> Result<(usize, Scalar), LittleError> {
+        self.check_payoff(payoff)?;
+        Ok(best_response::best_response(payoff, &self.strategy()))
+    }
+
+    fn check_payoff(&self, payoff: ArrayView2<Scalar>) -> Result<(), LittleError> {
+        if payoff.ncols() != self.counts.len() || payoff.nrows() == 0 {
+            return Err(LittleError::InvalidParameter(
+                "payoff must have a column per opponent action",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use crate::regret_minimizer::RegretMinimizer;
+
+    #[test]
+    fn test_frequencies() {
+        let mut model = OpponentModel::new(3).unwrap().with_prior(1.0).unwrap();
+        assert_eq!(model.strategy(), vec![1.0 / 3.0; 3]);
+        for _i in 0..7 {
+            model.observe(0).unwrap();
+        }
+        assert_eq!(model.strategy(), vec![0.8, 0.1, 0.1]);
+        assert_eq!(model.num_observations(), 7);
+        assert!(model.observe(3).is_err());
+
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let rewards = model.expected_rewards(payoff.view()).unwrap();
+        let mut matcher = RegretMatcher::new(3).unwrap();
+        for _i in 0..100 {
+            matcher.update_regret(&rewards).unwrap();
+        }
+        assert_eq!(matcher.best_action(), 1);
+        assert_eq!(model.best_response(payoff.view()).unwrap().0, 1);
+        assert!(model
+            .expected_rewards(payoff.t().slice(s![..2, ..]))
+            .is_ok());
+        assert!(model.expected_rewards(payoff.slice(s![.., ..2])).is_err());
+    }
+
+    #[test]
+    fn test_decay_follows_a_switch() {
+        let mut decayed = OpponentModel::new(2).unwrap().with_decay(0.9).unwrap();
+        let mut plain = OpponentModel::new(2).unwrap();
+        for action in [0; 100].into_iter().chain([1; 20]) {
+            decayed.observe(action).unwrap();
+            plain.observe(action).unwrap();
+        }
+        assert!(decayed.strategy()[1] > 0.8);
+        assert!(plain.strategy()[1] < 0.2);
+        assert!(OpponentModel::new(2).unwrap().with_decay(0.0).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+#![allow(clippy::cast_precision_loss)]
+//! Optimistic Hedge. This is Hedge (exponential weights) where
+//! the most recent reward vector is used as a prediction of the
+//! next one. In two player zero-sum games the last iterate, not
+//! just the average, converges to equilibrium.
+use ndarray::prelude::*;
+use ndarray::Zip;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+pub(crate) const DEFAULT_ETA: Scalar = 0.1;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OptimisticHedgeRegretMatcher {
+    // Learning rate
+    eta: Scalar,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // Regret against each expert. Only kept for reporting.
+    cumulative_regret: Array1<Scalar>,
+    // Total reward each expert would have earned
+    expert_reward: Array1<Scalar>,
+    // The last reward vector seen. Used as the prediction.
+    last_reward: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl OptimisticHedgeRegretMatcher {
+    /// Create a new matcher with learning rate `eta`.
+    pub fn new_with_params(num_experts: usize, eta: Scalar) -> Result<Self, LittleError> {
+        if !(eta > 0.0 && eta.is_finite()) {
+            return Err(LittleError::InvalidParameter("eta must be positive"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            eta,
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_regret: Array1::zeros(num_experts),
+            expert_reward: Array1::zeros(num_experts),
+            last_reward: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        let weight = options.regret_weight;
+        let expected = self.p.dot(&reward_array);
+        self.cumulative_regret.scaled_add(weight, &reward_array);
+        self.cumulative_regret -= weight * expected;
+        self.expert_reward.scaled_add(weight, &reward_array);
+        self.last_reward.assign(&reward_array);
+        self.last_reward *= weight;
+
+        // Play as if the next reward will look like the last one.
+        let eta = self.eta;
+        Zip::from(&mut self.p)
+            .and(&self.expert_reward)
+            .and(&self.last_reward)
+            .for_each(|p, e, l| *p = (e + l) * eta);
+        regret_minimizer::softmax_in_place(self.p.as_slice_mut().unwrap());
+
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    // Play Hedge on the rewards so far with no prediction.
+    fn play_expert_reward(&mut self) -> Result<(), LittleError> {
+        self.p.assign(&self.expert_reward);
+        self.p *= self.eta;
+        regret_minimizer::softmax_in_place(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    /// The strategy that will be played next. For optimistic
+    /// algorithms this iterate converges on its own.
+    #[must_use]
+    pub fn current_weight(&self) -> Vec<Scalar> {
+        self.p.to_vec()
+    }
+
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+}
+
+impl RegretMinimizer for OptimisticHedgeRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DEFAULT_ETA)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![("eta", self.eta)]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        self.expert_reward.fill(0.0);
+        self.last_reward.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let logits = regret_minimizer::log_strategy(&strategy);
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.expert_reward = Array1::from(logits) / matcher.eta;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        matcher.play_expert_reward()?;
+        Ok(matcher)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.cumulative_regret = Array1::from(regrets.to_vec());
+        matcher.expert_reward = Array1::from(regrets.to_vec());
+        matcher.play_expert_reward()?;
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_eta() {
+        assert!(OptimisticHedgeRegretMatcher::new_with_params(3, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_last_iterate_rps() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let mut one = OptimisticHedgeRegretMatcher::new_with_params(3, 0.1).unwrap();
+        let mut two = OptimisticHedgeRegretMatcher::new_with_params(3, 0.1).unwrap();
+        // Bias the start so there is something to converge from.
+        one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        for _i in 0..5_000 {
+            let p1 = Array1::from(one.current_weight());
+            let p2 = Array1::from(two.current_weight());
+            let r1 = payoff.dot(&p2);
+            let r2 = payoff.dot(&p1);
+            one.update_regret(r1.as_slice().unwrap()).unwrap();
+            two.update_regret(r2.as_slice().unwrap()).unwrap();
+        }
+        for w in one.current_weight() {
+            assert!((w - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+}
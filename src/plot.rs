@@ -0,0 +1,140 @@
+//! Convergence charts drawn with plotters. Runs are collected as
+//! `(iteration, exploitability)` points, for example with
+//! `exploitability_curve`, and any number of them are drawn as
+//! lines on one SVG chart so algorithms can be compared by eye.
+// `f64::from` does nothing when `Scalar` is already `f64`.
+#![allow(clippy::cast_precision_loss, clippy::useless_conversion)]
+use plotters::prelude::*;
+
+use std::path::Path;
+use std::string::ToString;
+use std::vec::Vec;
+
+use crate::cfr_br::CfrBrRegretMatcher;
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+/// One line on a chart.
+#[derive(Debug, Clone, Copy)]
+pub struct Series<'a> {
+    pub name: &'a str,
+    pub points: &'a [(usize, Scalar)],
+}
+
+fn to_plot_error<E: std::error::Error>(err: E) -> LittleError {
+    LittleError::Plot(err.to_string())
+}
+
+/// Train `learner` for `iterations` updates, recording the best
+/// responder's value every `every` updates.
+pub fn exploitability_curve<M: RegretMinimizer>(
+    learner: &mut CfrBrRegretMatcher<M>,
+    iterations: usize,
+    every: usize,
+) -> Result<Vec<(usize, Scalar)>, LittleError> {
+    if every == 0 {
+        return Err(LittleError::InvalidParameter(
+            "every must be greater than 0",
+        ));
+    }
+    let mut points = Vec::with_capacity(iterations / every + 1);
+    for i in 1..=iterations {
+        learner.update()?;
+        if i.is_multiple_of(every) {
+            points.push((i, learner.best_response_value()));
+        }
+    }
+    Ok(points)
+}
+
+/// Draw exploitability against iteration for every series and
+/// write the chart to `path` as an SVG.
+pub fn plot_convergence<P: AsRef<Path>>(
+    path: P,
+    title: &str,
+    series: &[Series],
+) -> Result<(), LittleError> {
+    let points = || series.iter().flat_map(|s| s.points.iter());
+    let max_x = points().map(|(x, _)| *x).max().unwrap_or(1).max(1);
+    let max_y = points().map(|(_, y)| *y).fold(0.0, Scalar::max);
+    let max_y = if max_y > 0.0 { f64::from(max_y) } else { 1.0 };
+
+    let root = SVGBackend::new(path.as_ref(), (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE).map_err(to_plot_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..max_x as f64, 0.0..max_y)
+        .map_err(to_plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc("iteration")
+        .y_desc("exploitability")
+        .draw()
+        .map_err(to_plot_error)?;
+
+    for (i, s) in series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(
+                s.points.iter().map(|(x, y)| (*x as f64, f64::from(*y))),
+                color.stroke_width(2),
+            ))
+            .map_err(to_plot_error)?
+            .label(s.name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(to_plot_error)?;
+    root.present().map_err(to_plot_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+    use ndarray::array;
+
+    #[test]
+    fn test_plot_convergence() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let mut rm = CfrBrRegretMatcher::<RegretMatcher>::new(payoff.clone()).unwrap();
+        let mut cfr = CfrBrRegretMatcher::<VanillaCfrRegretMatcher>::new(payoff).unwrap();
+        let rm_points = exploitability_curve(&mut rm, 500, 10).unwrap();
+        let cfr_points = exploitability_curve(&mut cfr, 500, 10).unwrap();
+        assert_eq!(rm_points.len(), 50);
+
+        let path = std::env::temp_dir().join(format!("little-sorry-{}.svg", std::process::id()));
+        plot_convergence(
+            &path,
+            "rock paper scissors",
+            &[
+                Series {
+                    name: "regret matching",
+                    points: &rm_points,
+                },
+                Series {
+                    name: "vanilla cfr",
+                    points: &cfr_points,
+                },
+            ],
+        )
+        .unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("vanilla cfr"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+#![allow(clippy::cast_precision_loss)]
+//! Lossy card abstraction. Hands are described by features the
+//! caller computes, like equity against a random hand or an equity
+//! histogram, and hands with similar features share a bucket so a
+//! solve only needs an information state per bucket rather than per
+//! hand.
+//!
+//! Bucket ids are stable: they're ordered from the weakest bucket
+//! to the strongest, judged by the sum of the features, so the same
+//! data always gives the same ids however k-means was seeded.
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dist;
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Buckets {
+    // The lowest strength in every bucket but the first.
+    Percentile(Vec<Scalar>),
+    Centroids(Vec<Vec<Scalar>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CardAbstraction {
+    buckets: Buckets,
+}
+
+fn squared_distance(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest(centroids: &[Vec<Scalar>], point: &[Scalar]) -> usize {
+    let mut best = (Scalar::INFINITY, 0);
+    for (i, c) in centroids.iter().enumerate() {
+        let d = squared_distance(c, point);
+        if d < best.0 {
+            best = (d, i);
+        }
+    }
+    best.1
+}
+
+impl CardAbstraction {
+    /// Split hands into `num_buckets` buckets holding about the
+    /// same number of `strengths` each.
+    pub fn percentile(strengths: &[Scalar], num_buckets: usize) -> Result<Self, LittleError> {
+        if num_buckets == 0 || strengths.len() < num_buckets {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one strength per bucket",
+            ));
+        }
+        if strengths.iter().any(|s| !s.is_finite()) {
+            return Err(LittleError::InvalidParameter("strengths must be finite"));
+        }
+        let mut sorted = strengths.to_vec();
+        sorted.sort_by(Scalar::total_cmp);
+        let thresholds = (1..num_buckets)
+            .map(|i| sorted[i * sorted.len() / num_buckets])
+            .collect();
+        Ok(Self {
+            buckets: Buckets::Percentile(thresholds),
+        })
+    }
+
+    /// Cluster `features` into `num_buckets` buckets with k-means,
+    /// seeded with k-means++ and run for at most `iterations`
+    /// rounds.
+    pub fn kmeans_with_rng<R: Rng>(
+        features: &[Vec<Scalar>],
+        num_buckets: usize,
+        iterations: usize,
+        rng: &mut R,
+    ) -> Result<Self, LittleError> {
+        if num_buckets == 0 || features.len() < num_buckets {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one hand per bucket",
+            ));
+        }
+        let dim = features[0].len();
+        if dim == 0
+            || features
+                .iter()
+                .any(|f| f.len() != dim || f.iter().any(|x| !x.is_finite()))
+        {
+            return Err(LittleError::InvalidParameter(
+                "features must be finite and all the same length",
+            ));
+        }
+
+        let mut centroids = vec![features[rng.gen_range(0..features.len())].clone()];
+        while centroids.len() < num_buckets {
+            let distances: Vec<Scalar> = features
+                .iter()
+                .map(|f| squared_distance(&centroids[nearest(&centroids, f)], f))
+                .collect();
+            // Every hand already sits on a centroid.
+            let next = if distances.iter().sum::<Scalar>() > 0.0 {
+                dist::sample_weights(&distances, rng)
+            } else {
+                rng.gen_range(0..features.len())
+            };
+            centroids.push(features[next].clone());
+        }
+
+        let mut assignment = vec![usize::MAX; features.len()];
+        for _i in 0..iterations {
+            let mut changed = false;
+            for (a, f) in assignment.iter_mut().zip(features) {
+                let n = nearest(&centroids, f);
+                changed |= *a != n;
+                *a = n;
+            }
+            if !changed {
+                break;
+            }
+            let mut sums = vec![vec![0.0; dim]; num_buckets];
+            let mut counts = vec![0_usize; num_buckets];
+            for (a, f) in assignment.iter().zip(features) {
+                counts[*a] += 1;
+                for (s, x) in sums[*a].iter_mut().zip(f) {
+                    *s += x;
+                }
+            }
+            // Empty clusters keep their old centroid.
+            for ((c, s), n) in centroids.iter_mut().zip(sums).zip(counts) {
+                if n > 0 {
+                    *c = s.into_iter().map(|x| x / n as Scalar).collect();
+                }
+            }
+        }
+
+        centroids.sort_by(|a, b| {
+            let (sa, sb) = (a.iter().sum::<Scalar>(), b.iter().sum::<Scalar>());
+            sa.total_cmp(&sb).then_with(|| {
+                a.iter()
+                    .zip(b)
+                    .map(|(x, y)| x.total_cmp(y))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        Ok(Self {
+            buckets: Buckets::Centroids(centroids),
+        })
+    }
+
+    pub fn num_buckets(&self) -> usize {
+        match &self.buckets {
+            Buckets::Percentile(thresholds) => thresholds.len() + 1,
+            Buckets::Centroids(centroids) => centroids.len(),
+        }
+    }
+
+    /// The bucket for a hand with `features`. Percentile buckets
+    /// take a single strength.
+    pub fn bucket(&self, features: &[Scalar]) -> Result<usize, LittleError> {
+        match &self.buckets {
+            Buckets::Percentile(thresholds) if features.len() == 1 => {
+                Ok(thresholds.partition_point(|t| *t <= features[0]))
+            }
+            Buckets::Centroids(centroids) if features.len() == centroids[0].len() => {
+                Ok(nearest(centroids, features))
+            }
+            _ => Err(LittleError::InvalidParameter(
+                "features must be the length the abstraction was built with",
+            )),
+        }
+    }
+
+    /// An information state key for a hand with `features` after
+    /// the public `history`, the same for every hand in a bucket.
+    pub fn infoset_key(&self, features: &[Scalar], history: &str) -> Result<String, LittleError> {
+        Ok(format!("bucket {}:{history}", self.bucket(features)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_percentile() {
+        let strengths: Vec<Scalar> = (0..100).map(|i| i as Scalar).collect();
+        let abstraction = CardAbstraction::percentile(&strengths, 4).unwrap();
+        assert_eq!(abstraction.num_buckets(), 4);
+        assert_eq!(abstraction.bucket(&[24.0]).unwrap(), 0);
+        assert_eq!(abstraction.bucket(&[25.0]).unwrap(), 1);
+        assert_eq!(abstraction.bucket(&[99.0]).unwrap(), 3);
+        assert_eq!(
+            abstraction.infoset_key(&[60.0], "cr").unwrap(),
+            "bucket 2:cr"
+        );
+        assert!(abstraction.bucket(&[1.0, 2.0]).is_err());
+        assert!(CardAbstraction::percentile(&strengths[..3], 4).is_err());
+    }
+
+    #[test]
+    fn test_kmeans_ids_are_stable() {
+        // Weak, middling and strong hands with a little noise.
+        let mut features = Vec::new();
+        for i in 0..30 {
+            let noise = (i % 5) as Scalar * 0.01;
+            features.push(vec![[0.8, 0.2, 0.5][i % 3] + noise, 0.1 + noise]);
+        }
+        let fit = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            CardAbstraction::kmeans_with_rng(&features, 3, 50, &mut rng).unwrap()
+        };
+        let abstraction = fit(7);
+        assert_eq!(abstraction.bucket(&[0.21, 0.1]).unwrap(), 0);
+        assert_eq!(abstraction.bucket(&[0.5, 0.12]).unwrap(), 1);
+        assert_eq!(abstraction.bucket(&[0.83, 0.1]).unwrap(), 2);
+        for seed in 0..5 {
+            assert_eq!(fit(seed), abstraction);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Action abstraction for bet sizes. A solve only considers a few
+//! sizes, given as fractions of the pot, and an opponent's bet of
+//! any other size has to be translated onto them. Translating
+//! deterministically to the nearest size is easy to exploit, so
+//! `translate` uses the randomized pseudo-harmonic mapping of
+//! Ganzfried and Sandholm, which splits a bet between the sizes on
+//! either side of it.
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use smallvec::{smallvec, SmallVec};
+
+use std::vec::Vec;
+
+use crate::dist;
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BetAbstraction {
+    // Pot fractions in increasing order.
+    sizes: Vec<Scalar>,
+}
+
+impl BetAbstraction {
+    /// The abstract bet `sizes` as fractions of the pot. They're
+    /// sorted and duplicates dropped.
+    pub fn new(sizes: &[Scalar]) -> Result<Self, LittleError> {
+        if sizes.is_empty() || sizes.iter().any(|s| !(s.is_finite() && *s > 0.0)) {
+            return Err(LittleError::InvalidParameter(
+                "bet sizes must be finite and positive, and there must be at least one",
+            ));
+        }
+        let mut sizes = sizes.to_vec();
+        sizes.sort_by(Scalar::total_cmp);
+        sizes.dedup();
+        Ok(Self { sizes })
+    }
+
+    pub fn sizes(&self) -> &[Scalar] {
+        &self.sizes
+    }
+
+    /// The abstract size closest to `size`, for picking our own
+    /// bets.
+    pub fn nearest(&self, size: Scalar) -> usize {
+        let mut best = (Scalar::INFINITY, 0);
+        for (i, s) in self.sizes.iter().enumerate() {
+            let d = (s - size).abs();
+            if d < best.0 {
+                best = (d, i);
+            }
+        }
+        best.1
+    }
+
+    /// Each abstract size an opponent's bet of `size` translates to
+    /// and how likely it is to. Bets outside the abstraction go to
+    /// the smallest or largest size.
+    pub fn translate(&self, size: Scalar) -> Result<SmallVec<[(usize, Scalar); 2]>, LittleError> {
+        if !(size.is_finite() && size >= 0.0) {
+            return Err(LittleError::InvalidParameter(
+                "bet size must be finite and non-negative",
+            ));
+        }
+        let above = self.sizes.partition_point(|s| *s < size);
+        if above == 0 {
+            return Ok(smallvec![(0, 1.0)]);
+        }
+        if above == self.sizes.len() {
+            return Ok(smallvec![(above - 1, 1.0)]);
+        }
+        let (a, b) = (self.sizes[above - 1], self.sizes[above]);
+        if size == b {
+            return Ok(smallvec![(above, 1.0)]);
+        }
+        let p = pseudo_harmonic(a, b, size);
+        Ok(smallvec![(above - 1, p), (above, 1.0 - p)])
+    }
+
+    /// Sample the abstract size for an opponent's bet of `size`.
+    pub fn translate_with_rng<R: Rng>(
+        &self,
+        size: Scalar,
+        rng: &mut R,
+    ) -> Result<usize, LittleError> {
+        let translated = self.translate(size)?;
+        let probs: SmallVec<[Scalar; 2]> = translated.iter().map(|(_, p)| *p).collect();
+        Ok(translated[dist::sample_weights(&probs, rng)].0)
+    }
+}
+
+/// How often a bet of `x` should be treated as the smaller size `a`
+/// rather than the larger `b`, all as fractions of the pot.
+#[must_use]
+pub fn pseudo_harmonic(a: Scalar, b: Scalar, x: Scalar) -> Scalar {
+    ((b - x) * (1.0 + a)) / ((b - a) * (1.0 + x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let abstraction = BetAbstraction::new(&[1.0, 0.5, 2.0, 1.0]).unwrap();
+        assert_eq!(abstraction.sizes(), &[0.5, 1.0, 2.0]);
+        assert_eq!(abstraction.nearest(0.8), 1);
+        assert_eq!(abstraction.translate(0.25).unwrap().as_slice(), &[(0, 1.0)]);
+        assert_eq!(abstraction.translate(1.0).unwrap().as_slice(), &[(1, 1.0)]);
+        assert_eq!(abstraction.translate(5.0).unwrap().as_slice(), &[(2, 1.0)]);
+        let split = abstraction.translate(0.75).unwrap();
+        assert_eq!((split[0].0, split[1].0), (0, 1));
+        assert!((split[0].1 - 3.0 / 7.0).abs() < 1e-6);
+        assert!((split[0].1 + split[1].1 - 1.0).abs() < 1e-6);
+        assert!(BetAbstraction::new(&[0.0]).is_err());
+        assert!(abstraction.translate(-1.0).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! A worked heads up example. Villain goes all in with a known
+//! range and hero learns, for every hand they could hold, whether
+//! calling or folding is better. Each hand gets its own regret
+//! minimizer fed with the rewards from `fold_call_rewards`.
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::poker;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+const FOLD: usize = 0;
+const CALL: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct CallOrFold<R: Ord, M: RegretMinimizer> {
+    // The rewards never change so they're worked out once.
+    rewards: Vec<[Scalar; 2]>,
+    hands: Vec<R>,
+    matchers: Vec<M>,
+}
+
+impl<R: Ord, M: RegretMinimizer> CallOrFold<R, M> {
+    /// Hero has put `invested` in the pot and faces an all in of
+    /// `to_call` more from `villain_range`.
+    pub fn new_with_params(
+        hero_hands: Vec<R>,
+        villain_range: &[(R, Scalar)],
+        invested: Scalar,
+        to_call: Scalar,
+    ) -> Result<Self, LittleError> {
+        let rewards = hero_hands
+            .iter()
+            .map(|h| poker::fold_call_rewards(h, villain_range, invested, to_call))
+            .collect::<Result<Vec<_>, _>>()?;
+        let matchers = hero_hands
+            .iter()
+            .map(|_| M::new(2))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            rewards,
+            hands: hero_hands,
+            matchers,
+        })
+    }
+
+    pub fn train(&mut self, iterations: usize) -> Result<(), LittleError> {
+        for _i in 0..iterations {
+            for (m, r) in self.matchers.iter_mut().zip(&self.rewards) {
+                m.update_regret(r)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn hands(&self) -> &[R] {
+        &self.hands
+    }
+
+    /// How often hero calls with the hand at `index` in the
+    /// average strategy.
+    #[must_use]
+    pub fn call_frequency(&self, index: usize) -> Scalar {
+        self.matchers[index].best_weight()[CALL]
+    }
+
+    /// How often hero folds with the hand at `index`.
+    #[must_use]
+    pub fn fold_frequency(&self, index: usize) -> Scalar {
+        self.matchers[index].best_weight()[FOLD]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_matcher::RegretMatcher;
+
+    #[test]
+    fn test_calls_strong_hands() {
+        // Villain shoves the top half of ten hand strengths.
+        let range: Vec<(u8, Scalar)> = (5..10).map(|r| (r, 1.0)).collect();
+        let mut trainer =
+            CallOrFold::<u8, RegretMatcher>::new_with_params((0..10).collect(), &range, 1.0, 4.0)
+                .unwrap();
+        trainer.train(200).unwrap();
+        assert!(trainer.call_frequency(9) > 0.95);
+        assert!(trainer.fold_frequency(0) > 0.95);
+    }
+}
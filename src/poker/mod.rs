@@ -0,0 +1,143 @@
+//! Turn poker showdowns into rewards for `update_regret`. Hand
+//! strengths can be anything `Ord` where a bigger value is a better
+//! hand, so ranks from rs-poker's evaluator can be passed straight
+//! in.
+pub mod abstraction;
+pub mod action_abstraction;
+pub mod heads_up;
+pub mod preflop;
+pub mod push_fold;
+
+use ndarray::prelude::*;
+
+use std::cmp::Ordering;
+
+use crate::errors::LittleError;
+use crate::scalar::Scalar;
+
+pub use self::abstraction::CardAbstraction;
+pub use self::action_abstraction::BetAbstraction;
+pub use self::heads_up::CallOrFold;
+pub use self::preflop::{PreflopConfig, PreflopGame};
+pub use self::push_fold::{PushFold, PushFoldChart};
+
+// Blinds in big blinds, for the heads up solvers.
+const SMALL_BLIND: Scalar = 0.5;
+const BIG_BLIND: Scalar = 1.0;
+
+/// What hero wins at showdown when both players have `stake` in
+/// the pot. Ties split it.
+pub fn showdown_reward<R: Ord>(hero: &R, villain: &R, stake: Scalar) -> Scalar {
+    match hero.cmp(villain) {
+        Ordering::Greater => stake,
+        Ordering::Less => -stake,
+        Ordering::Equal => 0.0,
+    }
+}
+
+/// Hero's expected showdown reward against a weighted range of
+/// villain hands.
+pub fn range_reward<R: Ord>(
+    hero: &R,
+    villain_range: &[(R, Scalar)],
+    stake: Scalar,
+) -> Result<Scalar, LittleError> {
+    let total: Scalar = villain_range.iter().map(|(_, w)| w).sum();
+    if villain_range
+        .iter()
+        .any(|(_, w)| !(w.is_finite() && *w >= 0.0))
+        || total <= 0.0
+    {
+        return Err(LittleError::InvalidParameter(
+            "range weights must be non-negative with a positive sum",
+        ));
+    }
+    let reward: Scalar = villain_range
+        .iter()
+        .map(|(villain, w)| w * showdown_reward(hero, villain, stake))
+        .sum();
+    Ok(reward / total)
+}
+
+/// The rewards for `[fold, call]` when facing an all in. Folding
+/// gives up the `invested` chips already in the pot and calling
+/// puts `to_call` more in and goes to showdown.
+pub fn fold_call_rewards<R: Ord>(
+    hero: &R,
+    villain_range: &[(R, Scalar)],
+    invested: Scalar,
+    to_call: Scalar,
+) -> Result<[Scalar; 2], LittleError> {
+    Ok([
+        -invested,
+        range_reward(hero, villain_range, invested + to_call)?,
+    ])
+}
+
+/// The chance of each pair of hands being dealt when every hand
+/// is dealt independently in proportion to `weights`, which
+/// ignores card removal.
+#[must_use]
+pub fn independent_deal(weights: &[Scalar]) -> Array2<Scalar> {
+    let w = ArrayView1::from(weights);
+    w.insert_axis(Axis(1)).dot(&w.insert_axis(Axis(0)))
+}
+
+// Checks an all in equity table and the chance of each deal, each
+// with a row and column per hand, and returns the deal normalized.
+fn check_equity_table(
+    num_hands: usize,
+    equity: ArrayView2<Scalar>,
+    deal: ArrayView2<Scalar>,
+) -> Result<Array2<Scalar>, LittleError> {
+    if num_hands == 0 || equity.dim() != (num_hands, num_hands) || deal.dim() != equity.dim() {
+        return Err(LittleError::InvalidParameter(
+            "equity and deal must be square with a row per hand",
+        ));
+    }
+    if equity.iter().any(|e| !(0.0..=1.0).contains(e)) {
+        return Err(LittleError::InvalidParameter(
+            "equities must be between 0 and 1",
+        ));
+    }
+    let total: Scalar = deal.sum();
+    if deal.iter().any(|p| !(p.is_finite() && *p >= 0.0)) || total <= 0.0 {
+        return Err(LittleError::InvalidParameter(
+            "deal weights must be non-negative with a positive sum",
+        ));
+    }
+    Ok(&deal / total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rs_poker::core::{Hand, Rankable};
+
+    #[test]
+    fn test_fold_call_rewards() {
+        let range = [(2_u8, 1.0), (4, 1.0), (6, 2.0)];
+        // Beats half the range by weight and loses to the rest.
+        assert_eq!(range_reward(&5, &range, 10.0).unwrap(), 0.0);
+        assert_eq!(
+            fold_call_rewards(&7, &range, 1.0, 9.0).unwrap(),
+            [-1.0, 10.0]
+        );
+        assert!(range_reward(&5, &[(1_u8, 0.0)], 1.0).is_err());
+        assert!(range_reward(&5, &[(1_u8, 1.0), (2, -0.5)], 1.0).is_err());
+        assert!(range_reward(&5, &[(1_u8, 1.0), (2, Scalar::NAN)], 1.0).is_err());
+    }
+
+    #[test]
+    fn test_rs_poker_ranks() {
+        let rank = |cards: &str| Hand::new_from_str(cards).unwrap().rank();
+        let board = "2c7d8hJsKd";
+        let aces = rank(&format!("AsAh{board}"));
+        let range = [
+            (rank(&format!("9c9d{board}")), 1.0),
+            (rank(&format!("Ts9s{board}")), 1.0),
+        ];
+        // Aces beat the nines but not the straight.
+        assert_eq!(range_reward(&aces, &range, 2.0).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,264 @@
+//! Heads up preflop solving, range against range with `VectorCfr`.
+//! The small blind acts first and each player can fold, check or
+//! call, raise to a multiple of the current bet or go all in. When
+//! the betting closes without a fold the hands are valued by their
+//! all in equity, so postflop play is treated as if both players
+//! realize their equity.
+//!
+//! Like `push_fold` the hands are whatever buckets the caller
+//! chooses, usually the 169 classes, with an equity table and the
+//! chance of each deal. A vector traversal values every hand at
+//! once with a matrix product at each terminal, so an iteration
+//! costs the size of the betting tree times the square of the
+//! number of hands rather than walking every deal.
+use ndarray::prelude::*;
+
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::Action;
+use crate::poker::{self, BIG_BLIND, SMALL_BLIND};
+use crate::scalar::Scalar;
+use crate::solver::vector::{PublicNode, RangeGame};
+
+pub const FOLD: Action = 0;
+/// Check, or call when facing a bet.
+pub const CALL: Action = 1;
+pub const ALL_IN: Action = 2;
+/// Raise to `raise_sizes[a - RAISE]` times the current bet.
+pub const RAISE: Action = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopConfig {
+    /// The effective stack in big blinds.
+    pub stack: Scalar,
+    /// Raise sizes as multiples of the bet being raised.
+    pub raise_sizes: Vec<Scalar>,
+    /// How many raises short of all in there can be.
+    pub max_raises: usize,
+}
+
+impl Default for PreflopConfig {
+    fn default() -> Self {
+        Self {
+            stack: 100.0,
+            raise_sizes: vec![3.0],
+            max_raises: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PreflopGame {
+    hands: Vec<String>,
+    // The chance of each deal, small blind's hand by big blind's.
+    deal: Array2<Scalar>,
+    // The small blind's share of the pot at showdown, from -1 to 1,
+    // times the chance of the deal.
+    showdown: Array2<Scalar>,
+    config: PreflopConfig,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopState {
+    history: Vec<Action>,
+    contributions: [Scalar; 2],
+    num_raises: usize,
+    folded: Option<usize>,
+    closed: bool,
+}
+
+impl PreflopGame {
+    /// See `PushFold::new` for `equity` and `deal`.
+    pub fn new(
+        hands: Vec<String>,
+        equity: ArrayView2<Scalar>,
+        deal: ArrayView2<Scalar>,
+        config: PreflopConfig,
+    ) -> Result<Self, LittleError> {
+        let deal = poker::check_equity_table(hands.len(), equity, deal)?;
+        if !(config.stack.is_finite() && config.stack >= BIG_BLIND) {
+            return Err(LittleError::InvalidParameter(
+                "the stack must cover the big blind",
+            ));
+        }
+        if config
+            .raise_sizes
+            .iter()
+            .any(|r| !(r.is_finite() && *r > 1.0))
+        {
+            return Err(LittleError::InvalidParameter(
+                "raise sizes must be finite and more than one",
+            ));
+        }
+        let showdown = &deal * &equity.mapv(|e| 2.0 * e - 1.0);
+        Ok(Self {
+            hands,
+            deal,
+            showdown,
+            config,
+        })
+    }
+
+    /// Like `new` with hands dealt independently in proportion to
+    /// `weights`, ignoring card removal.
+    pub fn independent(
+        hands: Vec<String>,
+        equity: ArrayView2<Scalar>,
+        weights: &[Scalar],
+        config: PreflopConfig,
+    ) -> Result<Self, LittleError> {
+        Self::new(
+            hands,
+            equity,
+            poker::independent_deal(weights).view(),
+            config,
+        )
+    }
+
+    #[must_use]
+    pub fn hands(&self) -> &[String] {
+        &self.hands
+    }
+
+    pub fn config(&self) -> &PreflopConfig {
+        &self.config
+    }
+
+    fn legal_actions(&self, state: &PreflopState) -> Vec<Action> {
+        let actor = state.history.len() % 2;
+        let bet = state.contributions[1 - actor];
+        let mut actions = Vec::with_capacity(3 + self.config.raise_sizes.len());
+        if bet > state.contributions[actor] {
+            actions.push(FOLD);
+        }
+        actions.push(CALL);
+        if bet < self.config.stack {
+            actions.push(ALL_IN);
+            if state.num_raises < self.config.max_raises {
+                actions.extend(
+                    (0..self.config.raise_sizes.len())
+                        .filter(|k| self.config.raise_sizes[*k] * bet < self.config.stack)
+                        .map(|k| RAISE + k),
+                );
+            }
+        }
+        actions
+    }
+}
+
+impl RangeGame for PreflopGame {
+    type PublicState = PreflopState;
+
+    fn num_hands(&self, _player: usize) -> usize {
+        self.hands.len()
+    }
+
+    fn root(&self) -> PreflopState {
+        PreflopState {
+            history: Vec::new(),
+            contributions: [SMALL_BLIND, BIG_BLIND],
+            num_raises: 0,
+            folded: None,
+            closed: false,
+        }
+    }
+
+    fn node(&self, state: &PreflopState) -> PublicNode {
+        if state.folded.is_some() || state.closed {
+            return PublicNode::Terminal;
+        }
+        PublicNode::Player {
+            player: state.history.len() % 2,
+            actions: self.legal_actions(state),
+        }
+    }
+
+    fn child(&self, state: &PreflopState, action: Action) -> PreflopState {
+        let mut child = state.clone();
+        let actor = state.history.len() % 2;
+        let bet = state.contributions[1 - actor];
+        match action {
+            FOLD => child.folded = Some(actor),
+            // Only the small blind's opening limp leaves the betting
+            // open.
+            CALL => {
+                child.contributions[actor] = bet;
+                child.closed = !state.history.is_empty();
+            }
+            ALL_IN => child.contributions[actor] = self.config.stack,
+            _ => {
+                child.contributions[actor] = self.config.raise_sizes[action - RAISE] * bet;
+                child.num_raises += 1;
+            }
+        }
+        child.history.push(action);
+        child
+    }
+
+    fn public_key(&self, state: &PreflopState) -> String {
+        state
+            .history
+            .iter()
+            .map(|a| match *a {
+                FOLD => String::from("f"),
+                CALL => String::from("c"),
+                ALL_IN => String::from("a"),
+                raise => format!("r{}", raise - RAISE),
+            })
+            .collect()
+    }
+
+    fn terminal_values(
+        &self,
+        state: &PreflopState,
+        player: usize,
+        opponent_reach: ArrayView1<Scalar>,
+    ) -> Array1<Scalar> {
+        let (table, small_blind_won) = match state.folded {
+            Some(0) => (&self.deal, -state.contributions[0]),
+            Some(_) => (&self.deal, state.contributions[1]),
+            None => (&self.showdown, state.contributions[0]),
+        };
+        if player == 0 {
+            table.dot(&opponent_reach) * small_blind_won
+        } else {
+            table.t().dot(&opponent_reach) * -small_blind_won
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::VectorCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_three_hands() {
+        let hands = vec!["strong".into(), "medium".into(), "weak".into()];
+        let equity = array![[0.5, 0.8, 0.9], [0.2, 0.5, 0.7], [0.1, 0.3, 0.5]];
+        let config = PreflopConfig {
+            stack: 20.0,
+            ..PreflopConfig::default()
+        };
+        let game = PreflopGame::independent(hands, equity.view(), &[1.0; 3], config).unwrap();
+        let mut cfr = VectorCfr::<_, VanillaCfrRegretMatcher>::new(game);
+        cfr.run(1_000).unwrap();
+
+        // Fold, limp, shove or raise to 3.
+        let open = cfr.average_strategy("").unwrap();
+        assert_eq!(open.ncols(), 4);
+        assert!(open[[0, FOLD]] < 0.01, "{open}");
+        let facing_shove = cfr.average_strategy("a").unwrap();
+        assert!(facing_shove[[0, CALL]] > 0.99, "{facing_shove}");
+        assert!(facing_shove[[2, CALL]] < 0.01, "{facing_shove}");
+        // The second raise to 9 is followed by one to 27, which is
+        // more than the stack.
+        assert_eq!(cfr.average_strategy("r0r0").unwrap().ncols(), 3);
+
+        let [one, two] = cfr.iterate().unwrap();
+        assert!((one + two).abs() < 0.01, "{one} {two}");
+    }
+}
@@ -0,0 +1,231 @@
+//! Heads up push or fold, solved with `GameCfr`. The small blind
+//! either folds or goes all in for the effective stack and the big
+//! blind either folds or calls. With stacks of about 15 big blinds
+//! or less this is close to optimal play, and the answer is a chart
+//! of how often to push and call with each hand.
+//!
+//! Hands are whatever the caller buckets them into, usually the 169
+//! preflop classes, and come with a table of each hand's all in
+//! equity against each other hand, from rs-poker or any other
+//! evaluator.
+use ndarray::prelude::*;
+
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::exploitability::Policy;
+use crate::games::game::{Action, Game, Player, State};
+use crate::poker::{self, BIG_BLIND, SMALL_BLIND};
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+use crate::solver::GameCfr;
+
+pub const FOLD: Action = 0;
+/// Push for the small blind and call for the big blind.
+pub const ALL_IN: Action = 1;
+
+#[derive(Debug)]
+struct Table {
+    hands: Vec<String>,
+    // Every deal with a positive chance, as the small blind's hand,
+    // the big blind's hand and the chance.
+    deals: Vec<(usize, usize, Scalar)>,
+    equity: Array2<Scalar>,
+    stack: Scalar,
+}
+
+#[derive(Debug, Clone)]
+pub struct PushFold(Rc<Table>);
+
+#[derive(Debug, Clone)]
+pub struct PushFoldState {
+    table: Rc<Table>,
+    deal: Option<usize>,
+    actions: Vec<Action>,
+}
+
+/// How often each hand is pushed by the small blind and called
+/// with by the big blind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldChart {
+    pub hands: Vec<String>,
+    pub push: Vec<Scalar>,
+    pub call: Vec<Scalar>,
+}
+
+impl PushFold {
+    /// `equity[[i, j]]` is the share of the pot hand `i` wins all in
+    /// against hand `j`, counting ties as half, and `deal[[i, j]]`
+    /// is how likely the small blind is to hold `i` while the big
+    /// blind holds `j`, which is where card removal comes in.
+    /// `stack` is the effective stack in big blinds.
+    pub fn new(
+        hands: Vec<String>,
+        equity: Array2<Scalar>,
+        deal: ArrayView2<Scalar>,
+        stack: Scalar,
+    ) -> Result<Self, LittleError> {
+        let deal = poker::check_equity_table(hands.len(), equity.view(), deal)?;
+        if !(stack.is_finite() && stack >= BIG_BLIND) {
+            return Err(LittleError::InvalidParameter(
+                "the stack must cover the big blind",
+            ));
+        }
+        let deals = deal
+            .indexed_iter()
+            .filter(|(_, p)| **p > 0.0)
+            .map(|((i, j), p)| (i, j, *p))
+            .collect();
+        Ok(Self(Rc::new(Table {
+            hands,
+            deals,
+            equity,
+            stack,
+        })))
+    }
+
+    /// Like `new` where each player's hand is dealt independently
+    /// with the chance given by `weights`, ignoring card removal.
+    pub fn independent(
+        hands: Vec<String>,
+        equity: Array2<Scalar>,
+        weights: &[Scalar],
+        stack: Scalar,
+    ) -> Result<Self, LittleError> {
+        let w = ArrayView1::from(weights);
+        let deal = w.insert_axis(Axis(1)).dot(&w.insert_axis(Axis(0)));
+        Self::new(hands, equity, deal.view(), stack)
+    }
+
+    #[must_use]
+    pub fn hands(&self) -> &[String] {
+        &self.0.hands
+    }
+
+    pub fn stack(&self) -> Scalar {
+        self.0.stack
+    }
+
+    /// Run `iterations` of CFR and read the chart off the average
+    /// strategy.
+    pub fn solve<M: RegretMinimizer>(
+        &self,
+        iterations: usize,
+    ) -> Result<PushFoldChart, LittleError> {
+        let mut cfr = GameCfr::<_, M>::new(self.clone());
+        cfr.run(iterations)?;
+        Ok(self.chart(&cfr.average_policy()))
+    }
+
+    /// The chart for a policy over this game's information states.
+    /// Hands that are never dealt are left at zero.
+    pub fn chart(&self, policy: &Policy) -> PushFoldChart {
+        let frequency = |position: &str, hand: &String| {
+            policy
+                .get(&format!("{position} {hand}"))
+                .map_or(0.0, |s| s[1])
+        };
+        PushFoldChart {
+            hands: self.0.hands.clone(),
+            push: self.0.hands.iter().map(|h| frequency("sb", h)).collect(),
+            call: self.0.hands.iter().map(|h| frequency("bb", h)).collect(),
+        }
+    }
+}
+
+impl Game for PushFold {
+    type State = PushFoldState;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    fn num_distinct_actions(&self) -> usize {
+        2
+    }
+
+    fn new_initial_state(&self) -> PushFoldState {
+        PushFoldState {
+            table: Rc::clone(&self.0),
+            deal: None,
+            actions: Vec::with_capacity(2),
+        }
+    }
+}
+
+impl State for PushFoldState {
+    fn current_player(&self) -> Player {
+        match (self.deal, self.actions.as_slice()) {
+            (None, _) => Player::Chance,
+            (_, []) => Player::Id(0),
+            (_, [ALL_IN]) => Player::Id(1),
+            _ => Player::Terminal,
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        match self.current_player() {
+            Player::Id(_) => vec![FOLD, ALL_IN],
+            _ => Vec::new(),
+        }
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        self.table
+            .deals
+            .iter()
+            .enumerate()
+            .map(|(d, (_, _, p))| (d, *p))
+            .collect()
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        if self.deal.is_none() {
+            self.deal = Some(action);
+        } else {
+            self.actions.push(action);
+        }
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        let (sb, bb, _) = self.table.deals[self.deal.unwrap()];
+        let won = match self.actions.as_slice() {
+            [FOLD] => -SMALL_BLIND,
+            [_, FOLD] => BIG_BLIND,
+            _ => self.table.stack * (2.0 * self.table.equity[[sb, bb]] - 1.0),
+        };
+        vec![won, -won]
+    }
+
+    fn information_state_string(&self, player: usize) -> String {
+        let (sb, bb, _) = self.table.deals[self.deal.unwrap()];
+        match player {
+            0 => format!("sb {}", self.table.hands[sb]),
+            _ => format!("bb {}", self.table.hands[bb]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::exploitability;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_three_hands() {
+        let hands = vec!["strong".into(), "medium".into(), "weak".into()];
+        let equity = array![[0.5, 0.8, 0.9], [0.2, 0.5, 0.7], [0.1, 0.3, 0.5]];
+        let game = PushFold::independent(hands, equity, &[1.0; 3], 10.0).unwrap();
+        let chart = game.solve::<VanillaCfrRegretMatcher>(2_000).unwrap();
+        assert!(chart.push[0] > 0.99 && chart.call[0] > 0.99, "{chart:?}");
+        assert!(chart.call[2] < 0.01, "{chart:?}");
+
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(game.clone());
+        cfr.run(2_000).unwrap();
+        assert!(exploitability(&game, &cfr.average_policy()) < 0.05);
+        assert_eq!(game.chart(&cfr.average_policy()), chart);
+    }
+}
@@ -0,0 +1,281 @@
+//! Cheap counters for working out why a game converges slowly.
+//! The hot paths count events, like an alias table being rebuilt or
+//! regret matching falling back to uniform, in thread local totals.
+//! `Profiled` wraps a matcher and attributes whatever happened on
+//! the thread during each of its calls to it, so `stats()` reports
+//! what that one matcher has been doing.
+//!
+//! Allocations are only counted when `CountingAllocator` is
+//! installed as the global allocator:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: little_sorry::profiling::CountingAllocator =
+//!     little_sorry::profiling::CountingAllocator;
+//! ```
+use rand::Rng;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ops::{AddAssign, Sub};
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileStats {
+    pub updates: u64,
+    pub allocations: u64,
+    /// Alias tables built for sampling.
+    pub dist_rebuilds: u64,
+    /// Actions sampled by walking the cumulative weights.
+    pub walk_samples: u64,
+    /// Actions sampled from an alias table.
+    pub alias_samples: u64,
+    /// Times regret matching found no positive regret and played
+    /// uniformly instead.
+    pub uniform_fallbacks: u64,
+}
+
+impl ProfileStats {
+    const ZERO: Self = Self {
+        updates: 0,
+        allocations: 0,
+        dist_rebuilds: 0,
+        walk_samples: 0,
+        alias_samples: 0,
+        uniform_fallbacks: 0,
+    };
+}
+
+impl Sub for ProfileStats {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            updates: self.updates - other.updates,
+            allocations: self.allocations - other.allocations,
+            dist_rebuilds: self.dist_rebuilds - other.dist_rebuilds,
+            walk_samples: self.walk_samples - other.walk_samples,
+            alias_samples: self.alias_samples - other.alias_samples,
+            uniform_fallbacks: self.uniform_fallbacks - other.uniform_fallbacks,
+        }
+    }
+}
+
+impl AddAssign for ProfileStats {
+    fn add_assign(&mut self, other: Self) {
+        self.updates += other.updates;
+        self.allocations += other.allocations;
+        self.dist_rebuilds += other.dist_rebuilds;
+        self.walk_samples += other.walk_samples;
+        self.alias_samples += other.alias_samples;
+        self.uniform_fallbacks += other.uniform_fallbacks;
+    }
+}
+
+thread_local! {
+    static TOTALS: Cell<ProfileStats> = const { Cell::new(ProfileStats::ZERO) };
+}
+
+/// Count an event against the current thread.
+pub(crate) fn record(f: impl FnOnce(&mut ProfileStats)) {
+    TOTALS.with(|totals| {
+        let mut stats = totals.get();
+        f(&mut stats);
+        totals.set(stats);
+    });
+}
+
+/// Everything counted on the current thread so far.
+#[must_use]
+pub fn thread_stats() -> ProfileStats {
+    TOTALS.with(Cell::get)
+}
+
+/// The system allocator, counting every allocation made.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(|s| s.allocations += 1);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(|s| s.allocations += 1);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(|s| s.allocations += 1);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+// Run `f` and add whatever was counted meanwhile to `stats`.
+fn measure<T>(stats: &Cell<ProfileStats>, f: impl FnOnce() -> T) -> T {
+    let before = thread_stats();
+    let result = f();
+    let mut total = stats.get();
+    total += thread_stats() - before;
+    stats.set(total);
+    result
+}
+
+#[derive(Debug, Clone)]
+pub struct Profiled<M: RegretMinimizer> {
+    inner: M,
+    stats: Cell<ProfileStats>,
+}
+
+impl<M: RegretMinimizer> Profiled<M> {
+    #[must_use]
+    pub fn new_with_params(inner: M) -> Self {
+        Self {
+            inner,
+            stats: Cell::new(ProfileStats::default()),
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// What this matcher has done since it was created or its
+    /// stats were last cleared.
+    #[must_use]
+    pub fn stats(&self) -> ProfileStats {
+        self.stats.get()
+    }
+
+    pub fn clear_stats(&mut self) {
+        self.stats.set(ProfileStats::default());
+    }
+}
+
+impl<M: RegretMinimizer> RegretMinimizer for Profiled<M> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(M::new(num_experts)?))
+    }
+
+    fn num_experts(&self) -> usize {
+        self.inner.num_experts()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.inner.num_updates()
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        self.inner.hyperparameters()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        measure(&self.stats, || self.inner.next_action(rng))
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        let result = measure(&self.stats, || {
+            self.inner.update_regret_with_options(rewards, options)
+        });
+        let mut stats = self.stats.get();
+        stats.updates += 1;
+        self.stats.set(stats);
+        result
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        measure(&self.stats, || {
+            self.inner.accumulate_average(strategy, weight)
+        })
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.inner.current_strategy()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        measure(&self.stats, || self.inner.best_weight())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.inner.cumulative_regrets()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.inner = self.inner.with_averaging(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.inner.averaging()
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.clear_stats();
+        self.inner.reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.inner.soft_reset()
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(M::warm_start_from_strategy(
+            strategy,
+        )?))
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Ok(Self::new_with_params(M::warm_start_from_regrets(regrets)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[global_allocator]
+    static ALLOC: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_stats() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut m = Profiled::<VanillaCfrRegretMatcher>::new(20).unwrap();
+        let mut rewards = vec![0.0; 20];
+        m.update_regret(&rewards).unwrap();
+        rewards[3] = 1.0;
+        m.update_regret(&rewards).unwrap();
+        assert_eq!(m.next_action(&mut rng), 3);
+        m.next_action(&mut rng);
+
+        let stats = m.stats();
+        assert_eq!(stats.updates, 2);
+        assert_eq!(stats.uniform_fallbacks, 1);
+        assert_eq!(stats.alias_samples, 2);
+        // The table is only built once for the two samples.
+        assert_eq!(stats.dist_rebuilds, 1);
+        assert!(stats.allocations > 0);
+    }
+}
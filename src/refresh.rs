@@ -0,0 +1,203 @@
+//! Sample from a strategy that is only refreshed every `every`
+//! updates. Between refreshes actions come from a slightly stale
+//! copy of the strategy, which is a common Monte Carlo CFR trick
+//! when strategies change far more often than they're sampled. How
+//! far the sampled strategy has drifted is exposed so the bias can
+//! be kept in check.
+use rand::distributions::Distribution;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+
+const DEFAULT_EVERY: usize = 10;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RefreshWrapper<M: RegretMinimizer> {
+    inner: M,
+    every: usize,
+    // Updates since the sampled strategy was last refreshed.
+    since_refresh: usize,
+    // The strategy actions are actually sampled from.
+    dist: StrategyDist,
+}
+
+impl<M: RegretMinimizer> RefreshWrapper<M> {
+    /// Refresh the sampled strategy from `inner` every `every`
+    /// updates.
+    pub fn new_with_params(inner: M, every: usize) -> Result<Self, LittleError> {
+        if every == 0 {
+            return Err(LittleError::InvalidParameter("every must be positive"));
+        }
+        let dist = StrategyDist::new(inner.current_strategy().to_vec())?;
+        Ok(Self {
+            inner,
+            every,
+            since_refresh: 0,
+            dist,
+        })
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// The possibly stale strategy actions are sampled from.
+    #[must_use]
+    pub fn sampled_strategy(&self) -> &[Scalar] {
+        self.dist.weights()
+    }
+
+    /// How many updates the sampled strategy is behind.
+    #[must_use]
+    pub fn staleness(&self) -> usize {
+        self.since_refresh
+    }
+
+    /// The most updates the sampled strategy can ever be behind.
+    #[must_use]
+    pub fn max_staleness(&self) -> usize {
+        self.every - 1
+    }
+
+    /// The total variation distance between the sampled strategy
+    /// and the current one. Any expected reward bounded by `r`
+    /// differs by at most `2 * r` times this between the two.
+    #[must_use]
+    pub fn sampling_error(&self) -> Scalar {
+        0.5 * self
+            .sampled_strategy()
+            .iter()
+            .zip(self.inner.current_strategy())
+            .map(|(s, c)| (s - c).abs())
+            .sum::<Scalar>()
+    }
+
+    /// Sample from the current strategy from now on.
+    pub fn refresh(&mut self) -> Result<(), LittleError> {
+        self.dist.set_weights(self.inner.current_strategy())?;
+        self.since_refresh = 0;
+        Ok(())
+    }
+}
+
+impl<M: RegretMinimizer> RegretMinimizer for RefreshWrapper<M> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(M::new(num_experts)?, DEFAULT_EVERY)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.inner.num_experts()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.inner.num_updates()
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        let mut params = self.inner.hyperparameters();
+        params.push(("refresh_every", self.every as Scalar));
+        params
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.inner.update_regret_with_options(rewards, options)?;
+        self.since_refresh += 1;
+        if self.since_refresh >= self.every {
+            self.refresh()?;
+        }
+        Ok(())
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.inner.accumulate_average(strategy, weight)
+    }
+
+    /// The up to date strategy, which may not be what's sampled.
+    fn current_strategy(&self) -> &[Scalar] {
+        self.inner.current_strategy()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.inner.cumulative_regrets()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.inner = self.inner.with_averaging(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.inner.averaging()
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.inner.reset()?;
+        self.refresh()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.inner.soft_reset()?;
+        self.refresh()
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(M::warm_start_from_strategy(strategy)?, DEFAULT_EVERY)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(M::warm_start_from_regrets(regrets)?, DEFAULT_EVERY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_refresh_interval() {
+        let inner = VanillaCfrRegretMatcher::new(2).unwrap();
+        let mut m = RefreshWrapper::new_with_params(inner, 3).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+        assert_eq!(m.sampled_strategy(), &[0.5, 0.5]);
+        assert_eq!(m.staleness(), 1);
+        assert_eq!(m.sampling_error(), 0.5);
+
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(m.sampled_strategy(), &[1.0, 0.0]);
+        assert_eq!(m.staleness(), 0);
+        assert_eq!(m.max_staleness(), 2);
+        assert!(
+            RefreshWrapper::new_with_params(VanillaCfrRegretMatcher::new(2).unwrap(), 0).is_err()
+        );
+    }
+}
@@ -0,0 +1,213 @@
+#![allow(clippy::cast_precision_loss)]
+//! Tracking the regret actually incurred next to what theory
+//! promises. The tracker sees the strategy played and the rewards
+//! each round, so it measures true external regret whatever the
+//! algorithm keeps internally, and computes the known bound for the
+//! algorithm from the number of actions, the rounds so far and the
+//! largest spread of rewards seen in a round.
+//!
+//! Both are reported as average regret per unit of weight, so
+//! linear CFR's weighted regret can be compared with the others.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+/// The algorithms with a known regret bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RegretBound {
+    /// Regret matching and regret matching plus, where regret grows
+    /// like `range * sqrt(num_actions * rounds)`.
+    RegretMatching,
+    /// Regret matching with round `t` weighted by `t`, as in linear
+    /// CFR.
+    LinearCfr,
+    /// Hedge with a fixed learning rate `eta`, where regret is at
+    /// most `ln(num_actions) / eta + eta * rounds * range^2 / 8`.
+    Hedge { eta: Scalar },
+}
+
+impl RegretBound {
+    fn weight(self, round: usize) -> Scalar {
+        match self {
+            Self::LinearCfr => round as Scalar,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RegretBoundTracker {
+    bound: RegretBound,
+    // Weighted reward of always playing each action.
+    action_rewards: Vec<Scalar>,
+    // Weighted reward of the strategies actually played.
+    played_reward: Scalar,
+    total_weight: Scalar,
+    total_squared_weight: Scalar,
+    reward_range: Scalar,
+    num_rounds: usize,
+}
+
+impl RegretBoundTracker {
+    pub fn new(bound: RegretBound, num_actions: usize) -> Result<Self, LittleError> {
+        if num_actions == 0 {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one action",
+            ));
+        }
+        if let RegretBound::Hedge { eta } = bound {
+            if !(eta > 0.0 && eta.is_finite()) {
+                return Err(LittleError::InvalidParameter(
+                    "eta must be finite and positive",
+                ));
+            }
+        }
+        Ok(Self {
+            bound,
+            action_rewards: vec![0.0; num_actions],
+            played_reward: 0.0,
+            total_weight: 0.0,
+            total_squared_weight: 0.0,
+            reward_range: 0.0,
+            num_rounds: 0,
+        })
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.num_rounds
+    }
+
+    /// Record a round where `strategy` was played and each action
+    /// would have earned `rewards`.
+    pub fn observe(&mut self, strategy: &[Scalar], rewards: &[Scalar]) -> Result<(), LittleError> {
+        if strategy.len() != self.action_rewards.len() || rewards.len() != strategy.len() {
+            return Err(LittleError::InvalidParameter(
+                "strategy and rewards must have an entry per action",
+            ));
+        }
+        self.num_rounds += 1;
+        let weight = self.bound.weight(self.num_rounds);
+        for (total, r) in self.action_rewards.iter_mut().zip(rewards) {
+            *total += weight * r;
+        }
+        let value: Scalar = strategy.iter().zip(rewards).map(|(p, r)| p * r).sum();
+        self.played_reward += weight * value;
+        self.total_weight += weight;
+        self.total_squared_weight += weight * weight;
+        let max = rewards
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        let min = rewards.iter().copied().fold(Scalar::INFINITY, Scalar::min);
+        self.reward_range = self.reward_range.max(max - min);
+        Ok(())
+    }
+
+    /// Record the round `matcher` is about to play and then update
+    /// it with `rewards`.
+    pub fn update<M: RegretMinimizer>(
+        &mut self,
+        matcher: &mut M,
+        rewards: &[Scalar],
+    ) -> Result<(), LittleError> {
+        self.observe(matcher.current_strategy(), rewards)?;
+        matcher.update_regret(rewards)
+    }
+
+    /// The weighted average regret for not having always played the
+    /// best single action.
+    #[must_use]
+    pub fn observed_regret(&self) -> Scalar {
+        if self.total_weight == 0.0 {
+            return 0.0;
+        }
+        let best = self
+            .action_rewards
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        (best - self.played_reward) / self.total_weight
+    }
+
+    /// The most `observed_regret` can be according to theory.
+    #[must_use]
+    pub fn theoretical_bound(&self) -> Scalar {
+        if self.total_weight == 0.0 {
+            return 0.0;
+        }
+        let num_actions = self.action_rewards.len() as Scalar;
+        let regret = match self.bound {
+            RegretBound::RegretMatching | RegretBound::LinearCfr => {
+                self.reward_range * (num_actions * self.total_squared_weight).sqrt()
+            }
+            RegretBound::Hedge { eta } => {
+                num_actions.ln() / eta
+                    + eta * self.num_rounds as Scalar * self.reward_range * self.reward_range / 8.0
+            }
+        };
+        regret / self.total_weight
+    }
+
+    /// How much of the bound the observed regret uses up. Values
+    /// near zero mean theory is very pessimistic.
+    #[must_use]
+    pub fn tightness(&self) -> Scalar {
+        let bound = self.theoretical_bound();
+        if bound > 0.0 {
+            self.observed_regret() / bound
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::regret_matcher::RegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Rewards for rock paper scissors against a random opponent.
+    fn rewards<R: Rng>(rng: &mut R) -> [Scalar; 3] {
+        match rng.gen_range(0..3) {
+            0 => [0.0, 1.0, -1.0],
+            1 => [-1.0, 0.0, 1.0],
+            _ => [1.0, -1.0, 0.0],
+        }
+    }
+
+    #[test]
+    fn test_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut rm = RegretMatcher::new(3).unwrap();
+        let mut linear = DiscountedRegretMatcher::lcfr(3).unwrap();
+        let mut rm_tracker = RegretBoundTracker::new(RegretBound::RegretMatching, 3).unwrap();
+        let mut linear_tracker = RegretBoundTracker::new(RegretBound::LinearCfr, 3).unwrap();
+        for _i in 0..1_000 {
+            let r = rewards(&mut rng);
+            rm_tracker.update(&mut rm, &r).unwrap();
+            linear_tracker
+                .observe(linear.current_strategy(), &r)
+                .unwrap();
+            linear.update_regret(&r).unwrap();
+        }
+        for tracker in [&rm_tracker, &linear_tracker] {
+            assert!(tracker.theoretical_bound() > 0.0);
+            assert!(tracker.observed_regret() <= tracker.theoretical_bound());
+            assert!(tracker.tightness() < 1.0);
+        }
+        // Two is the spread of rewards in rock paper scissors.
+        let expected = 2.0 * (3.0 as Scalar).sqrt() / (1_000.0 as Scalar).sqrt();
+        assert!((rm_tracker.theoretical_bound() - expected).abs() < 1e-4);
+        assert!(RegretBoundTracker::new(RegretBound::Hedge { eta: 0.0 }, 3).is_err());
+    }
+}
@@ -3,30 +3,40 @@
 //! with rust. Specifically this is mostly about poker.
 use ndarray::prelude::*;
 use rand::distributions::Distribution;
-use rand::thread_rng;
-use rand_distr::WeightedAliasIndex;
+use rand::{thread_rng, Rng};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use std::vec::Vec;
 
+use crate::averaging::AveragingScheme;
+use crate::compensated::{Compensated, CompensatedArray};
+use crate::dist::StrategyDist;
 use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RegretMatcher {
     // The chance each expert has of being chosen
-    p: Array1<f32>,
-    sum_p: Array1<f32>,
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
     // The amount the expert has accumulated
-    expert_reward: Array1<f32>,
+    expert_reward: CompensatedArray<Accumulator>,
     // The cumulative reward earned
-    cumulative_reward: f32,
+    cumulative_reward: Compensated<Accumulator>,
     // The distribution that generates actions.
-    dist: WeightedAliasIndex<f32>,
+    dist: StrategyDist,
     num_updates: usize,
 }
 
 impl RegretMatcher {
-    fn init_weights(num_experts: usize) -> Vec<f32> {
-        vec![1.0 / num_experts as f32; num_experts]
+    fn init_weights(num_experts: usize) -> Vec<Scalar> {
+        vec![1.0 / num_experts as Scalar; num_experts]
     }
     pub fn new(num_experts: usize) -> Result<Self, LittleError> {
         // Every expert starts out with a weight.
@@ -34,45 +44,74 @@ impl RegretMatcher {
         let p = Self::init_weights(num_experts);
         Self::new_from_p(p)
     }
-    pub fn new_from_p(p: Vec<f32>) -> Result<Self, LittleError> {
+    pub fn new_from_p(p: Vec<Scalar>) -> Result<Self, LittleError> {
         // We're going to move p so capture it now
         let num_experts = p.len();
         // Create the distribution. This is a lot of
         // precompute
-        let dist = WeightedAliasIndex::new(p.clone())?;
+        let dist = StrategyDist::new(p.clone())?;
         Ok(Self {
             p: Array1::from(p),
-            sum_p: Array1::zeros(num_experts),
-            cumulative_reward: 0.0_f32,
-            expert_reward: Array1::from(vec![0.0_f32; num_experts]),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_reward: Compensated::new(0.0),
+            expert_reward: CompensatedArray::zeros(num_experts),
             dist,
             num_updates: 0,
         })
     }
+    /// Start with `regrets` already accumulated, as if the
+    /// experts had earned that much more than was played.
+    pub fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut p = vec![0.0; regrets.len()];
+        regret_minimizer::regret_match(regrets, &mut p);
+        let mut matcher = Self::new_from_p(p)?;
+        matcher.expert_reward = CompensatedArray::from(scalar::widen(regrets));
+        Ok(matcher)
+    }
+    /// Start as if `strategy` had been played once. Unlike
+    /// `new_from_p` the strategy also counts towards the regrets
+    /// and the average so it isn't forgotten after one update.
+    pub fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::warm_start_from_regrets(&strategy)?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
     pub fn next_action(&self) -> usize {
         self.dist.sample(&mut thread_rng())
     }
 
-    pub fn update_regret(&mut self, reward_array: ArrayView1<f32>) -> Result<(), LittleError> {
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
         let num_experts = self.p.len();
         // Compute how much reward we could expect.
         // Any reward for an agent with a very low p will be very low.
+        let weight = options.regret_weight;
         let r = self.p.dot(&reward_array);
         // Keep track of the total
-        self.cumulative_reward += r;
+        self.cumulative_reward.add((weight * r) as Accumulator);
         // Keep track of total un scaled amount each agent would win
-        self.expert_reward += &reward_array;
+        self.expert_reward
+            .scaled_add(weight as Accumulator, reward_array);
         // The amount that each expert would be rewarded minus the expected value is the regret.
-        let regret = &self.expert_reward - self.cumulative_reward;
+        let regret = &self.expert_reward.values() - self.cumulative_reward.value();
         // Any regret that's negative is performing much worse than the
         // current suggestion. So just don't try and use it.
-        let capped_regret: Array1<f32> = regret.iter().map(|v: &f32| f32::max(0.0, *v)).collect();
+        let capped_regret: Array1<Scalar> = regret
+            .iter()
+            .map(|v: &Accumulator| Accumulator::max(0.0, *v) as Scalar)
+            .collect();
         let regret_sum = capped_regret.sum();
         if regret_sum <= 0.0 {
             // This shouldn't happen but if it does then don't count the previous tries.
             self.p = Array1::from(Self::init_weights(num_experts));
-            self.cumulative_reward = 0.0;
-            self.expert_reward = Array1::zeros(num_experts);
+            self.cumulative_reward = Compensated::new(0.0);
+            self.expert_reward = CompensatedArray::zeros(num_experts);
             self.num_updates = 0;
         } else {
             // The new probablities are the capped
@@ -80,21 +119,127 @@ impl RegretMatcher {
             // give a number between 0 and 1. These
             // numbers should sum to 1
             self.p = capped_regret / regret_sum;
+            // Need to keep track of the number of times update_regret has been called.
+            self.num_updates += 1;
             // We'll use the sum_p to keep track of our best
             // guesses over all time. This will keep from
             // swinging wildly for any times that the more
             // than one agent has credibility.
-            self.sum_p += &self.p;
-            // Need to keep track of the number of times update_regret has been called.
-            self.num_updates += 1;
+            if options.contribute_to_average {
+                self.averaging.accumulate_weighted(
+                    self.num_updates,
+                    &mut self.sum_p,
+                    self.p.view(),
+                    options.average_weight,
+                );
+            }
         }
-        self.dist = WeightedAliasIndex::new(self.p.to_vec())?;
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
         Ok(())
     }
 
     #[must_use]
-    pub fn best_weight(&self) -> Vec<f32> {
-        (self.sum_p.clone() / self.num_updates as f32).to_vec()
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    /// The chance each expert has of being chosen next.
+    #[must_use]
+    pub fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    /// The weighted sum of every strategy played so far. This is
+    /// `best_weight` before it's normalized.
+    #[must_use]
+    pub fn cumulative_strategy(&self) -> &[Accumulator] {
+        self.sum_p.as_slice()
+    }
+
+    /// The total reward each expert would have earned.
+    #[must_use]
+    pub fn expert_rewards(&self) -> &[Accumulator] {
+        self.expert_reward.as_slice()
+    }
+}
+
+impl RegretMinimizer for RegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new(num_experts)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        Self::current_strategy(self)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        let regret = &self.expert_reward.values() - self.cumulative_reward.value();
+        scalar::narrow(regret.view())
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.expert_reward.fill(0.0);
+        self.cumulative_reward = Compensated::new(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Self::warm_start_from_strategy(strategy)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Self::warm_start_from_regrets(regrets)
     }
 }
 
@@ -111,6 +256,24 @@ mod tests {
         let _rg = RegretMatcher::new(3);
     }
 
+    #[test]
+    fn test_warm_start_from_strategy() {
+        let mut rg = RegretMatcher::warm_start_from_strategy(&[3.0, 1.0]).unwrap();
+        assert_eq!(rg.current_strategy(), &[0.75, 0.25]);
+        // A reward that favors neither expert keeps the warm start.
+        rg.update_regret(&[0.0, 0.0]).unwrap();
+        assert_eq!(rg.best_weight(), vec![0.75, 0.25]);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mut rg = RegretMatcher::new(2).unwrap();
+        rg.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(rg.expert_rewards(), &[1.0, 0.0]);
+        assert_eq!(rg.current_strategy(), &[1.0, 0.0]);
+        assert_eq!(rg.cumulative_strategy(), &[1.0, 0.0]);
+    }
+
     #[test]
     fn test_next_action() {
         let rg = RegretMatcher::new(100).unwrap();
@@ -0,0 +1,925 @@
+#![allow(clippy::cast_precision_loss)]
+//! The shared interfaces for regret minimizers, both full
+//! information and bandit feedback, along with the small helpers
+//! that most of them use to turn regrets into strategies.
+use rand::distributions::Distribution;
+use rand::{Rng, RngCore};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Debug;
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::dist::{self, StrategyDist};
+use crate::errors::LittleError;
+use crate::kernels;
+use crate::report::{self, MatcherReport};
+use crate::scalar::Scalar;
+use crate::workspace::Workspace;
+
+/// A learner that is told the reward of every action each round
+/// and tries to have no regret for not having played any single one.
+pub trait RegretMinimizer: Clone + Sized {
+    /// Create a minimizer with the default parameters.
+    fn new(num_experts: usize) -> Result<Self, LittleError>;
+
+    fn num_experts(&self) -> usize;
+
+    /// How many times the regrets have been updated.
+    fn num_updates(&self) -> usize;
+
+    /// The parameters the algorithm was configured with, for
+    /// reporting. Algorithms without any return nothing.
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        Vec::new()
+    }
+
+    /// A snapshot of the matcher that can be serialized.
+    fn to_report(&self) -> MatcherReport {
+        MatcherReport {
+            algorithm: report::algorithm_name::<Self>(),
+            params: self
+                .hyperparameters()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            num_updates: self.num_updates(),
+            current_strategy: self.current_strategy().to_vec(),
+            average_strategy: self.best_weight(),
+            cumulative_regrets: self.cumulative_regrets(),
+        }
+    }
+
+    /// Sample an action from the current strategy.
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize;
+
+    /// The most likely action in the average strategy, with ties
+    /// going to the lowest index. This is the purified policy to
+    /// deploy when randomizing isn't wanted.
+    fn best_action(&self) -> usize {
+        argmax(&self.best_weight())
+    }
+
+    /// Like `best_action` with control over how ties are broken.
+    fn next_action_greedy<R: Rng>(&self, rng: &mut R, tie_break: TieBreak) -> usize {
+        tie_break.argmax(&self.best_weight(), rng)
+    }
+
+    /// The average strategy with every action played less than
+    /// `threshold` of the time removed and the rest scaled back up
+    /// to sum to one.
+    fn purified_weight(&self, threshold: Scalar) -> Result<Vec<Scalar>, LittleError> {
+        purify_strategy(&self.best_weight(), threshold)
+    }
+
+    /// Always play `best_action`.
+    fn fully_purified_weight(&self) -> Vec<Scalar> {
+        let mut p = vec![0.0; self.num_experts()];
+        p[self.best_action()] = 1.0;
+        p
+    }
+
+    /// Feed in the reward every action would have gotten this round.
+    fn update_regret(&mut self, rewards: &[Scalar]) -> Result<(), LittleError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("update_regret", num_experts = rewards.len()).entered();
+        self.update_regret_with_options(rewards, UpdateOptions::default())?;
+        #[cfg(feature = "tracing")]
+        trace_regrets(self);
+        Ok(())
+    }
+
+    /// Apply one update per round, in order. Each round can be
+    /// anything that derefs to a slice, like a `Vec` or an array. The
+    /// sampling distribution is only rebuilt the next time an action
+    /// is sampled, so the rounds in between cost nothing extra.
+    fn update_regret_batch<R: AsRef<[Scalar]>>(&mut self, rounds: &[R]) -> Result<(), LittleError> {
+        if rounds
+            .iter()
+            .any(|r| r.as_ref().len() != self.num_experts())
+        {
+            return Err(LittleError::InvalidParameter(
+                "rounds must have one entry per expert",
+            ));
+        }
+        for r in rounds {
+            self.update_regret(r.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Sample an action from the current strategy restricted to
+    /// the actions where `mask` is true. When the strategy already
+    /// gives the illegal actions nothing this samples from the
+    /// matcher's own distribution.
+    fn next_action_masked<R: Rng>(&self, rng: &mut R, mask: &[bool]) -> Result<usize, LittleError> {
+        self.next_action_masked_with_workspace(rng, mask, &mut Workspace::new())
+    }
+
+    /// Like `next_action_masked` using `workspace` for temporaries.
+    fn next_action_masked_with_workspace<R: Rng>(
+        &self,
+        rng: &mut R,
+        mask: &[bool],
+        workspace: &mut Workspace,
+    ) -> Result<usize, LittleError> {
+        let strategy = self.current_strategy();
+        if mask.len() == strategy.len()
+            && mask.iter().any(|legal| *legal)
+            && strategy
+                .iter()
+                .zip(mask)
+                .all(|(p, legal)| *legal || *p == 0.0)
+        {
+            return Ok(self.next_action(rng));
+        }
+        mask_strategy_into(strategy, mask, &mut workspace.strategy)?;
+        Ok(dist::sample_weights(&workspace.strategy, rng))
+    }
+
+    /// Like `update_regret` when only the actions where `mask` is
+    /// true were legal. Illegal actions are given the value of the
+    /// masked strategy as their reward so they pick up no regret,
+    /// and the legal actions are measured against that value too.
+    /// The masked strategy is what goes into the average, so
+    /// illegal actions never get any average weight.
+    fn update_regret_masked(
+        &mut self,
+        rewards: &[Scalar],
+        mask: &[bool],
+    ) -> Result<(), LittleError> {
+        self.update_regret_masked_with_workspace(rewards, mask, &mut Workspace::new())
+    }
+
+    /// Like `update_regret_masked` using `workspace` for temporaries.
+    fn update_regret_masked_with_workspace(
+        &mut self,
+        rewards: &[Scalar],
+        mask: &[bool],
+        workspace: &mut Workspace,
+    ) -> Result<(), LittleError> {
+        if rewards.len() != mask.len() {
+            return Err(LittleError::InvalidParameter(
+                "rewards must have one entry per expert",
+            ));
+        }
+        mask_strategy_into(self.current_strategy(), mask, &mut workspace.strategy)?;
+        let value: Scalar = workspace
+            .strategy
+            .iter()
+            .zip(rewards)
+            .map(|(p, r)| p * r)
+            .sum();
+        workspace.rewards.clear();
+        workspace.rewards.extend(
+            rewards
+                .iter()
+                .zip(mask)
+                .map(|(r, legal)| if *legal { *r } else { value }),
+        );
+        let options = UpdateOptions {
+            contribute_to_average: false,
+            ..UpdateOptions::default()
+        };
+        self.update_regret_with_options(&workspace.rewards, options)?;
+        self.accumulate_average(&workspace.strategy, 1.0)
+    }
+
+    /// Like `update_regret` but with this round's regrets scaled by
+    /// `weight`. Monte Carlo CFR uses this to apply importance
+    /// sampling corrections and reach probabilities. See
+    /// `UpdateOptions::regret_weight` for what each algorithm
+    /// scales.
+    fn update_regret_weighted(
+        &mut self,
+        rewards: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        if !(weight >= 0.0 && weight.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "weight must be finite and non-negative",
+            ));
+        }
+        let options = UpdateOptions {
+            regret_weight: weight,
+            ..UpdateOptions::default()
+        };
+        self.update_regret_with_options(rewards, options)
+    }
+
+    /// Like `update_regret` with control over how the update is
+    /// applied.
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError>;
+
+    /// Add `strategy` to the average as the one played in the
+    /// latest update, with `weight` on top of the averaging
+    /// scheme's. Pair it with an update that had
+    /// `contribute_to_average` off.
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError>;
+
+    /// The strategy that will be played next.
+    fn current_strategy(&self) -> &[Scalar];
+
+    /// The average strategy. This is what converges to equilibrium.
+    fn best_weight(&self) -> Vec<Scalar>;
+
+    /// The regret for not having always played each expert. This
+    /// is a copy because some algorithms only store it scaled or
+    /// split into parts.
+    fn cumulative_regrets(&self) -> Vec<Scalar>;
+
+    /// Use `averaging` to weight the strategies played when
+    /// building `best_weight`. Set it before the first update.
+    #[must_use]
+    fn with_averaging(self, averaging: AveragingScheme) -> Self;
+
+    /// How the strategies played are weighted in `best_weight`.
+    fn averaging(&self) -> AveragingScheme;
+
+    /// Put the minimizer back to how it was when created, keeping
+    /// its parameters and allocations.
+    fn reset(&mut self) -> Result<(), LittleError>;
+
+    /// Forget the regrets but keep the average strategy. The
+    /// next strategy played is uniform.
+    fn soft_reset(&mut self) -> Result<(), LittleError>;
+
+    /// Create a minimizer with the default parameters that starts
+    /// out playing `strategy`, as if it had already been played
+    /// once. Useful to seed a solve from a previous solution.
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = normalize_strategy(strategy)?;
+        Self::warm_start_from_regrets(&strategy)
+    }
+
+    /// Create a minimizer with the default parameters whose
+    /// cumulative regrets start out as `regrets`.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError>;
+}
+
+/// An object safe version of `RegretMinimizer` so that matchers of
+/// different types can be stored together as
+/// `Box<dyn DynRegretMinimizer>`. Every `RegretMinimizer` gets this
+/// for free. The methods end in `_dyn` so they never clash with
+/// the `RegretMinimizer` ones when both traits are in scope.
+pub trait DynRegretMinimizer: Debug {
+    fn num_experts_dyn(&self) -> usize;
+
+    fn num_updates_dyn(&self) -> usize;
+
+    /// Sample an action from the current strategy.
+    fn next_action_dyn(&self, rng: &mut dyn RngCore) -> usize;
+
+    fn update_regret_dyn(&mut self, rewards: &[Scalar]) -> Result<(), LittleError>;
+
+    fn update_regret_with_options_dyn(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError>;
+
+    fn current_strategy_dyn(&self) -> &[Scalar];
+
+    fn best_weight_dyn(&self) -> Vec<Scalar>;
+
+    fn cumulative_regrets_dyn(&self) -> Vec<Scalar>;
+
+    fn reset_dyn(&mut self) -> Result<(), LittleError>;
+
+    fn soft_reset_dyn(&mut self) -> Result<(), LittleError>;
+
+    fn to_report_dyn(&self) -> MatcherReport;
+
+    fn clone_box(&self) -> Box<dyn DynRegretMinimizer>;
+}
+
+impl<M: RegretMinimizer + Debug + 'static> DynRegretMinimizer for M {
+    fn num_experts_dyn(&self) -> usize {
+        RegretMinimizer::num_experts(self)
+    }
+
+    fn num_updates_dyn(&self) -> usize {
+        RegretMinimizer::num_updates(self)
+    }
+
+    fn next_action_dyn(&self, mut rng: &mut dyn RngCore) -> usize {
+        RegretMinimizer::next_action(self, &mut rng)
+    }
+
+    fn update_regret_dyn(&mut self, rewards: &[Scalar]) -> Result<(), LittleError> {
+        RegretMinimizer::update_regret(self, rewards)
+    }
+
+    fn update_regret_with_options_dyn(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        RegretMinimizer::update_regret_with_options(self, rewards, options)
+    }
+
+    fn current_strategy_dyn(&self) -> &[Scalar] {
+        RegretMinimizer::current_strategy(self)
+    }
+
+    fn best_weight_dyn(&self) -> Vec<Scalar> {
+        RegretMinimizer::best_weight(self)
+    }
+
+    fn cumulative_regrets_dyn(&self) -> Vec<Scalar> {
+        RegretMinimizer::cumulative_regrets(self)
+    }
+
+    fn reset_dyn(&mut self) -> Result<(), LittleError> {
+        RegretMinimizer::reset(self)
+    }
+
+    fn soft_reset_dyn(&mut self) -> Result<(), LittleError> {
+        RegretMinimizer::soft_reset(self)
+    }
+
+    fn to_report_dyn(&self) -> MatcherReport {
+        RegretMinimizer::to_report(self)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynRegretMinimizer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn DynRegretMinimizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A learner that only sees the reward of the action it played.
+pub trait BanditMinimizer {
+    fn num_experts(&self) -> usize;
+
+    /// Pick the next action to play.
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize;
+
+    /// Feed in the reward that playing `action` earned.
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError>;
+
+    /// The average strategy played so far.
+    fn best_weight(&self) -> Vec<Scalar>;
+}
+
+/// Which action `next_action_greedy` picks when several are
+/// tied for the largest weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    #[default]
+    Lowest,
+    Highest,
+    /// Uniformly at random among the tied actions.
+    Random,
+}
+
+impl TieBreak {
+    /// The index of the largest value with ties broken by `self`.
+    pub fn argmax<R: Rng>(self, values: &[Scalar], rng: &mut R) -> usize {
+        let max = values
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        let mut tied = values.iter().enumerate().filter(|(_, v)| **v == max);
+        match self {
+            Self::Lowest => tied.next().map_or(0, |(i, _)| i),
+            Self::Highest => tied.next_back().map_or(0, |(i, _)| i),
+            Self::Random => {
+                let tied: Vec<usize> = tied.map(|(i, _)| i).collect();
+                if tied.is_empty() {
+                    0
+                } else {
+                    tied[rng.gen_range(0..tied.len())]
+                }
+            }
+        }
+    }
+}
+
+/// Options for a single call to `update_regret_with_options`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UpdateOptions {
+    /// Whether the strategy played counts towards the average
+    /// strategy. With alternating updates this should be false on
+    /// the traversals where the other player is updating.
+    pub contribute_to_average: bool,
+    /// Extra weight for the strategy played in the average, on top
+    /// of the averaging scheme's. In a game tree this is the
+    /// player's own probability of reaching the decision.
+    pub average_weight: Scalar,
+    /// How much this round counts towards the regrets. Regret
+    /// matching style algorithms scale the regret they add, while
+    /// the multiplicative weights family applies the round's
+    /// multiplier `regret_weight` times over, so rewards are never
+    /// pushed out of their valid range.
+    pub regret_weight: Scalar,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        Self {
+            contribute_to_average: true,
+            average_weight: 1.0,
+            regret_weight: 1.0,
+        }
+    }
+}
+
+/// The learned state of a regret matching style minimizer in a
+/// form that doesn't depend on how any one algorithm stores it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatcherState {
+    pub num_updates: usize,
+    /// Cumulative regret of every action, with any discounting applied.
+    pub cumulative_regret: Vec<Scalar>,
+    /// The unnormalized cumulative strategy.
+    pub cumulative_strategy: Vec<Scalar>,
+}
+
+impl MatcherState {
+    /// State for a minimizer that hasn't been updated yet
+    /// but already has some regret.
+    #[must_use]
+    pub fn from_regrets(regrets: &[Scalar]) -> Self {
+        Self {
+            num_updates: 0,
+            cumulative_regret: regrets.to_vec(),
+            cumulative_strategy: vec![0.0; regrets.len()],
+        }
+    }
+
+    /// State for a regret matching minimizer that has played
+    /// `strategy` once and would keep playing it.
+    pub fn from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = normalize_strategy(strategy)?;
+        Ok(Self {
+            num_updates: 1,
+            cumulative_regret: strategy.clone(),
+            cumulative_strategy: strategy,
+        })
+    }
+}
+
+/// Minimizers whose state can be moved to a different algorithm
+/// mid solve, keeping the receiver's own parameters.
+pub trait StateTransfer: RegretMinimizer {
+    fn export_state(&self) -> MatcherState;
+
+    /// Replace all learned state with `state`.
+    fn import_state(&mut self, state: &MatcherState) -> Result<(), LittleError>;
+}
+
+/// Check that `strategy` has an entry for each of `num_experts`.
+pub fn check_strategy_len(strategy: &[Scalar], num_experts: usize) -> Result<(), LittleError> {
+    if strategy.len() != num_experts {
+        return Err(LittleError::InvalidParameter(
+            "strategy must have one entry per expert",
+        ));
+    }
+    Ok(())
+}
+
+/// Check that `rewards` has an entry for each of `num_experts`.
+pub fn check_rewards_len(rewards: &[Scalar], num_experts: usize) -> Result<(), LittleError> {
+    if rewards.len() != num_experts {
+        return Err(LittleError::InvalidParameter(
+            "rewards must have one entry per expert",
+        ));
+    }
+    Ok(())
+}
+
+/// Check that `state` fits a minimizer with `num_experts` experts.
+pub fn check_state(state: &MatcherState, num_experts: usize) -> Result<(), LittleError> {
+    if state.cumulative_regret.len() != num_experts
+        || state.cumulative_strategy.len() != num_experts
+    {
+        return Err(LittleError::InvalidParameter(
+            "state has the wrong number of experts",
+        ));
+    }
+    Ok(())
+}
+
+/// Scale `strategy` so that it sums to one. It must be non-empty,
+/// non-negative, and have a positive sum.
+pub fn normalize_strategy(strategy: &[Scalar]) -> Result<Vec<Scalar>, LittleError> {
+    if strategy.iter().any(|p| !(p.is_finite() && *p >= 0.0)) {
+        return Err(LittleError::InvalidParameter(
+            "strategy must be finite and non-negative",
+        ));
+    }
+    let total: Scalar = strategy.iter().sum();
+    if total <= 0.0 {
+        return Err(LittleError::InvalidParameter(
+            "strategy must have a positive sum",
+        ));
+    }
+    Ok(strategy.iter().map(|p| p / total).collect())
+}
+
+/// Zero every action in `strategy` with less than `threshold`
+/// weight and scale the rest to sum to one. The most likely
+/// action is always kept even if it's below the threshold.
+pub fn purify_strategy(strategy: &[Scalar], threshold: Scalar) -> Result<Vec<Scalar>, LittleError> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(LittleError::InvalidParameter(
+            "threshold must be between 0 and 1",
+        ));
+    }
+    let best = argmax(strategy);
+    let mask: Vec<bool> = strategy
+        .iter()
+        .enumerate()
+        .map(|(i, p)| i == best || *p >= threshold)
+        .collect();
+    mask_strategy(strategy, &mask)
+}
+
+/// Restrict `strategy` to the actions where `mask` is true and
+/// scale it to sum to one. If the strategy puts no weight on any
+/// legal action they are all played with equal chance.
+pub fn mask_strategy(strategy: &[Scalar], mask: &[bool]) -> Result<Vec<Scalar>, LittleError> {
+    let mut out = Vec::with_capacity(strategy.len());
+    mask_strategy_into(strategy, mask, &mut out)?;
+    Ok(out)
+}
+
+/// Like `mask_strategy` writing into `out`, which is cleared first.
+pub fn mask_strategy_into(
+    strategy: &[Scalar],
+    mask: &[bool],
+    out: &mut Vec<Scalar>,
+) -> Result<(), LittleError> {
+    if mask.len() != strategy.len() {
+        return Err(LittleError::InvalidParameter(
+            "mask must have one entry per expert",
+        ));
+    }
+    let legal = mask.iter().filter(|m| **m).count();
+    if legal == 0 {
+        return Err(LittleError::InvalidParameter(
+            "mask must allow at least one action",
+        ));
+    }
+    let total: Scalar = strategy
+        .iter()
+        .zip(mask)
+        .filter(|(_, m)| **m)
+        .map(|(p, _)| p)
+        .sum();
+    out.clear();
+    out.extend(
+        strategy
+            .iter()
+            .zip(mask)
+            .map(|(p, m)| match (*m, total > 0.0) {
+                (false, _) => 0.0,
+                (true, true) => p / total,
+                (true, false) => 1.0 / legal as Scalar,
+            }),
+    );
+    Ok(())
+}
+
+/// Log of a strategy for the exponential weights family. Zero
+/// probabilities are clamped so they stay finite and can recover.
+#[must_use]
+pub fn log_strategy(strategy: &[Scalar]) -> Vec<Scalar> {
+    strategy
+        .iter()
+        .map(|p| Scalar::max(*p, Scalar::MIN_POSITIVE).ln())
+        .collect()
+}
+
+/// Check that a sampled action is one of the experts.
+pub fn check_action(action: usize, num_experts: usize) -> Result<(), LittleError> {
+    if action >= num_experts {
+        return Err(LittleError::ActionOutOfRange {
+            action,
+            num_experts,
+        });
+    }
+    Ok(())
+}
+
+/// Every action equally likely.
+#[must_use]
+pub fn uniform(num_experts: usize) -> Vec<Scalar> {
+    vec![1.0 / num_experts as Scalar; num_experts]
+}
+
+/// Fill `out` with the uniform strategy.
+pub fn fill_uniform(out: &mut [Scalar]) {
+    let n = out.len();
+    out.fill(1.0 / n as Scalar);
+}
+
+/// Regret matching. Each action is played in proportion to
+/// its positive regret. If nothing has positive regret then
+/// play uniformly.
+pub fn regret_match(regrets: &[Scalar], out: &mut [Scalar]) {
+    kernels::regret_match(regrets, out);
+}
+
+/// Softmax of `logits` written into `out`. The max is subtracted
+/// first so that large cumulative rewards don't overflow.
+pub fn softmax(logits: &[Scalar], out: &mut [Scalar]) {
+    out.copy_from_slice(logits);
+    softmax_in_place(out);
+}
+
+/// Replace `values` with their softmax. Lets the logits be built
+/// in the strategy's own buffer so that no temporary is needed.
+pub fn softmax_in_place(values: &mut [Scalar]) {
+    let max = values
+        .iter()
+        .fold(Scalar::NEG_INFINITY, |acc, v| Scalar::max(acc, *v));
+    let mut sum = 0.0;
+    for v in values.iter_mut() {
+        *v = (*v - max).exp();
+        sum += *v;
+    }
+    values.iter_mut().for_each(|v| *v /= sum);
+}
+
+/// The index of the largest value. Ties go to the lowest index.
+#[must_use]
+pub fn argmax(values: &[Scalar]) -> usize {
+    let mut best = 0;
+    for (i, v) in values.iter().enumerate() {
+        if *v > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Build the distribution used to sample actions from a strategy.
+pub fn build_dist(p: &[Scalar]) -> Result<StrategyDist, LittleError> {
+    StrategyDist::new(p.to_vec())
+}
+
+// Only worked out when someone is listening since copying the
+// regrets isn't free.
+#[cfg(feature = "tracing")]
+fn trace_regrets<M: RegretMinimizer>(m: &M) {
+    if tracing::enabled!(tracing::Level::TRACE) {
+        let regrets = m.cumulative_regrets();
+        let max_regret = regrets
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        tracing::trace!(
+            algorithm = %report::algorithm_name::<M>(),
+            num_updates = m.num_updates(),
+            regret_sum = regrets.iter().sum::<Scalar>(),
+            max_regret,
+            "updated regrets"
+        );
+    }
+}
+
+/// Sample an action from a prebuilt distribution.
+pub fn sample<R: Rng>(dist: &StrategyDist, rng: &mut R) -> usize {
+    dist.sample(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_regret_batch() {
+        use crate::dcfr::DiscountedRegretMatcher;
+        let rounds = [[1.0, 0.0, 0.5], [0.0, 2.0, 0.0], [0.5, 0.5, 1.0]];
+        let mut batched = DiscountedRegretMatcher::new(3).unwrap();
+        let mut one_by_one = batched.clone();
+        batched.update_regret_batch(&rounds).unwrap();
+        for r in &rounds {
+            one_by_one.update_regret(r).unwrap();
+        }
+        assert_eq!(batched.export_state(), one_by_one.export_state());
+        batched.update_regret_batch(&[vec![1.0; 3]]).unwrap();
+        assert!(batched.update_regret_batch(&[vec![0.0; 2]]).is_err());
+    }
+
+    #[test]
+    fn test_regret_match() {
+        let mut out = [0.0; 3];
+        regret_match(&[1.0, -1.0, 3.0], &mut out);
+        assert_eq!(out, [0.25, 0.0, 0.75]);
+    }
+
+    #[test]
+    fn test_regret_match_all_negative() {
+        let mut out = [0.0; 4];
+        regret_match(&[-1.0, -1.0, 0.0, -3.0], &mut out);
+        assert_eq!(out, [0.25; 4]);
+    }
+
+    #[test]
+    fn test_normalize_strategy() {
+        assert_eq!(normalize_strategy(&[1.0, 3.0]).unwrap(), vec![0.25, 0.75]);
+        assert!(normalize_strategy(&[0.0, 0.0]).is_err());
+        assert!(normalize_strategy(&[-1.0, 2.0]).is_err());
+        assert!(normalize_strategy(&[]).is_err());
+    }
+
+    #[test]
+    fn test_mask_strategy() {
+        let masked = mask_strategy(&[0.5, 0.25, 0.25], &[false, true, true]).unwrap();
+        assert_eq!(masked, vec![0.0, 0.5, 0.5]);
+        let masked = mask_strategy(&[1.0, 0.0, 0.0], &[false, true, true]).unwrap();
+        assert_eq!(masked, vec![0.0, 0.5, 0.5]);
+        assert!(mask_strategy(&[1.0, 0.0], &[false, false]).is_err());
+    }
+
+    #[test]
+    fn test_purify_strategy() {
+        let purified = purify_strategy(&[0.5, 0.375, 0.125], 0.2).unwrap();
+        assert_eq!(purified, vec![0.5 / 0.875, 0.375 / 0.875, 0.0]);
+        assert_eq!(purify_strategy(&[0.5, 0.5], 0.9).unwrap(), vec![1.0, 0.0]);
+        assert!(purify_strategy(&[0.5, 0.5], 1.5).is_err());
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let mut rng = rand::thread_rng();
+        let values = [0.25, 0.375, 0.0, 0.375];
+        assert_eq!(TieBreak::Lowest.argmax(&values, &mut rng), 1);
+        assert_eq!(TieBreak::Highest.argmax(&values, &mut rng), 3);
+        for _i in 0..20 {
+            let a = TieBreak::Random.argmax(&values, &mut rng);
+            assert!(a == 1 || a == 3);
+        }
+    }
+
+    #[test]
+    fn test_dyn_minimizers() {
+        let mut minimizers: Vec<Box<dyn DynRegretMinimizer>> = vec![
+            Box::new(crate::vanilla_cfr::VanillaCfrRegretMatcher::new(2).unwrap()),
+            Box::new(crate::dcfr::DiscountedRegretMatcher::new(2).unwrap()),
+        ];
+        let mut rng = rand::thread_rng();
+        for m in &mut minimizers {
+            m.update_regret_dyn(&[1.0, 0.0]).unwrap();
+            assert_eq!(m.next_action_dyn(&mut rng), 0);
+        }
+        let copies = minimizers.clone();
+        assert_eq!(copies[1].num_updates_dyn(), 1);
+    }
+
+    fn assert_rejects_wrong_length<M: RegretMinimizer>() {
+        let mut m = M::new(3).unwrap();
+        for rewards in [&[1.0, 0.0][..], &[1.0, 0.0, 0.0, 0.0][..]] {
+            assert!(matches!(
+                m.update_regret(rewards),
+                Err(LittleError::InvalidParameter(_))
+            ));
+        }
+        assert_eq!(m.num_updates(), 0);
+    }
+
+    #[test]
+    fn test_rewards_len() {
+        use crate::blackwell::{BlackwellMatcher, NegativeOrthant};
+        use crate::dcfr::DiscountedRegretMatcher;
+        use crate::drift::DriftRestart;
+        use crate::history::HistoryWrapper;
+        use crate::lazy_dcfr::LazyDcfrRegretMatcher;
+        use crate::meta::MetaRegretMatcher;
+        use crate::mwu::MwuRegretMatcher;
+        use crate::omd::OmdRegretMatcher;
+        use crate::optimistic_hedge::OptimisticHedgeRegretMatcher;
+        use crate::refresh::RefreshWrapper;
+        use crate::regret_matcher::RegretMatcher;
+        use crate::scheduled::ScheduledRegretMatcher;
+        use crate::smooth::SmoothRegretMatcher;
+        use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+        use crate::windowed::WindowedRegretMatcher;
+
+        assert_rejects_wrong_length::<BlackwellMatcher<NegativeOrthant>>();
+        assert_rejects_wrong_length::<DiscountedRegretMatcher>();
+        assert_rejects_wrong_length::<DriftRestart<RegretMatcher>>();
+        assert_rejects_wrong_length::<HistoryWrapper<RegretMatcher>>();
+        assert_rejects_wrong_length::<LazyDcfrRegretMatcher>();
+        assert_rejects_wrong_length::<MetaRegretMatcher>();
+        assert_rejects_wrong_length::<MwuRegretMatcher>();
+        assert_rejects_wrong_length::<OmdRegretMatcher>();
+        assert_rejects_wrong_length::<OptimisticHedgeRegretMatcher>();
+        assert_rejects_wrong_length::<RefreshWrapper<RegretMatcher>>();
+        assert_rejects_wrong_length::<RegretMatcher>();
+        assert_rejects_wrong_length::<
+            ScheduledRegretMatcher<VanillaCfrRegretMatcher, DiscountedRegretMatcher>,
+        >();
+        assert_rejects_wrong_length::<SmoothRegretMatcher>();
+        assert_rejects_wrong_length::<VanillaCfrRegretMatcher>();
+        assert_rejects_wrong_length::<WindowedRegretMatcher>();
+        #[cfg(feature = "half")]
+        assert_rejects_wrong_length::<crate::half_precision::HalfRegretMatcher>();
+        #[cfg(feature = "profiling")]
+        assert_rejects_wrong_length::<crate::profiling::Profiled<RegretMatcher>>();
+    }
+
+    fn assert_close(a: &[Scalar], b: &[Scalar]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-4, "{a:?} != {b:?}");
+        }
+    }
+
+    // Weighting a round by 3 is the same as tripling its rewards for
+    // algorithms whose update is linear in the rewards.
+    fn assert_weight_scales_rewards<M: RegretMinimizer>() {
+        let mut weighted = M::new(3).unwrap();
+        let mut scaled = M::new(3).unwrap();
+        for rewards in [[1.0, 0.0, 0.5], [0.0, 0.25, -0.5]] {
+            weighted.update_regret_weighted(&rewards, 3.0).unwrap();
+            scaled.update_regret(&rewards.map(|r| r * 3.0)).unwrap();
+        }
+        assert_close(&weighted.cumulative_regrets(), &scaled.cumulative_regrets());
+        assert_close(weighted.current_strategy(), scaled.current_strategy());
+        let mut ignored = M::new(3).unwrap();
+        ignored
+            .update_regret_weighted(&[1.0, 0.0, 0.5], 0.0)
+            .unwrap();
+        assert_close(&ignored.cumulative_regrets(), &[0.0; 3]);
+        assert!(ignored
+            .update_regret_weighted(&[1.0, 0.0, 0.5], -1.0)
+            .is_err());
+        assert!(ignored
+            .update_regret_weighted(&[1.0, 0.0, 0.5], Scalar::NAN)
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_regret_weighted() {
+        use crate::blackwell::{BlackwellMatcher, NegativeOrthant};
+        use crate::dcfr::DiscountedRegretMatcher;
+        use crate::lazy_dcfr::LazyDcfrRegretMatcher;
+        use crate::meta::MetaRegretMatcher;
+        use crate::omd::OmdRegretMatcher;
+        use crate::optimistic_hedge::OptimisticHedgeRegretMatcher;
+        use crate::regret_matcher::RegretMatcher;
+        use crate::smooth::SmoothRegretMatcher;
+        use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+        use crate::windowed::WindowedRegretMatcher;
+
+        assert_weight_scales_rewards::<BlackwellMatcher<NegativeOrthant>>();
+        assert_weight_scales_rewards::<DiscountedRegretMatcher>();
+        assert_weight_scales_rewards::<LazyDcfrRegretMatcher>();
+        assert_weight_scales_rewards::<MetaRegretMatcher>();
+        assert_weight_scales_rewards::<OmdRegretMatcher>();
+        assert_weight_scales_rewards::<OptimisticHedgeRegretMatcher>();
+        assert_weight_scales_rewards::<RegretMatcher>();
+        assert_weight_scales_rewards::<SmoothRegretMatcher>();
+        assert_weight_scales_rewards::<VanillaCfrRegretMatcher>();
+        assert_weight_scales_rewards::<WindowedRegretMatcher>();
+        #[cfg(feature = "half")]
+        assert_weight_scales_rewards::<crate::half_precision::HalfRegretMatcher>();
+    }
+
+    #[test]
+    fn test_update_regret_weighted_mwu() {
+        use crate::mwu::MwuRegretMatcher;
+        // A weight of 2 applies the multiplier twice rather than
+        // doubling a reward that would then be out of range.
+        let rewards = [-6.0, 0.0, 1.0];
+        let mut weighted = MwuRegretMatcher::new(3).unwrap();
+        weighted.update_regret_weighted(&rewards, 2.0).unwrap();
+        let mut twice = MwuRegretMatcher::new(3).unwrap();
+        assert!(twice.update_regret(&rewards.map(|r| r * 2.0)).is_err());
+        twice.update_regret(&rewards).unwrap();
+        twice.update_regret(&rewards).unwrap();
+        assert_close(weighted.current_strategy(), twice.current_strategy());
+        let mut ignored = MwuRegretMatcher::new(3).unwrap();
+        ignored.update_regret_weighted(&rewards, 0.0).unwrap();
+        assert_close(ignored.current_strategy(), &uniform(3));
+        assert_close(&ignored.cumulative_regrets(), &[0.0; 3]);
+    }
+
+    #[test]
+    fn test_softmax_large() {
+        let mut out = [0.0; 2];
+        softmax(&[10_000.0, 10_000.0], &mut out);
+        assert_eq!(out, [0.5, 0.5]);
+    }
+}
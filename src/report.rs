@@ -0,0 +1,70 @@
+//! A snapshot of a matcher in a plain form that is easy to
+//! serialize, for dashboards and notebooks that want to track a
+//! solve without depending on any one algorithm's internals.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatcherReport {
+    /// The matcher's type name without module paths, for example
+    /// `DriftRestart<VanillaCfrRegretMatcher>`.
+    pub algorithm: String,
+    pub params: BTreeMap<String, Scalar>,
+    pub num_updates: usize,
+    pub current_strategy: Vec<Scalar>,
+    pub average_strategy: Vec<Scalar>,
+    pub cumulative_regrets: Vec<Scalar>,
+}
+
+/// The name of `T` with the module path stripped from it and from
+/// any of its type parameters.
+#[must_use]
+pub fn algorithm_name<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut name = String::with_capacity(full.len());
+    let mut segment = String::new();
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            name.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            name.push(c);
+        }
+    }
+    name.push_str(segment.rsplit("::").next().unwrap_or_default());
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::drift::DriftRestart;
+    use crate::regret_minimizer::RegretMinimizer;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_report() {
+        let mut m = DriftRestart::<VanillaCfrRegretMatcher>::new(2).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        let report = m.to_report();
+        assert_eq!(report.algorithm, "DriftRestart<VanillaCfrRegretMatcher>");
+        assert_eq!(report.num_updates, 1);
+        assert_eq!(report.current_strategy, vec![1.0, 0.0]);
+        assert_eq!(report.cumulative_regrets, vec![0.5, -0.5]);
+        let dcfr = DiscountedRegretMatcher::recommended(2).unwrap().to_report();
+        assert_eq!(dcfr.params["alpha"], 1.5);
+        assert_eq!(
+            algorithm_name::<DiscountedRegretMatcher>(),
+            "DiscountedRegretMatcher"
+        );
+    }
+}
@@ -1,56 +1,25 @@
+//! Rock paper scissors self play. An `RPSRunner` is a
+//! `NormalFormRunner` with constructors for rock paper scissors and
+//! its variants, and derefs to it for everything else.
 use crate::errors::LittleError;
+use crate::games::{NormalFormGame, NormalFormRunner};
 use crate::regret_matcher::RegretMatcher;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
 use ndarray::prelude::*;
-use once_cell::sync::Lazy;
-use std::cmp;
-use std::mem;
-
-use std::vec::Vec;
-
-#[allow(dead_code)]
-#[repr(usize)]
-#[derive(Debug, Clone, Copy)]
-enum RPSAction {
-    Rock = 0,
-    Paper = 1,
-    Scissors = 2,
-}
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-static ROCK_REWARD: Lazy<Array1<f32>> = Lazy::new(|| array![0.0_f32, 1.0_f32, -1.0_f32]);
-static PAPER_REWARD: Lazy<Array1<f32>> = Lazy::new(|| array![-1.0_f32, 0.0_f32, 1.0_f32]);
-static SCISSOR_REWARD: Lazy<Array1<f32>> = Lazy::new(|| array![1.0_f32, -1.0_f32, 0.0_f32]);
-
-impl RPSAction {
-    pub fn to_reward(self) -> ArrayView1<'static, f32> {
-        match self {
-            Self::Rock => ROCK_REWARD.view(),
-            Self::Paper => PAPER_REWARD.view(),
-            Self::Scissors => SCISSOR_REWARD.view(),
-        }
-    }
-}
-
-impl From<usize> for RPSAction {
-    fn from(i: usize) -> Self {
-        unsafe {
-            mem::transmute(cmp::max(
-                cmp::min(i, Self::Scissors as usize),
-                Self::Rock as usize,
-            ))
-        }
-    }
-}
+use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Clone)]
-pub struct RPSRunner {
-    pub matcher_one: RegretMatcher,
-    pub matcher_two: RegretMatcher,
-    pending_reward_one: Array1<f32>,
-    pending_reward_two: Array1<f32>,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct RPSRunner<M1: RegretMinimizer = RegretMatcher, M2: RegretMinimizer = M1> {
+    runner: NormalFormRunner<M1, M2>,
 }
 
 impl Default for RPSRunner {
-    #[must_use]
     fn default() -> Self {
         Self::new().unwrap()
     }
@@ -58,32 +27,230 @@ impl Default for RPSRunner {
 
 impl RPSRunner {
     pub fn new() -> Result<Self, LittleError> {
+        Self::new_with_matchers(RegretMatcher::new(3)?, RegretMatcher::new(3)?)
+    }
+    /// A runner where player two is a best responder rather
+    /// than a regret matcher.
+    pub fn with_best_response() -> Result<Self, LittleError> {
+        let mut runner = Self::new()?;
+        runner.set_best_response_opponent();
+        Ok(runner)
+    }
+    /// Self play of `NormalFormGame::cyclic(n)`, rock paper scissors
+    /// with `n` actions.
+    pub fn cyclic(n: usize) -> Result<Self, LittleError> {
+        Self::new_with_game(
+            NormalFormGame::cyclic(n)?,
+            RegretMatcher::new(n)?,
+            RegretMatcher::new(n)?,
+        )
+    }
+    /// Self play of the zero sum game where player one wins
+    /// `payoff[[a, b]]` playing `a` against `b`, e.g. rock paper
+    /// scissors where winning with rock pays 2.
+    pub fn with_payoff(payoff: Array2<Scalar>) -> Result<Self, LittleError> {
+        let game = NormalFormGame::zero_sum(payoff)?;
+        let one = RegretMatcher::new(game.num_actions_one())?;
+        let two = RegretMatcher::new(game.num_actions_two())?;
+        Self::new_with_game(game, one, two)
+    }
+    /// A runner where player two always plays `strategy`, so
+    /// player one learns to exploit it.
+    pub fn with_fixed_opponent(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let mut runner = Self::new()?;
+        runner.set_fixed_opponent(strategy)?;
+        Ok(runner)
+    }
+}
+
+impl<M1: RegretMinimizer, M2: RegretMinimizer> RPSRunner<M1, M2> {
+    /// A runner pitting two possibly different algorithms
+    /// against each other.
+    pub fn new_with_matchers(matcher_one: M1, matcher_two: M2) -> Result<Self, LittleError> {
+        Self::new_with_game(
+            NormalFormGame::rock_paper_scissors(),
+            matcher_one,
+            matcher_two,
+        )
+    }
+    /// Play some other rock paper scissors like `game`, such as
+    /// `NormalFormGame::cyclic`.
+    pub fn new_with_game(
+        game: NormalFormGame,
+        matcher_one: M1,
+        matcher_two: M2,
+    ) -> Result<Self, LittleError> {
         Ok(Self {
-            matcher_one: RegretMatcher::new(3)?,
-            matcher_two: RegretMatcher::new(3)?,
-            pending_reward_one: Array1::zeros(3),
-            pending_reward_two: Array1::zeros(3),
+            runner: NormalFormRunner::new_with_matchers(game, matcher_one, matcher_two)?,
         })
     }
-    pub fn run_one(&mut self) {
-        let a1 = RPSAction::from(self.matcher_one.next_action());
-        let a2 = RPSAction::from(self.matcher_two.next_action());
+    /// See `NormalFormRunner::with_full_feedback`.
+    pub fn with_full_feedback(self) -> Self {
+        Self {
+            runner: self.runner.with_full_feedback(),
+        }
+    }
+    /// The underlying normal form runner.
+    #[must_use]
+    pub fn into_inner(self) -> NormalFormRunner<M1, M2> {
+        self.runner
+    }
+}
+
+impl<M1: RegretMinimizer, M2: RegretMinimizer> Deref for RPSRunner<M1, M2> {
+    type Target = NormalFormRunner<M1, M2>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.runner
+    }
+}
 
-        self.pending_reward_one += &a2.to_reward();
-        self.pending_reward_two += &a1.to_reward();
+impl<M1: RegretMinimizer, M2: RegretMinimizer> DerefMut for RPSRunner<M1, M2> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.runner
     }
-    pub fn update_regret(&mut self) -> Result<(), LittleError> {
-        self.matcher_one
-            .update_regret(self.pending_reward_one.view())?;
-        self.matcher_two
-            .update_regret(self.pending_reward_two.view())?;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convergence::ConvergenceCriterion;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
-        self.pending_reward_one.fill(0.0);
-        self.pending_reward_two.fill(0.0);
-        Ok(())
+    #[test]
+    fn test_seeded_runs_match() {
+        let run = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut runner = RPSRunner::new().unwrap();
+            for _i in 0..100 {
+                runner.run_one_with_rng(&mut rng);
+                runner.update_regret().unwrap();
+            }
+            runner.best_weight()
+        };
+        assert_eq!(run(7), run(7));
     }
-    #[must_use]
-    pub fn best_weight(&self) -> Vec<f32> {
-        self.matcher_one.best_weight()
+
+    #[test]
+    fn test_exploitability_series() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = RPSRunner::new().unwrap();
+        runner.track_exploitability(100).unwrap();
+        for _i in 0..1_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        let series = runner.exploitability_series();
+        assert_eq!(series.len(), 10);
+        assert_eq!(series[9], (1_000, runner.exploitability()));
+        assert!(series[9].1 < series[0].1);
+    }
+
+    #[test]
+    fn test_run_until() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = RPSRunner::new().unwrap();
+        let report = runner
+            .run_until_with_rng(
+                &mut rng,
+                ConvergenceCriterion::Exploitability(0.05),
+                100,
+                1_000_000,
+            )
+            .unwrap();
+        assert!(report.converged);
+        assert!(report.exploitability <= 0.05);
+        assert!(report.iterations.is_multiple_of(100));
+        assert_eq!(runner.matcher_one.num_updates(), report.iterations);
+    }
+
+    #[test]
+    fn test_full_feedback() {
+        let runner = || {
+            let mut one = DiscountedRegretMatcher::new(3).unwrap();
+            // Start away from the uniform equilibrium.
+            one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+            RPSRunner::new_with_matchers(one, DiscountedRegretMatcher::new(3).unwrap()).unwrap()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sampled = runner();
+        let mut full = runner().with_full_feedback();
+        for _i in 0..1_000 {
+            sampled.run_one_with_rng(&mut rng);
+            sampled.update_regret().unwrap();
+            full.run_one_with_rng(&mut rng);
+            full.update_regret().unwrap();
+        }
+        assert!(full.exploitability() < 0.5 * sampled.exploitability());
+    }
+
+    #[test]
+    fn test_fixed_opponent() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // Leans towards rock, so paper wins.
+        let mut runner = RPSRunner::with_fixed_opponent(&[0.5, 0.25, 0.25]).unwrap();
+        for _i in 0..1_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert!(runner.best_weight()[1] > 0.9);
+        assert_eq!(runner.matcher_two.num_updates(), 0);
+        assert!(RPSRunner::with_fixed_opponent(&[1.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn test_cyclic() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = RPSRunner::cyclic(5).unwrap();
+        for _i in 0..10_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert!(runner.best_weight().iter().all(|p| (p - 0.2).abs() < 0.05));
+        assert!(runner.exploitability() < 0.1);
+        assert!(RPSRunner::cyclic(4).is_err());
+    }
+
+    #[test]
+    fn test_biased_payoff() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // Winning with rock pays 2.
+        let payoff = array![[0.0, -1.0, 2.0], [1.0, 0.0, -1.0], [-2.0, 1.0, 0.0]];
+        let mut runner = RPSRunner::with_payoff(payoff.clone()).unwrap();
+        for _i in 0..10_000 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        let (nash, _) = NormalFormGame::zero_sum(payoff)
+            .unwrap()
+            .fully_mixed_equilibrium()
+            .unwrap();
+        for (p, q) in runner.best_weight().iter().zip(&nash) {
+            assert!((p - q).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_mixed_algorithms() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut runner = RPSRunner::new_with_matchers(
+            DiscountedRegretMatcher::new(3).unwrap(),
+            VanillaCfrRegretMatcher::new(3).unwrap(),
+        )
+        .unwrap();
+        for _i in 0..100 {
+            runner.run_one_with_rng(&mut rng);
+            runner.update_regret().unwrap();
+        }
+        assert_eq!(runner.matcher_one.num_updates(), 100);
+        assert_eq!(runner.matcher_two.num_updates(), 100);
+        assert!(RPSRunner::new_with_matchers(
+            RegretMatcher::new(2).unwrap(),
+            RegretMatcher::new(3).unwrap()
+        )
+        .is_err());
     }
 }
@@ -0,0 +1,37 @@
+//! The floating point type used for rewards, regrets and
+//! strategies. This is `f32` unless the `f64` feature is enabled,
+//! which is worth it for very long solves where the cumulative
+//! sums get large enough for `f32` rounding to bias the average.
+use ndarray::{Array1, ArrayView1};
+
+#[cfg(not(feature = "f64"))]
+pub type Scalar = f32;
+#[cfg(feature = "f64")]
+pub type Scalar = f64;
+
+/// Mathematical constants for `Scalar`.
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
+
+/// The type used for long running sums such as cumulative regrets
+/// and the cumulative strategy. The `f64-accumulators` feature
+/// makes these f64 while strategies stay `Scalar`, which avoids
+/// cancellation when subtracting two huge sums late in a solve.
+#[cfg(not(any(feature = "f64", feature = "f64-accumulators")))]
+pub type Accumulator = f32;
+#[cfg(any(feature = "f64", feature = "f64-accumulators"))]
+pub type Accumulator = f64;
+
+/// Copy `values` into an array of accumulators.
+#[must_use]
+pub fn widen(values: &[Scalar]) -> Array1<Accumulator> {
+    values.iter().map(|v| *v as Accumulator).collect()
+}
+
+/// Copy accumulated `values` back into scalars.
+#[must_use]
+pub fn narrow(values: ArrayView1<Accumulator>) -> Vec<Scalar> {
+    values.iter().map(|v| *v as Scalar).collect()
+}
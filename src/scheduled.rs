@@ -0,0 +1,228 @@
+//! Run one algorithm for a while and then switch to another.
+//! The DCFR paper suggests, for example, warming up with Linear
+//! CFR before moving to a more aggressive discounting scheme.
+//! When the switch happens the learned regrets and cumulative
+//! strategy are moved to the second algorithm, so nothing learned
+//! during the warmup is thrown away.
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{RegretMinimizer, StateTransfer, UpdateOptions};
+use crate::scalar::Scalar;
+
+const DEFAULT_SWITCH_AT: usize = 1_000;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScheduledRegretMatcher<A: StateTransfer, B: StateTransfer> {
+    first: A,
+    second: B,
+    // Number of updates to give to `first` before switching.
+    switch_at: usize,
+    switched: bool,
+}
+
+impl<A: StateTransfer, B: StateTransfer> ScheduledRegretMatcher<A, B> {
+    /// Play `first` for `switch_at` updates then continue with
+    /// `second`. Both should have the same number of experts.
+    pub fn new_with_params(first: A, second: B, switch_at: usize) -> Result<Self, LittleError> {
+        if first.num_experts() != second.num_experts() {
+            return Err(LittleError::InvalidParameter(
+                "scheduled matchers must have the same number of experts",
+            ));
+        }
+        let mut scheduled = Self {
+            first,
+            second,
+            switch_at,
+            switched: false,
+        };
+        // Allow switching right away.
+        scheduled.maybe_switch(0)?;
+        Ok(scheduled)
+    }
+
+    fn maybe_switch(&mut self, updates: usize) -> Result<(), LittleError> {
+        if !self.switched && updates >= self.switch_at {
+            self.second.import_state(&self.first.export_state())?;
+            self.switched = true;
+        }
+        Ok(())
+    }
+
+    /// True once the second algorithm has taken over.
+    #[must_use]
+    pub fn switched(&self) -> bool {
+        self.switched
+    }
+
+    #[must_use]
+    pub fn switch_at(&self) -> usize {
+        self.switch_at
+    }
+}
+
+impl<A: StateTransfer, B: StateTransfer> RegretMinimizer for ScheduledRegretMatcher<A, B> {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(
+            A::new(num_experts)?,
+            B::new(num_experts)?,
+            DEFAULT_SWITCH_AT,
+        )
+    }
+
+    fn num_experts(&self) -> usize {
+        self.first.num_experts()
+    }
+
+    fn num_updates(&self) -> usize {
+        if self.switched {
+            self.second.num_updates()
+        } else {
+            self.first.num_updates()
+        }
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        let mut params = if self.switched {
+            self.second.hyperparameters()
+        } else {
+            self.first.hyperparameters()
+        };
+        params.push(("switch_at", self.switch_at as Scalar));
+        params
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        if self.switched {
+            self.second.next_action(rng)
+        } else {
+            self.first.next_action(rng)
+        }
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        if self.switched {
+            return self.second.update_regret_with_options(rewards, options);
+        }
+        self.first.update_regret_with_options(rewards, options)?;
+        let updates = self.first.num_updates();
+        self.maybe_switch(updates)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        if self.switched {
+            self.second.accumulate_average(strategy, weight)
+        } else {
+            self.first.accumulate_average(strategy, weight)
+        }
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        if self.switched {
+            self.second.current_strategy()
+        } else {
+            self.first.current_strategy()
+        }
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        if self.switched {
+            self.second.best_weight()
+        } else {
+            self.first.best_weight()
+        }
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        if self.switched {
+            self.second.cumulative_regrets()
+        } else {
+            self.first.cumulative_regrets()
+        }
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.first = self.first.with_averaging(averaging);
+        self.second = self.second.with_averaging(averaging);
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        if self.switched {
+            self.second.averaging()
+        } else {
+            self.first.averaging()
+        }
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.first.reset()?;
+        self.second.reset()?;
+        self.switched = false;
+        self.maybe_switch(0)
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        if self.switched {
+            self.second.soft_reset()
+        } else {
+            self.first.soft_reset()
+        }
+    }
+
+    // Only the first algorithm is warm started. The second
+    // inherits the warm start when the state is moved over.
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(
+            A::warm_start_from_strategy(strategy)?,
+            B::new(strategy.len())?,
+            DEFAULT_SWITCH_AT,
+        )
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        Self::new_with_params(
+            A::warm_start_from_regrets(regrets)?,
+            B::new(regrets.len())?,
+            DEFAULT_SWITCH_AT,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discount::DiscountParams;
+    use crate::lazy_dcfr::LazyDcfrRegretMatcher;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_state_carries_over() {
+        let first = LazyDcfrRegretMatcher::new_with_params(2, DiscountParams::lcfr()).unwrap();
+        let second = VanillaCfrRegretMatcher::new(2).unwrap();
+        let mut m = ScheduledRegretMatcher::new_with_params(first, second, 10).unwrap();
+        for _i in 0..10 {
+            m.update_regret(&[1.0, 0.0]).unwrap();
+        }
+        assert!(m.switched());
+        // The second matcher has never seen a reward itself but
+        // already knows the first action is better.
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+        assert!(m.best_weight()[0] > 0.5);
+    }
+}
@@ -0,0 +1,15 @@
+//! Inline storage for the per action arrays of a matcher. A tree
+//! solver keeps one matcher per information state and most
+//! decisions only have a handful of actions, so the strategy,
+//! average and regrets are stored inline for up to
+//! `INLINE_EXPERTS` actions. That saves a heap allocation per
+//! array and keeps each matcher in one place in memory. Larger
+//! decisions still work, they just spill to the heap.
+use smallvec::SmallVec;
+
+/// The most actions that are stored without a heap allocation.
+pub const INLINE_EXPERTS: usize = 8;
+
+/// A per action array that's inline for up to `INLINE_EXPERTS`
+/// actions.
+pub type Inline<T> = SmallVec<[T; INLINE_EXPERTS]>;
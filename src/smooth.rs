@@ -0,0 +1,240 @@
+#![allow(clippy::cast_precision_loss)]
+//! Smoothed regret matching. The regret matched strategy is
+//! mixed with a softmax over the cumulative regrets. The softmax
+//! part is strictly positive and smooth in the regrets, so the
+//! resulting strategy is fully mixed and differentiable, which is
+//! what gradient based opponent models want as input.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+pub(crate) const DEFAULT_TEMPERATURE: Scalar = 1.0;
+pub(crate) const DEFAULT_MIX: Scalar = 0.1;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmoothRegretMatcher {
+    // Softmax temperature. Lower is closer to argmax.
+    temperature: Scalar,
+    // Fraction of the strategy that comes from the softmax.
+    mix: Scalar,
+    cumulative_regret: CompensatedArray<Scalar>,
+    // Scratch space for the softmax half of the strategy.
+    smooth: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl SmoothRegretMatcher {
+    /// Create a matcher with softmax `temperature > 0` and a
+    /// `mix` in `[0, 1]`. A mix of zero is plain regret matching
+    /// and a mix of one is a pure softmax over regrets.
+    pub fn new_with_params(
+        num_experts: usize,
+        temperature: Scalar,
+        mix: Scalar,
+    ) -> Result<Self, LittleError> {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(LittleError::InvalidParameter(
+                "temperature must be positive",
+            ));
+        }
+        if !(0.0..=1.0).contains(&mix) {
+            return Err(LittleError::InvalidParameter("mix must be in [0, 1]"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            temperature,
+            mix,
+            cumulative_regret: CompensatedArray::zeros(num_experts),
+            smooth: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        let expected = self.p.dot(&reward_array);
+        for (i, r) in reward_array.iter().enumerate() {
+            self.cumulative_regret
+                .add(i, options.regret_weight * (r - expected));
+        }
+        self.play_regrets()
+    }
+
+    // Set the strategy from the cumulative regrets.
+    fn play_regrets(&mut self) -> Result<(), LittleError> {
+        regret_minimizer::regret_match(
+            self.cumulative_regret.as_slice(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        let logits = &self.cumulative_regret.values() / self.temperature;
+        regret_minimizer::softmax(
+            logits.as_slice().unwrap(),
+            self.smooth.as_slice_mut().unwrap(),
+        );
+        self.p *= 1.0 - self.mix;
+        self.p.scaled_add(self.mix, &self.smooth);
+
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn temperature(&self) -> Scalar {
+        self.temperature
+    }
+
+    #[must_use]
+    pub fn mix(&self) -> Scalar {
+        self.mix
+    }
+}
+
+impl RegretMinimizer for SmoothRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DEFAULT_TEMPERATURE, DEFAULT_MIX)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![("temperature", self.temperature), ("mix", self.mix)]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.values().to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::warm_start_from_regrets(&strategy)?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.cumulative_regret = CompensatedArray::from(Array1::from(regrets.to_vec()));
+        matcher.play_regrets()?;
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fully_mixed() {
+        let mut m = SmoothRegretMatcher::new(3).unwrap();
+        for _i in 0..10 {
+            m.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        }
+        // Plain regret matching would put zero on the last two.
+        assert!(m.current_strategy().iter().all(|p| *p > 0.0));
+        let total: Scalar = m.current_strategy().iter().sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_zero_mix_is_regret_matching() {
+        let mut m = SmoothRegretMatcher::new_with_params(2, 1.0, 0.0).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+    }
+}
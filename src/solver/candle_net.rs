@@ -0,0 +1,127 @@
+//! A reference `StrategyNet` and `ValueNet` built on candle: a
+//! plain multilayer perceptron with ReLU between layers. The layers
+//! are ordinary candle tensors, so they can be trained with any
+//! candle optimizer and saved or loaded with safetensors.
+use candle_core::{Device, Tensor};
+
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::Action;
+use crate::scalar::Scalar;
+use crate::solver::neural::{StrategyNet, ValueNet};
+
+#[derive(Debug, Clone)]
+pub struct CandleMlp {
+    // A weight of shape `(inputs, outputs)` and a bias of
+    // `(outputs,)` for each layer.
+    layers: Vec<(Tensor, Tensor)>,
+}
+
+impl CandleMlp {
+    /// A network with randomly initialized layers where `sizes`
+    /// gives the width of the input, each hidden layer and the
+    /// output.
+    pub fn new(sizes: &[usize], device: &Device) -> Result<Self, LittleError> {
+        if sizes.len() < 2 || sizes.contains(&0) {
+            return Err(LittleError::InvalidParameter(
+                "a network needs an input and an output size, all positive",
+            ));
+        }
+        let mut layers = Vec::with_capacity(sizes.len() - 1);
+        for pair in sizes.windows(2) {
+            let std = 1.0 / (pair[0] as Scalar).sqrt();
+            let weight = Tensor::randn(0.0 as Scalar, std, (pair[0], pair[1]), device)?;
+            let bias = Tensor::zeros(pair[1], weight.dtype(), device)?;
+            layers.push((weight, bias));
+        }
+        Ok(Self { layers })
+    }
+
+    /// A network from already trained layers.
+    pub fn from_layers(layers: Vec<(Tensor, Tensor)>) -> Result<Self, LittleError> {
+        let chained = layers
+            .windows(2)
+            .all(|pair| pair[0].0.dims().get(1) == pair[1].0.dims().first());
+        let shaped = layers
+            .iter()
+            .all(|(w, b)| w.rank() == 2 && b.dims() == [w.dims()[1]]);
+        if layers.is_empty() || !chained || !shaped {
+            return Err(LittleError::InvalidParameter(
+                "layers must be matrices and biases whose sizes chain together",
+            ));
+        }
+        Ok(Self { layers })
+    }
+
+    pub fn layers(&self) -> &[(Tensor, Tensor)] {
+        &self.layers
+    }
+
+    /// Run `features` through the network.
+    pub fn forward(&self, features: &[Scalar]) -> Result<Vec<Scalar>, LittleError> {
+        let device = self.layers[0].0.device();
+        let mut x = Tensor::from_slice(features, (1, features.len()), device)?;
+        for (i, (weight, bias)) in self.layers.iter().enumerate() {
+            x = x.matmul(weight)?.broadcast_add(bias)?;
+            if i + 1 < self.layers.len() {
+                x = x.relu()?;
+            }
+        }
+        Ok(x.squeeze(0)?.to_vec1()?)
+    }
+}
+
+/// The output has a logit per distinct action and the strategy is
+/// the softmax of the legal ones.
+///
+/// # Panics
+///
+/// If the features don't fit the network.
+impl StrategyNet for CandleMlp {
+    fn strategy(&self, features: &[Scalar], legal_actions: &[Action]) -> Vec<Scalar> {
+        let logits = self
+            .forward(features)
+            .expect("features must fit the network");
+        let legal: Vec<Scalar> = legal_actions.iter().map(|a| logits[*a]).collect();
+        let max = legal
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        let exp: Vec<Scalar> = legal.iter().map(|l| (l - max).exp()).collect();
+        let total: Scalar = exp.iter().sum();
+        exp.iter().map(|e| e / total).collect()
+    }
+}
+
+/// The output has a value per player.
+///
+/// # Panics
+///
+/// If the features don't fit the network.
+impl ValueNet for CandleMlp {
+    fn values(&self, features: &[Scalar]) -> Vec<Scalar> {
+        self.forward(features)
+            .expect("features must fit the network")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::Leduc;
+    use crate::solver::neural::tabulate_policy;
+
+    #[test]
+    fn test_leduc_policy() {
+        let net = CandleMlp::new(&[30, 16, 6], &Device::Cpu).unwrap();
+        let policy = tabulate_policy(&Leduc, &net);
+        assert_eq!(policy.len(), 288);
+        for strategy in policy.values() {
+            assert!((strategy.iter().sum::<Scalar>() - 1.0).abs() < 1e-5);
+        }
+        let layers = net.layers().to_vec();
+        assert!(CandleMlp::from_layers(layers[..1].to_vec()).is_ok());
+        assert!(CandleMlp::from_layers(vec![layers[1].clone(), layers[0].clone()]).is_err());
+    }
+}
@@ -0,0 +1,430 @@
+//! Counterfactual regret minimization over any `Game`, with one
+//! regret minimizer per information state. Every iteration walks
+//! the whole tree once per player and updates that player's
+//! minimizers with their counterfactual action values, so any
+//! `RegretMinimizer` in the crate can be used at the decisions.
+//!
+//! In chance sampling mode, `iterate_with_rng`, each traversal
+//! follows a single sampled outcome at every chance node while still
+//! trying every player action. Dividing by the sampling probability
+//! cancels chance's reach, so the sampled values are unbiased and
+//! an iteration over a card game only visits one deal.
+//!
+//! With `with_depth_limit` traversals stop at a fixed depth and
+//! take their values from a `LeafEvaluator` instead.
+//!
+//! New infosets get `M::new` unless `with_minimizers` says how to
+//! build them, which is how a discounted solve picks its exponents.
+use rand::{Rng, RngCore};
+
+#[cfg(feature = "checkpoint")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "checkpoint")]
+use serde::Serialize;
+
+use std::collections::HashMap;
+#[cfg(feature = "checkpoint")]
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+#[cfg(feature = "checkpoint")]
+use crate::checkpoint;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::games::exploitability;
+use crate::games::game::{Game, Player, State};
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+use crate::solver::depth_limit::{DepthLimit, LeafEvaluator};
+use crate::solver::store::{InfoSetStore, MinimizerFactory};
+
+// What one traversal learned about an information state.
+struct Pending {
+    // Action values weighted by everyone else's reach.
+    values: Vec<Scalar>,
+    // The acting player's own reach, which is the same for every
+    // history in the information state with perfect recall.
+    own_reach: Scalar,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameCfr<G: Game, M: RegretMinimizer> {
+    game: G,
+    infosets: InfoSetStore<M>,
+    num_iterations: usize,
+    depth_limit: Option<DepthLimit<G::State>>,
+    factory: Option<MinimizerFactory<M>>,
+}
+
+impl<G: Game, M: RegretMinimizer> GameCfr<G, M> {
+    pub fn new(game: G) -> Self {
+        Self {
+            game,
+            infosets: InfoSetStore::new(),
+            num_iterations: 0,
+            depth_limit: None,
+            factory: None,
+        }
+    }
+
+    /// Stop traversals `depth` actions from the root, chance's
+    /// included, and value the states there with `evaluator`.
+    #[must_use]
+    pub fn with_depth_limit(
+        mut self,
+        depth: usize,
+        evaluator: impl LeafEvaluator<G::State> + Send + Sync + 'static,
+    ) -> Self {
+        self.depth_limit = Some(DepthLimit::new(depth, evaluator));
+        self
+    }
+
+    /// Build the minimizer for each new infoset with `new`, which
+    /// is given the infoset's number of legal actions.
+    #[must_use]
+    pub fn with_minimizers(
+        mut self,
+        new: impl Fn(usize) -> Result<M, LittleError> + Send + Sync + 'static,
+    ) -> Self {
+        self.factory = Some(MinimizerFactory::new(new));
+        self
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn num_iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    /// The minimizer for each information state seen so far,
+    /// keyed by `information_state_string`.
+    pub fn infosets(&self) -> &InfoSetStore<M> {
+        &self.infosets
+    }
+
+    /// The average strategy at every information state. Entries
+    /// line up with the state's `legal_actions`.
+    pub fn average_policy(&self) -> HashMap<String, Vec<Scalar>> {
+        self.infosets.average_policy()
+    }
+
+    /// How exploitable the average policy is, see
+    /// `games::exploitability`.
+    pub fn exploitability(&self) -> Scalar {
+        exploitability::exploitability(&self.game, &self.average_policy())
+    }
+
+    /// Run one iteration, updating each player in turn.
+    pub fn iterate(&mut self) -> Result<(), LittleError> {
+        self.iterate_sampling::<dyn RngCore>(None)
+    }
+
+    /// Run one iteration, sampling chance outcomes with `rng`
+    /// instead of enumerating them.
+    pub fn iterate_with_rng<R: Rng>(&mut self, rng: &mut R) -> Result<(), LittleError> {
+        self.iterate_sampling(Some(rng))
+    }
+
+    fn iterate_sampling<R: RngCore + ?Sized>(
+        &mut self,
+        mut rng: Option<&mut R>,
+    ) -> Result<(), LittleError> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("cfr_iteration", iteration = self.num_iterations).entered();
+        let root = self.game.new_initial_state();
+        // The last entry is chance's contribution.
+        let reach = vec![1.0; self.game.num_players() + 1];
+        for player in 0..self.game.num_players() {
+            let mut pending = HashMap::new();
+            self.traverse(&root, 0, player, &reach, &mut pending, rng.as_deref_mut())?;
+            for (key, p) in pending {
+                let options = UpdateOptions {
+                    average_weight: p.own_reach,
+                    ..UpdateOptions::default()
+                };
+                self.infosets
+                    .get_mut(&key)
+                    .unwrap()
+                    .update_regret_with_options(&p.values, options)?;
+            }
+        }
+        self.num_iterations += 1;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(num_infosets = self.infosets.len(), "finished iteration");
+        Ok(())
+    }
+
+    pub fn run(&mut self, iterations: usize) -> Result<(), LittleError> {
+        for _i in 0..iterations {
+            self.iterate()?;
+        }
+        Ok(())
+    }
+
+    pub fn run_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        iterations: usize,
+    ) -> Result<(), LittleError> {
+        for _i in 0..iterations {
+            self.iterate_with_rng(rng)?;
+        }
+        Ok(())
+    }
+
+    // The value of `state`, `depth` actions from the root, to
+    // `player` when everyone plays their current strategy. Chance is
+    // sampled when there's an `rng`.
+    fn traverse<R: RngCore + ?Sized>(
+        &mut self,
+        state: &G::State,
+        depth: usize,
+        player: usize,
+        reach: &[Scalar],
+        pending: &mut HashMap<String, Pending>,
+        mut rng: Option<&mut R>,
+    ) -> Result<Scalar, LittleError> {
+        if let Some(limit) = &self.depth_limit {
+            if depth >= limit.depth && !state.is_terminal() {
+                return Ok(limit.evaluator.evaluate(state)[player]);
+            }
+        }
+        match state.current_player() {
+            Player::Terminal => Ok(state.returns()[player]),
+            Player::Chance if rng.is_some() => {
+                let outcomes = state.chance_outcomes();
+                let probs: Vec<Scalar> = outcomes.iter().map(|(_, p)| *p).collect();
+                let (action, _) =
+                    outcomes[dist::sample_weights(&probs, rng.as_deref_mut().unwrap())];
+                self.traverse(&state.child(action), depth + 1, player, reach, pending, rng)
+            }
+            Player::Chance => {
+                let chance = reach.len() - 1;
+                let mut value = 0.0;
+                for (action, prob) in state.chance_outcomes() {
+                    let mut child_reach = reach.to_vec();
+                    child_reach[chance] *= prob;
+                    value += prob
+                        * self.traverse(
+                            &state.child(action),
+                            depth + 1,
+                            player,
+                            &child_reach,
+                            pending,
+                            rng.as_deref_mut(),
+                        )?;
+                }
+                Ok(value)
+            }
+            Player::Id(actor) => {
+                let key = state.information_state_string(actor);
+                let actions = state.legal_actions();
+                let minimizer = match &self.factory {
+                    Some(factory) => self
+                        .infosets
+                        .get_or_insert_with(&key, || factory.build(actions.len()))?,
+                    None => self.infosets.get_or_insert(&key, actions.len())?,
+                };
+                let strategy = minimizer.current_strategy().to_vec();
+
+                let mut action_values = Vec::with_capacity(actions.len());
+                for (action, p) in actions.iter().zip(&strategy) {
+                    let mut child_reach = reach.to_vec();
+                    child_reach[actor] *= p;
+                    action_values.push(self.traverse(
+                        &state.child(*action),
+                        depth + 1,
+                        player,
+                        &child_reach,
+                        pending,
+                        rng.as_deref_mut(),
+                    )?);
+                }
+                let value = strategy
+                    .iter()
+                    .zip(&action_values)
+                    .map(|(p, v)| p * v)
+                    .sum();
+
+                if actor == player {
+                    let others: Scalar = reach
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != actor)
+                        .map(|(_, r)| r)
+                        .product();
+                    let entry = pending.entry(key).or_insert_with(|| Pending {
+                        values: vec![0.0; actions.len()],
+                        own_reach: reach[actor],
+                    });
+                    for (e, v) in entry.values.iter_mut().zip(&action_values) {
+                        *e += others * v;
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+impl<G: Game, M: RegretMinimizer + Serialize + DeserializeOwned> GameCfr<G, M> {
+    /// Save the solve to `path` with the state of `rng`, or `&()` when
+    /// chance is enumerated, see
+    /// `checkpoint::save_solve`.
+    /// The depth limit and minimizer factory aren't saved and have
+    /// to be set again.
+    pub fn save_checkpoint<R: Serialize, P: AsRef<Path>>(
+        &self,
+        path: P,
+        rng: &R,
+    ) -> Result<(), LittleError> {
+        checkpoint::save_solve(path, &self.infosets, self.num_iterations, rng)
+    }
+
+    /// Pick up a solve of `game` saved by `save_checkpoint`,
+    /// returning it with the saved rng.
+    pub fn resume<R: DeserializeOwned, P: AsRef<Path>>(
+        game: G,
+        path: P,
+    ) -> Result<(Self, R), LittleError> {
+        let saved = checkpoint::load_solve(path)?;
+        let solver = Self {
+            game,
+            infosets: saved.infosets,
+            num_iterations: saved.num_iterations,
+            depth_limit: None,
+            factory: None,
+        };
+        Ok((solver, saved.rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcfr::DiscountedRegretMatcher;
+    use crate::discount::DiscountParams;
+    use crate::games::exploitability::{exploitability, state_value};
+    use crate::games::game::Action;
+    use crate::games::goofspiel::GoofspielState;
+    use crate::games::{Goofspiel, Leduc};
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Rock paper scissors where the second player can't see the
+    // first player's choice.
+    struct Rps;
+
+    #[derive(Debug, Clone)]
+    struct RpsState {
+        moves: Vec<Action>,
+    }
+
+    impl Game for Rps {
+        type State = RpsState;
+
+        fn num_players(&self) -> usize {
+            2
+        }
+
+        fn num_distinct_actions(&self) -> usize {
+            3
+        }
+
+        fn new_initial_state(&self) -> RpsState {
+            RpsState { moves: Vec::new() }
+        }
+    }
+
+    impl State for RpsState {
+        fn current_player(&self) -> Player {
+            match self.moves.len() {
+                2 => Player::Terminal,
+                n => Player::Id(n),
+            }
+        }
+
+        fn legal_actions(&self) -> Vec<Action> {
+            vec![0, 1, 2]
+        }
+
+        fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+            Vec::new()
+        }
+
+        fn apply_action(&mut self, action: Action) {
+            self.moves.push(action);
+        }
+
+        fn returns(&self) -> Vec<Scalar> {
+            let won = match (self.moves[0] + 3 - self.moves[1]) % 3 {
+                0 => 0.0,
+                1 => 1.0,
+                _ => -1.0,
+            };
+            vec![won, -won]
+        }
+
+        fn information_state_string(&self, player: usize) -> String {
+            format!("player {player}")
+        }
+    }
+
+    #[test]
+    fn test_chance_sampling() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run_with_rng(&mut rng, 10).unwrap();
+        let early = cfr.exploitability();
+        cfr.run_with_rng(&mut rng, 1_000).unwrap();
+        let e = cfr.exploitability();
+        assert!(e < 0.5 * early, "{early} {e}");
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // After each round of Goofspiel the bids are public, so a
+        // solve of the first round is exact given the values of
+        // the rest of the game under an equilibrium.
+        let game = Goofspiel::new(3).unwrap();
+        let mut full = GameCfr::<_, VanillaCfrRegretMatcher>::new(game);
+        full.run(200).unwrap();
+        let blueprint = full.average_policy();
+
+        let values = blueprint.clone();
+        let mut limited = GameCfr::<_, VanillaCfrRegretMatcher>::new(game)
+            .with_depth_limit(3, move |s: &GoofspielState| state_value(s, &values));
+        limited.run(200).unwrap();
+        // Only the first round's infosets are ever reached.
+        assert_eq!(limited.infosets().len(), 6);
+        let mut combined = blueprint;
+        combined.extend(limited.average_policy());
+        assert!(exploitability(&game, &combined) < 0.05);
+    }
+
+    #[test]
+    fn test_with_minimizers() {
+        let params = DiscountParams::lcfr();
+        let mut cfr = GameCfr::new(Rps)
+            .with_minimizers(move |n| DiscountedRegretMatcher::new_with_params(n, params));
+        cfr.run(500).unwrap();
+        assert!(cfr.exploitability() < 0.05);
+    }
+
+    #[test]
+    fn test_rps() {
+        let mut cfr = GameCfr::<Rps, VanillaCfrRegretMatcher>::new(Rps);
+        cfr.run(2_000).unwrap();
+        let policy = cfr.average_policy();
+        assert_eq!(policy.len(), 2);
+        for p in policy.values().flatten() {
+            assert!((p - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+}
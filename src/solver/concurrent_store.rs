@@ -0,0 +1,212 @@
+//! An infoset store that several traversal threads can train at
+//! once. Infosets are keyed by a 64 bit hash of their key rather
+//! than the string, and split across shards that each have their
+//! own lock, so threads only wait on each other when they touch the
+//! same shard.
+//!
+//! `hash_key` is FNV-1a, which doesn't depend on the platform or
+//! the Rust version, so hashes saved in a checkpoint stay valid.
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+const DEFAULT_SHARDS: usize = 64;
+
+/// The stable 64 bit hash of an infoset key.
+#[must_use]
+pub fn hash_key(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Debug)]
+pub struct ConcurrentInfoSetStore<M> {
+    shards: Vec<RwLock<HashMap<u64, M>>>,
+}
+
+impl<M: RegretMinimizer> Default for ConcurrentInfoSetStore<M> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHARDS).unwrap()
+    }
+}
+
+impl<M: RegretMinimizer> ConcurrentInfoSetStore<M> {
+    pub fn new(num_shards: usize) -> Result<Self, LittleError> {
+        if num_shards == 0 {
+            return Err(LittleError::InvalidParameter(
+                "there must be at least one shard",
+            ));
+        }
+        Ok(Self {
+            shards: (0..num_shards).map(|_| RwLock::default()).collect(),
+        })
+    }
+
+    /// A store holding `infosets`, for example from `snapshot`.
+    pub fn from_map(num_shards: usize, infosets: HashMap<u64, M>) -> Result<Self, LittleError> {
+        let store = Self::new(num_shards)?;
+        for (hash, m) in infosets {
+            store.write(hash).insert(hash, m);
+        }
+        Ok(store)
+    }
+
+    fn shard(&self, hash: u64) -> &RwLock<HashMap<u64, M>> {
+        // The high bits, since FNV mixes them best.
+        &self.shards[((hash >> 32) as usize) % self.shards.len()]
+    }
+
+    // A poisoned shard only means another thread panicked part way
+    // through an update, which leaves at worst one odd update.
+    fn write(&self, hash: u64) -> RwLockWriteGuard<'_, HashMap<u64, M>> {
+        self.shard(hash)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read(&self, hash: u64) -> RwLockReadGuard<'_, HashMap<u64, M>> {
+        self.shard(hash)
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run `f` on the minimizer for `hash`, creating one with
+    /// `num_actions` experts if the infoset hasn't been seen. The
+    /// shard is locked while `f` runs.
+    pub fn with_minimizer<R>(
+        &self,
+        hash: u64,
+        num_actions: usize,
+        f: impl FnOnce(&mut M) -> R,
+    ) -> Result<R, LittleError> {
+        let mut shard = self.write(hash);
+        let m = match shard.entry(hash) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(M::new(num_actions)?),
+        };
+        Ok(f(m))
+    }
+
+    /// The strategy to play at `hash`, only taking a read lock when
+    /// the infoset already exists.
+    pub fn current_strategy(
+        &self,
+        hash: u64,
+        num_actions: usize,
+    ) -> Result<Vec<Scalar>, LittleError> {
+        if let Some(m) = self.read(hash).get(&hash) {
+            return Ok(m.current_strategy().to_vec());
+        }
+        self.with_minimizer(hash, num_actions, |m| m.current_strategy().to_vec())
+    }
+
+    /// Visit every infoset, a shard at a time.
+    pub fn for_each(&self, mut f: impl FnMut(u64, &M)) {
+        for shard in &self.shards {
+            let shard = shard.read().unwrap_or_else(PoisonError::into_inner);
+            for (hash, m) in shard.iter() {
+                f(*hash, m);
+            }
+        }
+    }
+
+    /// The average strategy at every infoset.
+    #[must_use]
+    pub fn average_policy(&self) -> HashMap<u64, Vec<Scalar>> {
+        let mut policy = HashMap::new();
+        self.for_each(|hash, m| {
+            policy.insert(hash, m.best_weight());
+        });
+        policy
+    }
+
+    /// A copy of every minimizer, which can be serialized for a
+    /// checkpoint and loaded back with `from_map`.
+    #[must_use]
+    pub fn snapshot(&self) -> HashMap<u64, M> {
+        let mut infosets = HashMap::new();
+        self.for_each(|hash, m| {
+            infosets.insert(hash, m.clone());
+        });
+        infosets
+    }
+
+    /// Move every infoset of `other` into this store. When both
+    /// have the same infoset `combine` folds the other's minimizer
+    /// into this one.
+    pub fn merge(
+        &self,
+        other: Self,
+        mut combine: impl FnMut(&mut M, M) -> Result<(), LittleError>,
+    ) -> Result<(), LittleError> {
+        for shard in other.shards {
+            let shard = shard.into_inner().unwrap_or_else(PoisonError::into_inner);
+            for (hash, m) in shard {
+                let mut mine = self.write(hash);
+                match mine.get_mut(&hash) {
+                    Some(existing) => combine(existing, m)?,
+                    None => {
+                        mine.insert(hash, m);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_threads_share_a_store() {
+        let store = ConcurrentInfoSetStore::<VanillaCfrRegretMatcher>::new(8).unwrap();
+        std::thread::scope(|s| {
+            for _t in 0..4 {
+                s.spawn(|| {
+                    for i in 0..100 {
+                        let hash = hash_key(&format!("infoset {i}"));
+                        store
+                            .with_minimizer(hash, 2, |m| m.update_regret(&[1.0, 0.0]))
+                            .unwrap()
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(store.len(), 100);
+        store.for_each(|_, m| assert_eq!(m.num_updates(), 4));
+
+        let other = ConcurrentInfoSetStore::from_map(2, store.snapshot()).unwrap();
+        other.with_minimizer(hash_key("new"), 3, |_| ()).unwrap();
+        store
+            .merge(other, |m, theirs| {
+                m.update_regret(&theirs.cumulative_regrets())
+            })
+            .unwrap();
+        assert_eq!(store.len(), 101);
+        let hash = hash_key("infoset 7");
+        assert_eq!(store.current_strategy(hash, 2).unwrap(), vec![1.0, 0.0]);
+        assert_eq!(hash_key(""), 0xcbf2_9ce4_8422_2325);
+    }
+}
@@ -0,0 +1,45 @@
+//! Training data for Deep CFR. Rather than keep a table of
+//! regrets, Deep CFR trains a network to predict them from an
+//! encoding of the information state. The dataset is the sampled
+//! regrets, or advantages, of every traverser decision in external
+//! sampling MCCFR, tagged with the iteration so later ones can be
+//! weighted more. `ExternalSamplingCfr::iterate_with_sink` hands
+//! each one to an `AdvantageSink` as it's computed.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::Action;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdvantageSample {
+    /// The traverser, who acts at the information state.
+    pub player: usize,
+    pub info_state: String,
+    /// The state's `information_state_tensor`.
+    pub features: Vec<Scalar>,
+    /// The iteration the sample came from, counting from one.
+    pub iteration: usize,
+    pub legal_actions: Vec<Action>,
+    /// How much better each legal action did than the current
+    /// strategy on this traversal.
+    pub advantages: Vec<Scalar>,
+}
+
+/// Somewhere to put advantage samples, like a replay buffer or a
+/// file.
+pub trait AdvantageSink {
+    fn push(&mut self, sample: AdvantageSample) -> Result<(), LittleError>;
+}
+
+impl AdvantageSink for Vec<AdvantageSample> {
+    fn push(&mut self, sample: AdvantageSample) -> Result<(), LittleError> {
+        Vec::push(self, sample);
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+//! Cutting a traversal off at a fixed depth. Below the limit the
+//! solver doesn't walk the rest of the tree but asks a
+//! `LeafEvaluator` what each player can expect from there, whether
+//! that's a heuristic, a blueprint's values or a learned model.
+use std::fmt;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::games::game::State;
+use crate::scalar::Scalar;
+
+pub trait LeafEvaluator<S: State> {
+    /// The estimated value of `state` to each player.
+    fn evaluate(&self, state: &S) -> Vec<Scalar>;
+}
+
+impl<S: State, F: Fn(&S) -> Vec<Scalar>> LeafEvaluator<S> for F {
+    fn evaluate(&self, state: &S) -> Vec<Scalar> {
+        self(state)
+    }
+}
+
+/// A depth, counted in actions from the root including chance's,
+/// and the evaluator for states that reach it.
+pub struct DepthLimit<S: State> {
+    pub depth: usize,
+    pub evaluator: Arc<dyn LeafEvaluator<S> + Send + Sync>,
+}
+
+impl<S: State> DepthLimit<S> {
+    pub fn new(depth: usize, evaluator: impl LeafEvaluator<S> + Send + Sync + 'static) -> Self {
+        Self {
+            depth,
+            evaluator: Arc::new(evaluator),
+        }
+    }
+}
+
+impl<S: State> Clone for DepthLimit<S> {
+    fn clone(&self) -> Self {
+        Self {
+            depth: self.depth,
+            evaluator: Arc::clone(&self.evaluator),
+        }
+    }
+}
+
+impl<S: State> fmt::Debug for DepthLimit<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepthLimit")
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,302 @@
+//! External sampling Monte Carlo CFR. Each iteration makes one
+//! traversal per player that samples a single outcome at chance
+//! nodes and a single action for every other player, while the
+//! traverser still tries each of its actions. The sampled values
+//! are unbiased estimates of the counterfactual values, so the
+//! regret updates are the same as in vanilla CFR, but an iteration
+//! only touches a small slice of the tree.
+//!
+//! The average strategy uses simple averaging: each time a
+//! traversal samples an action at another player's infoset, that
+//! player's current strategy is added to their average. A traversal
+//! reaches an infoset in proportion to its owner's own reach, so in
+//! expectation this is the reach weighted average of full CFR.
+//!
+//! `iterate_with_sink` also reports every sampled regret to an
+//! `AdvantageSink`, which is the training data for Deep CFR.
+use rand::Rng;
+
+#[cfg(feature = "checkpoint")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "checkpoint")]
+use serde::Serialize;
+
+use std::collections::HashMap;
+#[cfg(feature = "checkpoint")]
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+#[cfg(feature = "checkpoint")]
+use crate::checkpoint;
+use crate::dist;
+use crate::errors::LittleError;
+use crate::games::exploitability;
+use crate::games::game::{Game, Player, State};
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+use crate::solver::deep_cfr::{AdvantageSample, AdvantageSink};
+use crate::solver::store::InfoSetStore;
+
+#[derive(Debug, Clone)]
+pub struct ExternalSamplingCfr<G: Game, M: RegretMinimizer> {
+    game: G,
+    infosets: InfoSetStore<M>,
+    num_iterations: usize,
+}
+
+impl<G: Game, M: RegretMinimizer> ExternalSamplingCfr<G, M> {
+    pub fn new(game: G) -> Self {
+        Self {
+            game,
+            infosets: InfoSetStore::new(),
+            num_iterations: 0,
+        }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn num_iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    pub fn infosets(&self) -> &InfoSetStore<M> {
+        &self.infosets
+    }
+
+    pub fn average_policy(&self) -> HashMap<String, Vec<Scalar>> {
+        self.infosets.average_policy()
+    }
+
+    /// How exploitable the average policy is, see
+    /// `games::exploitability`.
+    pub fn exploitability(&self) -> Scalar {
+        exploitability::exploitability(&self.game, &self.average_policy())
+    }
+
+    /// Run one iteration, sampling a traversal for each player.
+    pub fn iterate_with_rng<R: Rng>(&mut self, rng: &mut R) -> Result<(), LittleError> {
+        self.iterate_sampling::<_, dyn AdvantageSink>(rng, None)
+    }
+
+    /// Run one iteration, also giving `sink` the sampled regrets
+    /// at each of the traversers' decisions.
+    pub fn iterate_with_sink<R: Rng, S: AdvantageSink + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        sink: &mut S,
+    ) -> Result<(), LittleError> {
+        self.iterate_sampling(rng, Some(sink))
+    }
+
+    fn iterate_sampling<R: Rng, S: AdvantageSink + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        mut sink: Option<&mut S>,
+    ) -> Result<(), LittleError> {
+        let root = self.game.new_initial_state();
+        for player in 0..self.game.num_players() {
+            let mut visits = HashMap::new();
+            self.traverse(&root, player, &mut visits, rng, sink.as_deref_mut())?;
+            // Nobody but the traverser learned anything, so every
+            // visit to an infoset saw the same strategy.
+            for (key, count) in visits {
+                let minimizer = self.infosets.get_mut(&key).unwrap();
+                let strategy = minimizer.current_strategy().to_vec();
+                minimizer.accumulate_average(&strategy, count)?;
+            }
+        }
+        self.num_iterations += 1;
+        Ok(())
+    }
+
+    pub fn run_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        iterations: usize,
+    ) -> Result<(), LittleError> {
+        for _i in 0..iterations {
+            self.iterate_with_rng(rng)?;
+        }
+        Ok(())
+    }
+
+    // A sampled estimate of the value of `state` to `player`,
+    // counting how often each of the other players' infosets is
+    // sampled in `visits`.
+    fn traverse<R: Rng, S: AdvantageSink + ?Sized>(
+        &mut self,
+        state: &G::State,
+        player: usize,
+        visits: &mut HashMap<String, Scalar>,
+        rng: &mut R,
+        mut sink: Option<&mut S>,
+    ) -> Result<Scalar, LittleError> {
+        match state.current_player() {
+            Player::Terminal => Ok(state.returns()[player]),
+            Player::Chance => {
+                let outcomes = state.chance_outcomes();
+                let probs: Vec<Scalar> = outcomes.iter().map(|(_, p)| *p).collect();
+                let (action, _) = outcomes[dist::sample_weights(&probs, rng)];
+                self.traverse(&state.child(action), player, visits, rng, sink)
+            }
+            Player::Id(actor) => {
+                let key = state.information_state_string(actor);
+                let actions = state.legal_actions();
+                let strategy = self
+                    .infosets
+                    .get_or_insert(&key, actions.len())?
+                    .current_strategy()
+                    .to_vec();
+                if actor != player {
+                    *visits.entry(key).or_insert(0.0) += 1.0;
+                    let action = actions[dist::sample_weights(&strategy, rng)];
+                    return self.traverse(&state.child(action), player, visits, rng, sink);
+                }
+
+                let mut values = Vec::with_capacity(actions.len());
+                for action in &actions {
+                    values.push(self.traverse(
+                        &state.child(*action),
+                        player,
+                        visits,
+                        rng,
+                        sink.as_deref_mut(),
+                    )?);
+                }
+                // The average is built at the other players' infosets.
+                let options = UpdateOptions {
+                    contribute_to_average: false,
+                    ..UpdateOptions::default()
+                };
+                // With perfect recall a traversal reaches each of the
+                // traverser's infosets at most once.
+                self.infosets
+                    .get_mut(&key)
+                    .unwrap()
+                    .update_regret_with_options(&values, options)?;
+                let value = strategy.iter().zip(&values).map(|(p, v)| p * v).sum();
+                if let Some(sink) = sink {
+                    sink.push(AdvantageSample {
+                        player,
+                        features: state.information_state_tensor(player),
+                        info_state: key,
+                        iteration: self.num_iterations + 1,
+                        legal_actions: actions,
+                        advantages: values.iter().map(|v| v - value).collect(),
+                    })?;
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+impl<G: Game, M: RegretMinimizer + Serialize + DeserializeOwned> ExternalSamplingCfr<G, M> {
+    /// Save the solve to `path` with the state of `rng`, see
+    /// `checkpoint::save_solve`.
+    pub fn save_checkpoint<R: Serialize, P: AsRef<Path>>(
+        &self,
+        path: P,
+        rng: &R,
+    ) -> Result<(), LittleError> {
+        checkpoint::save_solve(path, &self.infosets, self.num_iterations, rng)
+    }
+
+    /// Pick up a solve of `game` saved by `save_checkpoint`,
+    /// returning it with the saved rng.
+    pub fn resume<R: DeserializeOwned, P: AsRef<Path>>(
+        game: G,
+        path: P,
+    ) -> Result<(Self, R), LittleError> {
+        let saved = checkpoint::load_solve(path)?;
+        let solver = Self {
+            game,
+            infosets: saved.infosets,
+            num_iterations: saved.num_iterations,
+        };
+        Ok((solver, saved.rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::Leduc;
+    use crate::solver::GameCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    #[cfg(feature = "checkpoint")]
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_leduc() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut cfr = ExternalSamplingCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run_with_rng(&mut rng, 100).unwrap();
+        let early = cfr.exploitability();
+        cfr.run_with_rng(&mut rng, 5_000).unwrap();
+        let e = cfr.exploitability();
+        assert_eq!(cfr.infosets().len(), 288);
+        assert!(e < 0.5 * early, "{early} {e}");
+    }
+
+    #[test]
+    fn test_matches_full_traversals() {
+        let mut full = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        full.run(200).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sampled = ExternalSamplingCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        sampled.run_with_rng(&mut rng, 20_000).unwrap();
+        // Weighting the average by the traverser's own reach instead
+        // stalls at several times this.
+        let (f, e) = (full.exploitability(), sampled.exploitability());
+        assert!(e < 2.0 * f + 0.05, "{f} {e}");
+    }
+
+    #[test]
+    fn test_advantage_samples() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut cfr = ExternalSamplingCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        let mut samples = Vec::new();
+        cfr.iterate_with_sink(&mut rng, &mut samples).unwrap();
+        cfr.iterate_with_sink(&mut rng, &mut samples).unwrap();
+        assert!(samples.iter().any(|s| s.player == 0));
+        assert!(samples.iter().any(|s| s.player == 1));
+        assert_eq!(samples.last().unwrap().iteration, 2);
+        for s in &samples {
+            assert_eq!(s.features.len(), 30);
+            assert_eq!(s.advantages.len(), s.legal_actions.len());
+        }
+        // The first iteration plays uniformly, so the advantages
+        // average out to zero.
+        let first = samples.iter().find(|s| s.iteration == 1).unwrap();
+        let total: Scalar = first.advantages.iter().sum();
+        assert!(total.abs() < 1e-4);
+    }
+
+    #[cfg(feature = "checkpoint")]
+    #[test]
+    fn test_resume() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut cfr = ExternalSamplingCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run_with_rng(&mut rng, 20).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("little-sorry-mccfr-{}.ckpt", std::process::id()));
+        cfr.save_checkpoint(&path, &rng).unwrap();
+        cfr.run_with_rng(&mut rng, 20).unwrap();
+
+        let (mut resumed, mut rng): (ExternalSamplingCfr<_, VanillaCfrRegretMatcher>, ChaCha8Rng) =
+            ExternalSamplingCfr::resume(Leduc, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resumed.num_iterations(), 20);
+        resumed.run_with_rng(&mut rng, 20).unwrap();
+        // The saved rng replays the same samples, so the two solves
+        // end up identical.
+        assert_eq!(resumed.average_policy(), cfr.average_policy());
+    }
+}
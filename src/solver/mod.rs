@@ -0,0 +1,33 @@
+//! Solving extensive form games. Games are described with the
+//! `Game` and `State` traits from `games`, where each player's
+//! `information_state_string` identifies their infosets. An
+//! `InfoSetStore` keeps a regret minimizer per infoset and the
+//! traversals here walk the game tree computing counterfactual
+//! values to feed them, so the per-node matchers in this crate can
+//! solve whole games without a hand written tree walk.
+#[cfg(feature = "candle")]
+pub mod candle_net;
+pub mod cfr;
+pub mod concurrent_store;
+pub mod deep_cfr;
+pub mod depth_limit;
+pub mod mccfr;
+pub mod neural;
+pub mod resolve;
+pub mod sequence_form;
+pub mod store;
+pub mod vector;
+
+#[cfg(feature = "candle")]
+pub use self::candle_net::CandleMlp;
+pub use self::cfr::GameCfr;
+pub use self::concurrent_store::ConcurrentInfoSetStore;
+pub use self::deep_cfr::{AdvantageSample, AdvantageSink};
+pub use self::depth_limit::{DepthLimit, LeafEvaluator};
+pub use self::mccfr::ExternalSamplingCfr;
+pub use self::neural::{StrategyNet, ValueNet, ValueNetLeaf};
+pub use self::resolve::{resolve_subgame, ResolveConfig};
+pub use self::sequence_form::SequenceForm;
+pub use self::store::{InfoSetStore, MinimizerFactory};
+pub use self::vector::{PublicNode, RangeGame, VectorCfr};
+pub use crate::games::game::{Action, Game, Player, State};
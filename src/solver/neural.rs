@@ -0,0 +1,128 @@
+//! Hooks for plugging learned models into the solvers, as in Deep
+//! CFR and DREAM. A `StrategyNet` maps an information state's
+//! `information_state_tensor` to a strategy over its legal
+//! actions, and a `ValueNet` maps a history to what each player
+//! expects to win from it. Any closure with the right signature is
+//! either, and the `candle` feature adds a reference network.
+use std::collections::HashMap;
+use std::vec::Vec;
+
+use crate::games::exploitability::Policy;
+use crate::games::game::{Action, Game, Player, State};
+use crate::scalar::Scalar;
+use crate::solver::depth_limit::LeafEvaluator;
+
+pub trait StrategyNet {
+    /// A strategy over `legal_actions` for the information state
+    /// encoded by `features`.
+    fn strategy(&self, features: &[Scalar], legal_actions: &[Action]) -> Vec<Scalar>;
+}
+
+impl<F: Fn(&[Scalar], &[Action]) -> Vec<Scalar>> StrategyNet for F {
+    fn strategy(&self, features: &[Scalar], legal_actions: &[Action]) -> Vec<Scalar> {
+        self(features, legal_actions)
+    }
+}
+
+pub trait ValueNet {
+    /// The value to each player of the history encoded by
+    /// `features`, see `history_features`.
+    fn values(&self, features: &[Scalar]) -> Vec<Scalar>;
+}
+
+impl<F: Fn(&[Scalar]) -> Vec<Scalar>> ValueNet for F {
+    fn values(&self, features: &[Scalar]) -> Vec<Scalar> {
+        self(features)
+    }
+}
+
+/// Every player's `information_state_tensor` one after another,
+/// which together pin down the history.
+pub fn history_features<S: State>(state: &S, num_players: usize) -> Vec<Scalar> {
+    (0..num_players)
+        .flat_map(|p| state.information_state_tensor(p))
+        .collect()
+}
+
+/// Use a `ValueNet` as the leaf evaluator of a depth limited
+/// solve.
+#[derive(Debug, Clone)]
+pub struct ValueNetLeaf<N> {
+    net: N,
+    num_players: usize,
+}
+
+impl<N: ValueNet> ValueNetLeaf<N> {
+    pub fn new(net: N, num_players: usize) -> Self {
+        Self { net, num_players }
+    }
+}
+
+impl<S: State, N: ValueNet> LeafEvaluator<S> for ValueNetLeaf<N> {
+    fn evaluate(&self, state: &S) -> Vec<Scalar> {
+        self.net.values(&history_features(state, self.num_players))
+    }
+}
+
+/// Ask `net` for the strategy at every information state in
+/// `game`, so the result can be played or measured with
+/// `games::exploitability`.
+pub fn tabulate_policy<G: Game, N: StrategyNet>(game: &G, net: &N) -> Policy {
+    let mut policy = HashMap::new();
+    tabulate(&game.new_initial_state(), net, &mut policy);
+    policy
+}
+
+fn tabulate<S: State, N: StrategyNet>(state: &S, net: &N, policy: &mut Policy) {
+    let actions = match state.current_player() {
+        Player::Terminal => return,
+        Player::Chance => state
+            .chance_outcomes()
+            .into_iter()
+            .map(|(a, _)| a)
+            .collect(),
+        Player::Id(actor) => {
+            let actions = state.legal_actions();
+            policy
+                .entry(state.information_state_string(actor))
+                .or_insert_with(|| net.strategy(&state.information_state_tensor(actor), &actions));
+            actions
+        }
+    };
+    for action in actions {
+        tabulate(&state.child(action), net, policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::exploitability;
+    use crate::games::Leduc;
+    use crate::solver::GameCfr;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_tabulate_uniform() {
+        let uniform =
+            |_: &[Scalar], actions: &[Action]| vec![1.0 / actions.len() as Scalar; actions.len()];
+        let policy = tabulate_policy(&Leduc, &uniform);
+        assert_eq!(policy.len(), 288);
+        let expected = exploitability(&Leduc, &Policy::new());
+        assert!((exploitability(&Leduc, &policy) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_value_net_leaf() {
+        // Cutting off right after the deal leaves only player
+        // one's opening decision with each card.
+        let net = |features: &[Scalar]| {
+            assert_eq!(features.len(), 60);
+            vec![0.0, 0.0]
+        };
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc)
+            .with_depth_limit(3, ValueNetLeaf::new(net, 2));
+        cfr.run(10).unwrap();
+        assert_eq!(cfr.infosets().len(), 3);
+    }
+}
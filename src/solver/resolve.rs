@@ -0,0 +1,313 @@
+//! Safe re-solving of a subgame in a two player zero sum game,
+//! using the CFR-D gadget. A blueprint strategy is replaced below
+//! some point with one solved just for the subgame, while keeping
+//! the opponent from gaining anything by the change.
+//!
+//! The gadget game starts with chance picking one of the subgame's
+//! root histories in proportion to how likely the blueprint makes
+//! it. The opponent then sees their information state and either
+//! terminates, taking the value the blueprint gave them there, or
+//! follows into the subgame. Solving the gadget gives the resolving
+//! player a strategy that holds every opponent information state to
+//! at most its blueprint value.
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::exploitability::Policy;
+use crate::games::game::{Action, Game, Player, State};
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+use crate::solver::GameCfr;
+
+const TERMINATE: Action = 0;
+const FOLLOW: Action = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolveConfig {
+    /// The player whose strategy is re-solved.
+    pub player: usize,
+    /// CFR iterations to run on the gadget game.
+    pub iterations: usize,
+}
+
+impl Default for ResolveConfig {
+    fn default() -> Self {
+        Self {
+            player: 0,
+            iterations: 1_000,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Gadget<S> {
+    roots: Vec<S>,
+    // Normalized beliefs over the roots.
+    probs: Vec<Scalar>,
+    // The opponent's information state at each root and the value
+    // they get by terminating there.
+    keys: Vec<String>,
+    values: Vec<Scalar>,
+    opponent: usize,
+    num_distinct_actions: usize,
+}
+
+#[derive(Debug, Clone)]
+struct GadgetGame<S>(Rc<Gadget<S>>);
+
+#[derive(Debug, Clone)]
+enum Stage<S> {
+    Start,
+    Choose(usize),
+    Terminated(usize),
+    Playing(S),
+}
+
+#[derive(Debug, Clone)]
+struct GadgetState<S> {
+    gadget: Rc<Gadget<S>>,
+    stage: Stage<S>,
+}
+
+impl<S: State> Game for GadgetGame<S> {
+    type State = GadgetState<S>;
+
+    fn num_players(&self) -> usize {
+        2
+    }
+
+    fn num_distinct_actions(&self) -> usize {
+        self.0.num_distinct_actions
+    }
+
+    fn new_initial_state(&self) -> GadgetState<S> {
+        GadgetState {
+            gadget: Rc::clone(&self.0),
+            stage: Stage::Start,
+        }
+    }
+}
+
+impl<S: State> State for GadgetState<S> {
+    fn current_player(&self) -> Player {
+        match &self.stage {
+            Stage::Start => Player::Chance,
+            Stage::Choose(_) => Player::Id(self.gadget.opponent),
+            Stage::Terminated(_) => Player::Terminal,
+            Stage::Playing(s) => s.current_player(),
+        }
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        match &self.stage {
+            Stage::Choose(_) => vec![TERMINATE, FOLLOW],
+            Stage::Playing(s) => s.legal_actions(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn chance_outcomes(&self) -> Vec<(Action, Scalar)> {
+        match &self.stage {
+            Stage::Start => self
+                .gadget
+                .probs
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| **p > 0.0)
+                .map(|(i, p)| (i, *p))
+                .collect(),
+            Stage::Playing(s) => s.chance_outcomes(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn apply_action(&mut self, action: Action) {
+        self.stage = match std::mem::replace(&mut self.stage, Stage::Start) {
+            Stage::Start => Stage::Choose(action),
+            Stage::Choose(root) if action == TERMINATE => Stage::Terminated(root),
+            Stage::Choose(root) => Stage::Playing(self.gadget.roots[root].clone()),
+            Stage::Playing(mut s) => {
+                s.apply_action(action);
+                Stage::Playing(s)
+            }
+            Stage::Terminated(_) => panic!("the game is over"),
+        };
+    }
+
+    fn returns(&self) -> Vec<Scalar> {
+        match &self.stage {
+            Stage::Terminated(root) => {
+                let value = self.gadget.values[*root];
+                let mut returns = vec![-value; 2];
+                returns[self.gadget.opponent] = value;
+                returns
+            }
+            Stage::Playing(s) => s.returns(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn information_state_string(&self, player: usize) -> String {
+        match &self.stage {
+            Stage::Choose(root) => format!("resolve gadget {}", self.gadget.keys[*root]),
+            Stage::Playing(s) => s.information_state_string(player),
+            _ => String::new(),
+        }
+    }
+}
+
+// The resolving player's information states below `state`.
+fn collect_keys<S: State>(state: &S, player: usize, keys: &mut HashSet<String>) {
+    match state.current_player() {
+        Player::Terminal => return,
+        Player::Id(actor) if actor == player => {
+            keys.insert(state.information_state_string(actor));
+        }
+        _ => {}
+    }
+    let actions = if state.is_chance_node() {
+        state
+            .chance_outcomes()
+            .into_iter()
+            .map(|(a, _)| a)
+            .collect()
+    } else {
+        state.legal_actions()
+    };
+    for action in actions {
+        collect_keys(&state.child(action), player, keys);
+    }
+}
+
+/// Re-solve the subgame starting at the histories in
+/// `root_beliefs`, each with how likely the blueprint is to reach
+/// it, including chance. `blueprint_values` holds the opponent's
+/// expected value under the blueprint at each of their information
+/// states at the roots. Returns the resolving player's strategy at
+/// every information state in the subgame; the opponent's strategy
+/// from the gadget isn't safe and is left out.
+pub fn resolve_subgame<G: Game, M: RegretMinimizer>(
+    game: &G,
+    root_beliefs: &[(G::State, Scalar)],
+    blueprint_values: &HashMap<String, Scalar>,
+    config: &ResolveConfig,
+) -> Result<Policy, LittleError> {
+    if game.num_players() != 2 || config.player > 1 {
+        return Err(LittleError::InvalidParameter(
+            "re-solving needs a two player game",
+        ));
+    }
+    let total: Scalar = root_beliefs.iter().map(|(_, p)| p).sum();
+    if root_beliefs.is_empty() || total <= 0.0 {
+        return Err(LittleError::InvalidParameter(
+            "root beliefs must have a positive total",
+        ));
+    }
+    let opponent = 1 - config.player;
+    let mut keys = Vec::with_capacity(root_beliefs.len());
+    let mut values = Vec::with_capacity(root_beliefs.len());
+    for (state, _) in root_beliefs {
+        let key = state.information_state_string(opponent);
+        let value = *blueprint_values
+            .get(&key)
+            .ok_or(LittleError::InvalidParameter(
+                "blueprint_values must cover every opponent infoset at the roots",
+            ))?;
+        keys.push(key);
+        values.push(value);
+    }
+    let gadget = GadgetGame(Rc::new(Gadget {
+        roots: root_beliefs.iter().map(|(s, _)| s.clone()).collect(),
+        probs: root_beliefs.iter().map(|(_, p)| p / total).collect(),
+        keys,
+        values,
+        opponent,
+        num_distinct_actions: game.num_distinct_actions().max(2),
+    }));
+
+    let mut subgame_keys = HashSet::new();
+    for (state, _) in root_beliefs {
+        collect_keys(state, config.player, &mut subgame_keys);
+    }
+    let mut cfr = GameCfr::<_, M>::new(gadget);
+    cfr.run(config.iterations)?;
+    let mut policy = cfr.average_policy();
+    policy.retain(|key, _| subgame_keys.contains(key));
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::{best_response_value, state_value};
+    use crate::games::leduc::LeducState;
+    use crate::games::Leduc;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    // Every history where the public card has just been dealt, with
+    // how likely `policy` makes it.
+    fn second_round(
+        state: &LeducState,
+        reach: Scalar,
+        policy: &Policy,
+        roots: &mut Vec<(LeducState, Scalar)>,
+    ) {
+        if state.round() == 1 {
+            roots.push((state.clone(), reach));
+            return;
+        }
+        let children: Vec<(Action, Scalar)> = match state.current_player() {
+            Player::Terminal => return,
+            Player::Chance => state.chance_outcomes(),
+            Player::Id(actor) => {
+                let strategy = &policy[&state.information_state_string(actor)];
+                state
+                    .legal_actions()
+                    .into_iter()
+                    .zip(strategy.iter().copied())
+                    .collect()
+            }
+        };
+        for (action, p) in children {
+            second_round(&state.child(action), reach * p, policy, roots);
+        }
+    }
+
+    #[test]
+    fn test_resolving_is_safe() {
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run(100).unwrap();
+        let blueprint = cfr.average_policy();
+
+        let mut roots = Vec::new();
+        second_round(&Leduc.new_initial_state(), 1.0, &blueprint, &mut roots);
+        let mut totals: HashMap<String, (Scalar, Scalar)> = HashMap::new();
+        for (state, belief) in &roots {
+            let entry = totals.entry(state.information_state_string(1)).or_default();
+            entry.0 += belief * state_value(state, &blueprint)[1];
+            entry.1 += belief;
+        }
+        let values = totals
+            .into_iter()
+            .map(|(k, (v, p))| (k, if p > 0.0 { v / p } else { 0.0 }))
+            .collect();
+
+        let config = ResolveConfig {
+            player: 0,
+            iterations: 200,
+        };
+        let resolved =
+            resolve_subgame::<_, VanillaCfrRegretMatcher>(&Leduc, &roots, &values, &config)
+                .unwrap();
+        assert!(!resolved.is_empty());
+        let mut combined = blueprint.clone();
+        combined.extend(resolved);
+
+        let before = best_response_value(&Leduc, &blueprint, 1);
+        let after = best_response_value(&Leduc, &combined, 1);
+        assert!(after <= before + 0.01, "{before} {after}");
+    }
+}
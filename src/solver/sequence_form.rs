@@ -0,0 +1,304 @@
+// `f64::from` does nothing when `Scalar` is already `f64`.
+#![allow(clippy::cast_possible_truncation, clippy::useless_conversion)]
+//! The sequence form of a two player game. A sequence is a player's
+//! information state and the action they take there, plus the empty
+//! sequence at the root. A strategy becomes a realization plan, the
+//! chance of playing every action along each sequence, and plans are
+//! exactly the non-negative vectors with `E x = e`, one constraint
+//! per infoset. The expected payoff is the bilinear `x^T A y`.
+//!
+//! With the `lp` feature a zero sum game in this form can be solved
+//! exactly as a linear program, to check what CFR converges to
+//! without relying on tolerances.
+use ndarray::prelude::*;
+
+use std::collections::HashMap;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::exploitability::Policy;
+use crate::games::game::{Game, Player, State};
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, PartialEq)]
+struct SequenceInfoSet {
+    key: String,
+    // The sequence leading to this infoset.
+    parent: usize,
+    // Its actions are sequences `first..first + num_actions`.
+    first: usize,
+    num_actions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceForm {
+    // Each player's infosets, in the order they were first reached
+    // so parents always come before their children.
+    infosets: [Vec<SequenceInfoSet>; 2],
+    num_sequences: [usize; 2],
+    // Player one's payoff, with chance folded in.
+    payoff: Array2<Scalar>,
+}
+
+impl SequenceForm {
+    /// Enumerate the whole tree of a two player `game`.
+    pub fn new<G: Game>(game: &G) -> Result<Self, LittleError> {
+        if game.num_players() != 2 {
+            return Err(LittleError::InvalidParameter(
+                "the sequence form needs a two player game",
+            ));
+        }
+        let mut form = Self {
+            infosets: [Vec::new(), Vec::new()],
+            num_sequences: [1, 1],
+            payoff: Array2::zeros((0, 0)),
+        };
+        let mut index = [HashMap::new(), HashMap::new()];
+        let mut payoffs = HashMap::new();
+        form.walk(
+            &game.new_initial_state(),
+            [0, 0],
+            1.0,
+            &mut index,
+            &mut payoffs,
+        );
+        form.payoff = Array2::zeros((form.num_sequences[0], form.num_sequences[1]));
+        for ((one, two), v) in payoffs {
+            form.payoff[[one, two]] = v;
+        }
+        Ok(form)
+    }
+
+    fn walk<S: State>(
+        &mut self,
+        state: &S,
+        sequences: [usize; 2],
+        chance: Scalar,
+        index: &mut [HashMap<String, usize>; 2],
+        payoffs: &mut HashMap<(usize, usize), Scalar>,
+    ) {
+        match state.current_player() {
+            Player::Terminal => {
+                *payoffs.entry((sequences[0], sequences[1])).or_default() +=
+                    chance * state.returns()[0];
+            }
+            Player::Chance => {
+                for (action, p) in state.chance_outcomes() {
+                    self.walk(&state.child(action), sequences, chance * p, index, payoffs);
+                }
+            }
+            Player::Id(player) => {
+                let key = state.information_state_string(player);
+                let actions = state.legal_actions();
+                let i = *index[player].entry(key.clone()).or_insert_with(|| {
+                    self.infosets[player].push(SequenceInfoSet {
+                        key,
+                        parent: sequences[player],
+                        first: self.num_sequences[player],
+                        num_actions: actions.len(),
+                    });
+                    self.num_sequences[player] += actions.len();
+                    self.infosets[player].len() - 1
+                });
+                let first = self.infosets[player][i].first;
+                for (a, action) in actions.into_iter().enumerate() {
+                    let mut child_sequences = sequences;
+                    child_sequences[player] = first + a;
+                    self.walk(
+                        &state.child(action),
+                        child_sequences,
+                        chance,
+                        index,
+                        payoffs,
+                    );
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn num_sequences(&self, player: usize) -> usize {
+        self.num_sequences[player]
+    }
+
+    /// Player one's expected payoff for each pair of sequences.
+    /// Player two's is the negation in a zero sum game.
+    #[must_use]
+    pub fn payoff(&self) -> ArrayView2<'_, Scalar> {
+        self.payoff.view()
+    }
+
+    /// The constraints `E x = e` that a realization plan for
+    /// `player` must satisfy, as `(E, e)`.
+    #[must_use]
+    pub fn constraints(&self, player: usize) -> (Array2<Scalar>, Array1<Scalar>) {
+        let infosets = &self.infosets[player];
+        let mut e = Array2::zeros((infosets.len() + 1, self.num_sequences[player]));
+        let mut rhs = Array1::zeros(infosets.len() + 1);
+        e[[0, 0]] = 1.0;
+        rhs[0] = 1.0;
+        for (row, infoset) in infosets.iter().enumerate() {
+            e[[row + 1, infoset.parent]] = -1.0;
+            e.slice_mut(s![
+                row + 1,
+                infoset.first..infoset.first + infoset.num_actions
+            ])
+            .fill(1.0);
+        }
+        (e, rhs)
+    }
+
+    /// The realization plan of `player` playing `policy`. Infosets
+    /// missing from the policy are played uniformly.
+    #[must_use]
+    pub fn realization_plan(&self, player: usize, policy: &Policy) -> Array1<Scalar> {
+        let mut plan = Array1::zeros(self.num_sequences[player]);
+        plan[0] = 1.0;
+        for infoset in &self.infosets[player] {
+            let reach = plan[infoset.parent];
+            for a in 0..infoset.num_actions {
+                let p = policy
+                    .get(&infoset.key)
+                    .map_or(1.0 / infoset.num_actions as Scalar, |s| s[a]);
+                plan[infoset.first + a] = reach * p;
+            }
+        }
+        plan
+    }
+
+    /// The behavior strategy of `player` that realizes `plan`.
+    /// Infosets the plan never reaches are played uniformly.
+    #[must_use]
+    pub fn policy(&self, player: usize, plan: ArrayView1<Scalar>) -> Policy {
+        self.infosets[player]
+            .iter()
+            .map(|infoset| {
+                let actions = plan.slice(s![infoset.first..infoset.first + infoset.num_actions]);
+                let total = actions.sum();
+                let strategy = if total > 0.0 {
+                    actions.iter().map(|p| p / total).collect()
+                } else {
+                    vec![1.0 / infoset.num_actions as Scalar; infoset.num_actions]
+                };
+                (infoset.key.clone(), strategy)
+            })
+            .collect()
+    }
+
+    /// Player one's expected payoff when the players follow the
+    /// plans `one` and `two`.
+    #[must_use]
+    pub fn value(&self, one: ArrayView1<Scalar>, two: ArrayView1<Scalar>) -> Scalar {
+        one.dot(&self.payoff.dot(&two))
+    }
+
+    /// An exact equilibrium of a zero sum game, found by linear
+    /// programming, and player one's value for it.
+    #[cfg(feature = "lp")]
+    pub fn solve(&self) -> Result<(Policy, Scalar), LittleError> {
+        let (one, value) = self.solve_player(0)?;
+        let (two, _) = self.solve_player(1)?;
+        let mut policy = self.policy(0, one.view());
+        policy.extend(self.policy(1, two.view()));
+        Ok((policy, value))
+    }
+
+    // The maxmin plan for `player` from the LP
+    //   max f^T q  s.t.  E x = e, x >= 0, F^T q <= A^T x
+    // where A is the player's payoff and F y = f the opponent's
+    // constraints, along with the value.
+    #[cfg(feature = "lp")]
+    fn solve_player(&self, player: usize) -> Result<(Array1<Scalar>, Scalar), LittleError> {
+        use minilp::{ComparisonOp, LinearExpr, OptimizationDirection, Problem};
+
+        let payoff = if player == 0 {
+            self.payoff.clone()
+        } else {
+            self.payoff.t().mapv(|v| -v)
+        };
+        let (e, e_rhs) = self.constraints(player);
+        let (f, _) = self.constraints(1 - player);
+
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x: Vec<_> = (0..e.ncols())
+            .map(|_| problem.add_var(0.0, (0.0, f64::INFINITY)))
+            .collect();
+        // Only the root constraint of the opponent has f = 1.
+        let q: Vec<_> = (0..f.nrows())
+            .map(|k| {
+                let objective = if k == 0 { 1.0 } else { 0.0 };
+                problem.add_var(objective, (f64::NEG_INFINITY, f64::INFINITY))
+            })
+            .collect();
+        for (row, rhs) in e.rows().into_iter().zip(&e_rhs) {
+            let mut expr = LinearExpr::empty();
+            for (var, coeff) in x.iter().zip(row) {
+                if *coeff != 0.0 {
+                    expr.add(*var, f64::from(*coeff));
+                }
+            }
+            problem.add_constraint(expr, ComparisonOp::Eq, f64::from(*rhs));
+        }
+        for (column, f_column) in payoff.columns().into_iter().zip(f.columns()) {
+            let mut expr = LinearExpr::empty();
+            for (var, coeff) in q.iter().zip(f_column) {
+                if *coeff != 0.0 {
+                    expr.add(*var, f64::from(*coeff));
+                }
+            }
+            for (var, coeff) in x.iter().zip(column) {
+                if *coeff != 0.0 {
+                    expr.add(*var, -f64::from(*coeff));
+                }
+            }
+            problem.add_constraint(expr, ComparisonOp::Le, 0.0);
+        }
+        let solution = problem.solve()?;
+        let plan = x.iter().map(|v| solution[*v] as Scalar).collect();
+        Ok((plan, solution.objective() as Scalar))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::exploitability::policy_value;
+    use crate::games::{GameCfr, Leduc};
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_matches_the_tree() {
+        let mut cfr = GameCfr::<_, VanillaCfrRegretMatcher>::new(Leduc);
+        cfr.run(10).unwrap();
+        let policy = cfr.average_policy();
+        let form = SequenceForm::new(&Leduc).unwrap();
+
+        let one = form.realization_plan(0, &policy);
+        let two = form.realization_plan(1, &policy);
+        let (e, rhs) = form.constraints(0);
+        for (lhs, rhs) in e.dot(&one).iter().zip(&rhs) {
+            assert!((lhs - rhs).abs() < 1e-5);
+        }
+        let value = policy_value(&Leduc, &policy)[0];
+        assert!((form.value(one.view(), two.view()) - value).abs() < 1e-4);
+
+        let back = form.policy(0, one.view());
+        for (key, strategy) in &back {
+            for (a, b) in strategy.iter().zip(&policy[key]) {
+                assert!((a - b).abs() < 1e-4, "{key}");
+            }
+        }
+    }
+
+    #[cfg(feature = "lp")]
+    #[test]
+    fn test_solve() {
+        use crate::games::exploitability::exploitability;
+
+        let form = SequenceForm::new(&Leduc).unwrap();
+        let (policy, value) = form.solve().unwrap();
+        assert!((value + 0.0856).abs() < 1e-3, "{value}");
+        assert!(exploitability(&Leduc, &policy) < 1e-3);
+    }
+}
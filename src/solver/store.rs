@@ -0,0 +1,137 @@
+//! A regret minimizer for every information state, keyed by
+//! `information_state_string`. Minimizers are created the first
+//! time a traversal reaches their infoset, so the store only ever
+//! holds the part of the game that has been visited.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::hash_map::{self, HashMap};
+use std::fmt;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InfoSetStore<M: RegretMinimizer> {
+    infosets: HashMap<String, M>,
+}
+
+impl<M: RegretMinimizer> Default for InfoSetStore<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: RegretMinimizer> InfoSetStore<M> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            infosets: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.infosets.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.infosets.is_empty()
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&M> {
+        self.infosets.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut M> {
+        self.infosets.get_mut(key)
+    }
+
+    /// The minimizer for `key`, creating one with `num_actions`
+    /// experts if the infoset hasn't been seen before.
+    pub fn get_or_insert(&mut self, key: &str, num_actions: usize) -> Result<&mut M, LittleError> {
+        self.get_or_insert_with(key, || M::new(num_actions))
+    }
+
+    /// The minimizer for `key`, creating one with `new` if the
+    /// infoset hasn't been seen before.
+    pub fn get_or_insert_with<F>(&mut self, key: &str, new: F) -> Result<&mut M, LittleError>
+    where
+        F: FnOnce() -> Result<M, LittleError>,
+    {
+        if !self.infosets.contains_key(key) {
+            self.infosets.insert(key.into(), new()?);
+        }
+        Ok(self.infosets.get_mut(key).unwrap())
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, String, M> {
+        self.infosets.iter()
+    }
+
+    /// The average strategy at every infoset. Entries line up with
+    /// the state's `legal_actions`.
+    #[must_use]
+    pub fn average_policy(&self) -> HashMap<String, Vec<Scalar>> {
+        self.infosets
+            .iter()
+            .map(|(k, m)| (k.clone(), m.best_weight()))
+            .collect()
+    }
+}
+
+/// Builds the minimizer for a newly seen infoset from its number
+/// of actions, for minimizers that need more than `new`, like
+/// discounting with chosen parameters.
+pub struct MinimizerFactory<M>(Arc<dyn Fn(usize) -> Result<M, LittleError> + Send + Sync>);
+
+impl<M> MinimizerFactory<M> {
+    pub fn new(f: impl Fn(usize) -> Result<M, LittleError> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn build(&self, num_actions: usize) -> Result<M, LittleError> {
+        (self.0)(num_actions)
+    }
+}
+
+impl<M> Clone for MinimizerFactory<M> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<M> fmt::Debug for MinimizerFactory<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinimizerFactory").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_get_or_insert() {
+        let mut store = InfoSetStore::<VanillaCfrRegretMatcher>::new();
+        store
+            .get_or_insert("a", 2)
+            .unwrap()
+            .update_regret(&[1.0, 0.0])
+            .unwrap();
+        // The existing minimizer is returned rather than replaced.
+        assert_eq!(store.get_or_insert("a", 2).unwrap().num_updates(), 1);
+        store.get_or_insert("b", 3).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.average_policy()["b"].len(), 3);
+        assert!(store.get("c").is_none());
+    }
+}
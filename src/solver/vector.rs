@@ -0,0 +1,292 @@
+#![allow(clippy::cast_precision_loss)]
+//! Vector form CFR for two player games where the only private
+//! information is each player's hand. The traversal walks the public
+//! tree once per player, carrying each player's reach for every hand
+//! they could hold as an array, and returns the counterfactual value
+//! of every hand at once. Terminal nodes are evaluated range against
+//! range, so a deal is never enumerated and the cost of a traversal
+//! is the size of the public tree rather than of the full game.
+//!
+//! Public chance, like a board card, is enumerated with its
+//! probability folded into the opponent's reach. Private deals and
+//! card removal are the game's business in `terminal_values`.
+use ndarray::prelude::*;
+
+use std::collections::HashMap;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::errors::LittleError;
+use crate::games::game::Action;
+use crate::regret_minimizer::{RegretMinimizer, UpdateOptions};
+use crate::scalar::Scalar;
+
+/// What happens at a node of the public tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublicNode {
+    Terminal,
+    /// Public chance outcomes with their probabilities.
+    Chance(Vec<(Action, Scalar)>),
+    /// `player` acts, whatever hand they hold.
+    Player {
+        player: usize,
+        actions: Vec<Action>,
+    },
+}
+
+pub trait RangeGame {
+    type PublicState: Clone;
+
+    /// How many private hands `player`, zero or one, could hold.
+    fn num_hands(&self, player: usize) -> usize;
+
+    fn root(&self) -> Self::PublicState;
+
+    fn node(&self, state: &Self::PublicState) -> PublicNode;
+
+    fn child(&self, state: &Self::PublicState, action: Action) -> Self::PublicState;
+
+    /// Identifies the public state. A player's infoset is this and
+    /// their hand.
+    fn public_key(&self, state: &Self::PublicState) -> String;
+
+    /// At a terminal state, the value to `player` of each of their
+    /// hands against an opponent who reaches it with each of theirs
+    /// with probability `opponent_reach`, weighted by how likely each
+    /// pair of hands is to be dealt.
+    fn terminal_values(
+        &self,
+        state: &Self::PublicState,
+        player: usize,
+        opponent_reach: ArrayView1<Scalar>,
+    ) -> Array1<Scalar>;
+}
+
+#[derive(Debug, Clone)]
+pub struct VectorCfr<G: RangeGame, M: RegretMinimizer> {
+    game: G,
+    // A minimizer for each hand at each public state.
+    infosets: HashMap<String, Vec<M>>,
+    num_iterations: usize,
+}
+
+impl<G: RangeGame, M: RegretMinimizer> VectorCfr<G, M> {
+    pub fn new(game: G) -> Self {
+        Self {
+            game,
+            infosets: HashMap::new(),
+            num_iterations: 0,
+        }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn num_iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    /// The average strategy of each hand at the public state with
+    /// `public_key`, one row per hand.
+    #[must_use]
+    pub fn average_strategy(&self, public_key: &str) -> Option<Array2<Scalar>> {
+        let matchers = self.infosets.get(public_key)?;
+        let rows: Vec<Vec<Scalar>> = matchers.iter().map(RegretMinimizer::best_weight).collect();
+        let mut strategy = Array2::zeros((rows.len(), rows[0].len()));
+        for (mut row, r) in strategy.rows_mut().into_iter().zip(&rows) {
+            row.assign(&ArrayView1::from(r));
+        }
+        Some(strategy)
+    }
+
+    /// Run one iteration, updating each player in turn, and return
+    /// each player's expected value before their update.
+    pub fn iterate(&mut self) -> Result<[Scalar; 2], LittleError> {
+        let root = self.game.root();
+        let mut values = [0.0; 2];
+        for (player, value) in values.iter_mut().enumerate() {
+            let reach = [
+                Array1::ones(self.game.num_hands(0)),
+                Array1::ones(self.game.num_hands(1)),
+            ];
+            *value = self.traverse(&root, player, &reach)?.sum();
+        }
+        self.num_iterations += 1;
+        Ok(values)
+    }
+
+    pub fn run(&mut self, iterations: usize) -> Result<(), LittleError> {
+        for _i in 0..iterations {
+            self.iterate()?;
+        }
+        Ok(())
+    }
+
+    // The current strategy of every hand at `key`, one row per hand.
+    fn strategy(
+        &mut self,
+        key: &str,
+        num_hands: usize,
+        num_actions: usize,
+    ) -> Result<Array2<Scalar>, LittleError> {
+        if !self.infosets.contains_key(key) {
+            let matchers = (0..num_hands)
+                .map(|_| M::new(num_actions))
+                .collect::<Result<_, _>>()?;
+            self.infosets.insert(key.into(), matchers);
+        }
+        let mut strategy = Array2::zeros((num_hands, num_actions));
+        for (mut row, m) in strategy.rows_mut().into_iter().zip(&self.infosets[key]) {
+            row.assign(&ArrayView1::from(m.current_strategy()));
+        }
+        Ok(strategy)
+    }
+
+    // The counterfactual value to `player` of each of their hands.
+    fn traverse(
+        &mut self,
+        state: &G::PublicState,
+        player: usize,
+        reach: &[Array1<Scalar>; 2],
+    ) -> Result<Array1<Scalar>, LittleError> {
+        let opponent = 1 - player;
+        match self.game.node(state) {
+            PublicNode::Terminal => {
+                Ok(self
+                    .game
+                    .terminal_values(state, player, reach[opponent].view()))
+            }
+            PublicNode::Chance(outcomes) => {
+                let mut value = Array1::zeros(self.game.num_hands(player));
+                for (action, prob) in outcomes {
+                    let mut child_reach = reach.clone();
+                    child_reach[opponent] *= prob;
+                    value +=
+                        &self.traverse(&self.game.child(state, action), player, &child_reach)?;
+                }
+                Ok(value)
+            }
+            PublicNode::Player {
+                player: actor,
+                actions,
+            } => {
+                let key = self.game.public_key(state);
+                let strategy = self.strategy(&key, self.game.num_hands(actor), actions.len())?;
+                let mut action_values = Array2::zeros((self.game.num_hands(player), actions.len()));
+                for (i, action) in actions.iter().enumerate() {
+                    let mut child_reach = reach.clone();
+                    child_reach[actor] *= &strategy.column(i);
+                    let v =
+                        self.traverse(&self.game.child(state, *action), player, &child_reach)?;
+                    action_values.column_mut(i).assign(&v);
+                }
+                if actor != player {
+                    return Ok(action_values.sum_axis(Axis(1)));
+                }
+                let value = (&action_values * &strategy).sum_axis(Axis(1));
+                let matchers = self.infosets.get_mut(&key).unwrap();
+                for ((m, values), own_reach) in matchers
+                    .iter_mut()
+                    .zip(action_values.rows())
+                    .zip(&reach[player])
+                {
+                    let options = UpdateOptions {
+                        average_weight: *own_reach,
+                        ..UpdateOptions::default()
+                    };
+                    m.update_regret_with_options(&values.to_vec(), options)?;
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    // Kuhn poker, where each hand is one of the three cards and the
+    // public state is the betting.
+    struct RangeKuhn;
+
+    impl RangeGame for RangeKuhn {
+        type PublicState = String;
+
+        fn num_hands(&self, _player: usize) -> usize {
+            3
+        }
+
+        fn root(&self) -> String {
+            String::new()
+        }
+
+        fn node(&self, state: &String) -> PublicNode {
+            match state.as_str() {
+                "" | "p" | "b" | "pb" => PublicNode::Player {
+                    player: state.len() % 2,
+                    actions: vec![0, 1],
+                },
+                _ => PublicNode::Terminal,
+            }
+        }
+
+        fn child(&self, state: &String, action: Action) -> String {
+            format!("{state}{}", ['p', 'b'][action])
+        }
+
+        fn public_key(&self, state: &String) -> String {
+            state.clone()
+        }
+
+        fn terminal_values(
+            &self,
+            state: &String,
+            player: usize,
+            opponent_reach: ArrayView1<Scalar>,
+        ) -> Array1<Scalar> {
+            Array1::from_shape_fn(3, |hand| {
+                let mut value = 0.0;
+                for (other, reach) in opponent_reach.iter().enumerate() {
+                    if other == hand {
+                        continue;
+                    }
+                    let cards = if player == 0 {
+                        (hand, other)
+                    } else {
+                        (other, hand)
+                    };
+                    let showdown = if cards.0 > cards.1 { 1.0 } else { -1.0 };
+                    // Player one's winnings.
+                    let won = match state.as_str() {
+                        "pp" => showdown,
+                        "bp" => 1.0,
+                        "pbp" => -1.0,
+                        _ => 2.0 * showdown,
+                    };
+                    let won = if player == 0 { won } else { -won };
+                    value += reach * won / 6.0;
+                }
+                value
+            })
+        }
+    }
+
+    #[test]
+    fn test_kuhn() {
+        let mut cfr = VectorCfr::<_, VanillaCfrRegretMatcher>::new(RangeKuhn);
+        cfr.run(2_000).unwrap();
+        let first = cfr.average_strategy("").unwrap();
+        let alpha = first[[0, 1]];
+        assert!(alpha < 0.36);
+        assert!((first[[2, 1]] - 3.0 * alpha).abs() < 0.05);
+        assert!(first[[1, 1]] < 0.05);
+        let after_check = cfr.average_strategy("p").unwrap();
+        assert!((after_check[[0, 1]] - 1.0 / 3.0).abs() < 0.05);
+        let [one, two] = cfr.iterate().unwrap();
+        assert!((one + 1.0 / 18.0).abs() < 0.01, "{one}");
+        assert!((two - 1.0 / 18.0).abs() < 0.01, "{two}");
+    }
+}
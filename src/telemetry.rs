@@ -0,0 +1,130 @@
+#![allow(clippy::cast_precision_loss)]
+//! Operational metrics for long running solves, reported through
+//! the `metrics` facade so any exporter can pick them up. Install
+//! something like `metrics-exporter-prometheus` in the binary and
+//! call `observe` every so often from the training loop. Every
+//! metric carries a `matcher` label so several solves can share a
+//! process.
+use metrics::{counter, gauge};
+
+use std::string::String;
+use std::time::Instant;
+
+use crate::regret_bound::RegretBoundTracker;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::scalar::Scalar;
+
+/// Total number of regret updates.
+pub const UPDATES_TOTAL: &str = "little_sorry_updates_total";
+/// Updates per second since the previous observation.
+pub const UPDATES_PER_SECOND: &str = "little_sorry_updates_per_second";
+/// The L2 norm of the cumulative regrets.
+pub const REGRET_NORM: &str = "little_sorry_regret_norm";
+/// The largest cumulative regret.
+pub const MAX_REGRET: &str = "little_sorry_max_regret";
+/// The most recently measured exploitability.
+pub const EXPLOITABILITY: &str = "little_sorry_exploitability";
+/// Average regret measured by a `RegretBoundTracker`.
+pub const OBSERVED_REGRET: &str = "little_sorry_observed_regret";
+/// The theoretical bound on that regret.
+pub const REGRET_BOUND: &str = "little_sorry_regret_bound";
+
+#[derive(Debug, Clone)]
+pub struct MatcherMetrics {
+    label: String,
+    last_updates: usize,
+    last_at: Instant,
+}
+
+impl MatcherMetrics {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            last_updates: 0,
+            last_at: Instant::now(),
+        }
+    }
+
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Report the update count, the update rate since the last call
+    /// and the size of `matcher`'s regrets.
+    pub fn observe<M: RegretMinimizer>(&mut self, matcher: &M) {
+        let labels = [("matcher", self.label.clone())];
+        let updates = matcher.num_updates();
+        let now = Instant::now();
+        let new_updates = updates.saturating_sub(self.last_updates);
+        counter!(UPDATES_TOTAL, &labels).increment(new_updates as u64);
+
+        let elapsed = now.duration_since(self.last_at).as_secs_f64();
+        if elapsed > 0.0 {
+            gauge!(UPDATES_PER_SECOND, &labels).set(new_updates as f64 / elapsed);
+        }
+
+        let regrets = matcher.cumulative_regrets();
+        let norm: Scalar = regrets.iter().map(|r| r * r).sum::<Scalar>().sqrt();
+        let max = regrets
+            .iter()
+            .copied()
+            .fold(Scalar::NEG_INFINITY, Scalar::max);
+        gauge!(REGRET_NORM, &labels).set(norm);
+        gauge!(MAX_REGRET, &labels).set(max);
+
+        self.last_updates = updates;
+        self.last_at = now;
+    }
+
+    pub fn observe_exploitability(&self, exploitability: Scalar) {
+        gauge!(EXPLOITABILITY, "matcher" => self.label.clone()).set(exploitability);
+    }
+
+    /// Report the observed regret next to its theoretical bound.
+    pub fn observe_regret_bound(&self, tracker: &RegretBoundTracker) {
+        let labels = [("matcher", self.label.clone())];
+        gauge!(OBSERVED_REGRET, &labels).set(tracker.observed_regret());
+        gauge!(REGRET_BOUND, &labels).set(tracker.theoretical_bound());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_bound::RegretBound;
+    use crate::regret_matcher::RegretMatcher;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_observe() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let mut m = RegretMatcher::new(2).unwrap();
+            let mut metrics = MatcherMetrics::new("rps");
+            let mut tracker = RegretBoundTracker::new(RegretBound::RegretMatching, 2).unwrap();
+            for _i in 0..3 {
+                tracker.update(&mut m, &[3.0, -1.0]).unwrap();
+            }
+            metrics.observe(&m);
+            metrics.observe_regret_bound(&tracker);
+            m.update_regret(&[3.0, -1.0]).unwrap();
+            metrics.observe(&m);
+            metrics.observe_exploitability(0.5);
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value = |name: &str| {
+            &snapshot
+                .iter()
+                .find(|(k, _, _, _)| k.key().name() == name)
+                .unwrap()
+                .3
+        };
+        assert_eq!(value(UPDATES_TOTAL), &DebugValue::Counter(4));
+        assert_eq!(value(EXPLOITABILITY), &DebugValue::Gauge(0.5.into()));
+        assert!(matches!(value(REGRET_NORM), DebugValue::Gauge(v) if v.0 > 0.0));
+        assert!(matches!(value(REGRET_BOUND), DebugValue::Gauge(v) if v.0 > 0.0));
+    }
+}
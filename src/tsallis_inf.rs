@@ -0,0 +1,199 @@
+#![allow(clippy::cast_precision_loss)]
+//! Tsallis-INF. Online mirror descent with the 1/2-Tsallis
+//! entropy as the regularizer, for bandit feedback. It has the
+//! optimal regret rate in the adversarial setting and at the
+//! same time logarithmic regret when rewards are stochastic, so
+//! there is no need to know which regime you are in.
+use ndarray::prelude::*;
+use rand::distributions::Distribution;
+use rand::{thread_rng, Rng};
+
+use std::vec::Vec;
+
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, BanditMinimizer};
+use crate::scalar::Scalar;
+
+// Newton's method converges quickly; this is just a backstop.
+const MAX_NEWTON_STEPS: usize = 50;
+const NEWTON_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct TsallisInfRegretMatcher {
+    // Importance weighted estimate of each action's total loss.
+    estimated_loss: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: Array1<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl TsallisInfRegretMatcher {
+    pub fn new(num_experts: usize) -> Result<Self, LittleError> {
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            estimated_loss: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: Array1::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    /// A matcher that starts out playing `strategy`, as if it had
+    /// already been played once. Actions the strategy never plays
+    /// start with a huge but finite loss so they can still recover.
+    // With the f64 feature Scalar is already f64.
+    #[allow(clippy::useless_conversion)]
+    pub fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.num_updates = 1;
+        // Invert p_i = 4 / (eta * (L_i - x))^2 with x = 0.
+        let eta = Self::eta(matcher.num_updates);
+        matcher.estimated_loss = strategy
+            .iter()
+            .map(|p| (2.0 / (eta * f64::from(p.max(Scalar::MIN_POSITIVE)).sqrt())) as Scalar)
+            .collect();
+        matcher.solve_strategy(eta);
+        matcher.dist.set_weights(matcher.p.as_slice().unwrap())?;
+        matcher.sum_p = Array1::from(strategy);
+        Ok(matcher)
+    }
+
+    /// A matcher whose estimated losses start out as `-regrets`.
+    pub fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.estimated_loss = regrets.iter().map(|r| -r).collect();
+        matcher.solve_strategy(Self::eta(0));
+        matcher.dist.set_weights(matcher.p.as_slice().unwrap())?;
+        Ok(matcher)
+    }
+
+    // The learning rate for the round after `num_updates` updates.
+    fn eta(num_updates: usize) -> f64 {
+        2.0 / ((num_updates + 1) as f64).sqrt()
+    }
+
+    pub fn next_action(&self) -> usize {
+        self.dist.sample(&mut thread_rng())
+    }
+
+    /// Update with bandit feedback. `reward` is the reward received
+    /// for playing `action` and should be in `[0, 1]`.
+    pub fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        regret_minimizer::check_action(action, self.p.len())?;
+        self.sum_p += &self.p;
+        self.num_updates += 1;
+
+        let loss = 1.0 - reward;
+        self.estimated_loss[action] += loss / self.p[action];
+
+        self.solve_strategy(Self::eta(self.num_updates));
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    // The new strategy is p_i = 4 / (eta * (L_i - x))^2 where x is
+    // the normalizing constant that makes p sum to one. There's no
+    // closed form for x so find it with Newton's method.
+    // With the f64 feature Scalar is already f64.
+    #[allow(clippy::useless_conversion)]
+    fn solve_strategy(&mut self, eta: f64) {
+        let losses: Vec<f64> = self.estimated_loss.iter().map(|l| f64::from(*l)).collect();
+        let min_loss = losses.iter().fold(f64::INFINITY, |acc, l| acc.min(*l));
+        // Starting here puts the best action at p = 1 so the
+        // sum starts above one and decreases monotonically.
+        let mut x = min_loss - 2.0 / eta;
+        let mut weights = vec![0.0_f64; losses.len()];
+        for _step in 0..MAX_NEWTON_STEPS {
+            let mut sum = 0.0;
+            let mut slope = 0.0;
+            for (w, l) in weights.iter_mut().zip(&losses) {
+                *w = 4.0 / (eta * (l - x)).powi(2);
+                sum += *w;
+                slope += w.powf(1.5);
+            }
+            let step = (sum - 1.0) / (eta * slope);
+            x -= step;
+            if step.abs() < NEWTON_TOLERANCE {
+                break;
+            }
+        }
+        // Renormalize away whatever error is left over.
+        let total: f64 = weights.iter().sum();
+        for (p, w) in self.p.iter_mut().zip(&weights) {
+            *p = (w / total) as Scalar;
+        }
+    }
+
+    #[must_use]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        if self.num_updates == 0 {
+            return self.p.to_vec();
+        }
+        (self.sum_p.clone() / self.num_updates as Scalar).to_vec()
+    }
+}
+
+impl BanditMinimizer for TsallisInfRegretMatcher {
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        self.dist.sample(rng)
+    }
+
+    fn update_sampled(&mut self, action: usize, reward: Scalar) -> Result<(), LittleError> {
+        Self::update_sampled(self, action, reward)
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        Self::best_weight(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategy_sums_to_one() {
+        let mut m = TsallisInfRegretMatcher::new(4).unwrap();
+        m.update_sampled(2, 0.0).unwrap();
+        m.update_sampled(1, 1.0).unwrap();
+        let total: Scalar = m.p.sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let m = TsallisInfRegretMatcher::warm_start_from_strategy(&[3.0, 1.0, 0.0]).unwrap();
+        assert_eq!(m.best_weight(), vec![0.75, 0.25, 0.0]);
+        for (p, expected) in m.p.iter().zip([0.75, 0.25, 0.0]) {
+            assert!((p - expected).abs() < 1e-4);
+        }
+        assert!(TsallisInfRegretMatcher::warm_start_from_strategy(&[]).is_err());
+        let m = TsallisInfRegretMatcher::warm_start_from_regrets(&[5.0, 0.0]).unwrap();
+        assert!(m.p[0] > m.p[1]);
+        assert!((m.p.sum() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_finds_best_arm() {
+        let mut m = TsallisInfRegretMatcher::new(3).unwrap();
+        for _i in 0..5_000 {
+            let a = m.next_action();
+            let reward = if a == 0 { 0.8 } else { 0.2 };
+            m.update_sampled(a, reward).unwrap();
+        }
+        let w = m.best_weight();
+        assert!(w[0] > w[1]);
+        assert!(w[0] > w[2]);
+    }
+}
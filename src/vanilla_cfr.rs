@@ -0,0 +1,263 @@
+#![allow(clippy::cast_precision_loss)]
+//! Plain regret matching as used by the original CFR paper.
+//! Regrets are summed with their sign, never floored or
+//! discounted, and every iteration counts the same towards
+//! the average. This is the baseline the other variants
+//! are measured against.
+use ndarray::prelude::*;
+use rand::Rng;
+use smallvec::SmallVec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::kernels;
+use crate::regret_minimizer::{self, MatcherState, RegretMinimizer, StateTransfer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+use crate::small::Inline;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VanillaCfrRegretMatcher {
+    // The chance each expert has of being chosen
+    p: Inline<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // Signed regret for not having played each expert.
+    cumulative_regret: CompensatedArray<Scalar>,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl VanillaCfrRegretMatcher {
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        // The average is over the strategies that were actually played.
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                ArrayView1::from(&self.p[..]),
+                options.average_weight,
+            );
+        }
+
+        let expected = kernels::dot_view(ArrayView1::from(&self.p[..]), reward_array);
+        for (i, r) in reward_array.iter().enumerate() {
+            self.cumulative_regret
+                .add(i, options.regret_weight * (r - expected));
+        }
+
+        regret_minimizer::regret_match(self.cumulative_regret.as_slice(), &mut self.p);
+        self.dist.set_weights(&self.p)?;
+        Ok(())
+    }
+}
+
+impl RegretMinimizer for VanillaCfrRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            p: SmallVec::from_vec(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            cumulative_regret: CompensatedArray::zeros(num_experts),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        &self.p
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.cumulative_regret.values().to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.cumulative_regret.fill(0.0);
+        regret_minimizer::fill_uniform(&mut self.p);
+        self.dist.set_weights(&self.p)?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(strategy.len())?;
+        matcher.import_state(&MatcherState::from_strategy(strategy)?)?;
+        Ok(matcher)
+    }
+
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        matcher.import_state(&MatcherState::from_regrets(regrets))?;
+        Ok(matcher)
+    }
+}
+
+impl StateTransfer for VanillaCfrRegretMatcher {
+    fn export_state(&self) -> MatcherState {
+        MatcherState {
+            num_updates: self.num_updates,
+            cumulative_regret: self.cumulative_regret.values().to_vec(),
+            cumulative_strategy: scalar::narrow(self.sum_p.values()),
+        }
+    }
+
+    fn import_state(&mut self, state: &MatcherState) -> Result<(), LittleError> {
+        regret_minimizer::check_state(state, self.p.len())?;
+        self.num_updates = state.num_updates;
+        self.cumulative_regret =
+            CompensatedArray::from(Array1::from(state.cumulative_regret.clone()));
+        self.sum_p = CompensatedArray::from(scalar::widen(&state.cumulative_strategy));
+        regret_minimizer::regret_match(self.cumulative_regret.as_slice(), &mut self.p);
+        self.dist.set_weights(&self.p)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small::INLINE_EXPERTS;
+
+    #[test]
+    fn test_inline() {
+        let small = VanillaCfrRegretMatcher::new(INLINE_EXPERTS).unwrap();
+        assert!(!small.p.spilled());
+        let large = VanillaCfrRegretMatcher::new(INLINE_EXPERTS + 1).unwrap();
+        assert!(large.p.spilled());
+    }
+
+    #[test]
+    fn test_signed_regret() {
+        let mut m = VanillaCfrRegretMatcher::new(2).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+        // Regret for the second action is now negative and
+        // stays that way until it is paid back. CFR+ would have
+        // floored it to zero and started playing it here.
+        m.update_regret(&[0.0, 0.25]).unwrap();
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_rps_average() {
+        let payoff = array![[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let mut one = VanillaCfrRegretMatcher::new(3).unwrap();
+        let mut two = VanillaCfrRegretMatcher::new(3).unwrap();
+        one.update_regret(&[1.0, 0.0, 0.0]).unwrap();
+        for _i in 0..10_000 {
+            let r1 = payoff.dot(&ArrayView1::from(two.current_strategy()));
+            let r2 = payoff.dot(&ArrayView1::from(one.current_strategy()));
+            one.update_regret(r1.as_slice().unwrap()).unwrap();
+            two.update_regret(r2.as_slice().unwrap()).unwrap();
+        }
+        for w in one.best_weight() {
+            assert!((w - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_last_iterate_averaging() {
+        let mut m = VanillaCfrRegretMatcher::new(2)
+            .unwrap()
+            .with_averaging(AveragingScheme::LastIterate);
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        m.update_regret(&[1.0, 0.0]).unwrap();
+        // Only the strategy played on the last update counts.
+        assert_eq!(m.best_weight(), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_weighted_update() {
+        let mut weighted = VanillaCfrRegretMatcher::new(2).unwrap();
+        weighted.update_regret_weighted(&[1.0, 0.0], 3.0).unwrap();
+        let mut scaled = VanillaCfrRegretMatcher::new(2).unwrap();
+        scaled.update_regret(&[3.0, 0.0]).unwrap();
+        assert_eq!(weighted.export_state(), scaled.export_state());
+        assert!(weighted.update_regret_weighted(&[1.0, 0.0], -1.0).is_err());
+    }
+
+    #[test]
+    fn test_masked_update() {
+        let mut m = VanillaCfrRegretMatcher::new(3).unwrap();
+        let mask = [true, false, true];
+        m.update_regret_masked(&[0.0, 10.0, 1.0], &mask).unwrap();
+        // The illegal action's huge reward earns it no regret.
+        assert_eq!(m.current_strategy(), &[0.0, 0.0, 1.0]);
+        let mut rng = rand::thread_rng();
+        for _i in 0..100 {
+            assert_ne!(m.next_action_masked(&mut rng, &mask).unwrap(), 1);
+        }
+        // Only the masked strategy was averaged, uniform start and all.
+        m.update_regret_masked(&[0.0, 10.0, 1.0], &mask).unwrap();
+        assert_eq!(m.best_weight()[1], 0.0);
+        assert!(m.update_regret_masked(&[0.0, 1.0], &mask).is_err());
+        assert!(m
+            .update_regret_masked(&[0.0, 1.0, 1.0, 1.0], &mask)
+            .is_err());
+    }
+}
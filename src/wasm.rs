@@ -0,0 +1,153 @@
+//! JavaScript bindings built with wasm-bindgen. Every object owns
+//! a seeded rng so a page can replay exactly the same run, and so
+//! nothing here depends on an entropy source in the browser.
+//!
+//! The crate is only built as an rlib, so ask for the cdylib when
+//! building for the browser and run wasm-bindgen over the result:
+//!
+//! ```text
+//! cargo rustc --release --lib --target wasm32-unknown-unknown \
+//!     --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/little_sorry.wasm
+//! ```
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use std::vec::Vec;
+
+use crate::dcfr::DiscountedRegretMatcher;
+use crate::discount::DiscountParams;
+use crate::errors::LittleError;
+use crate::regret_matcher::RegretMatcher;
+use crate::regret_minimizer::RegretMinimizer;
+use crate::rps::RPSRunner;
+use crate::scalar::Scalar;
+
+fn to_js(err: LittleError) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+#[wasm_bindgen(js_name = RegretMatcher)]
+pub struct WasmRegretMatcher {
+    inner: RegretMatcher,
+    rng: StdRng,
+}
+
+#[wasm_bindgen(js_class = RegretMatcher)]
+impl WasmRegretMatcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_experts: usize, seed: u64) -> Result<WasmRegretMatcher, JsError> {
+        Ok(Self {
+            inner: RegretMatcher::new(num_experts).map_err(to_js)?,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    #[wasm_bindgen(js_name = nextAction)]
+    pub fn next_action(&mut self) -> usize {
+        RegretMinimizer::next_action(&self.inner, &mut self.rng)
+    }
+
+    #[wasm_bindgen(js_name = updateRegret)]
+    pub fn update_regret(&mut self, rewards: &[Scalar]) -> Result<(), JsError> {
+        self.inner.update_regret(rewards).map_err(to_js)
+    }
+
+    #[wasm_bindgen(js_name = currentStrategy)]
+    pub fn current_strategy(&self) -> Vec<Scalar> {
+        self.inner.current_strategy().to_vec()
+    }
+
+    #[wasm_bindgen(js_name = bestWeight)]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+}
+
+#[wasm_bindgen(js_name = DiscountedRegretMatcher)]
+pub struct WasmDiscountedRegretMatcher {
+    inner: DiscountedRegretMatcher,
+    rng: StdRng,
+}
+
+#[wasm_bindgen(js_class = DiscountedRegretMatcher)]
+impl WasmDiscountedRegretMatcher {
+    /// DCFR with the recommended parameters.
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_experts: usize, seed: u64) -> Result<WasmDiscountedRegretMatcher, JsError> {
+        Ok(Self {
+            inner: DiscountedRegretMatcher::recommended(num_experts).map_err(to_js)?,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    #[wasm_bindgen(js_name = withParams)]
+    pub fn with_params(
+        num_experts: usize,
+        alpha: Scalar,
+        beta: Scalar,
+        gamma: Scalar,
+        seed: u64,
+    ) -> Result<WasmDiscountedRegretMatcher, JsError> {
+        let params = DiscountParams::new(alpha, beta, gamma).map_err(to_js)?;
+        Ok(Self {
+            inner: DiscountedRegretMatcher::new_with_params(num_experts, params).map_err(to_js)?,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    #[wasm_bindgen(js_name = nextAction)]
+    pub fn next_action(&mut self) -> usize {
+        RegretMinimizer::next_action(&self.inner, &mut self.rng)
+    }
+
+    #[wasm_bindgen(js_name = updateRegret)]
+    pub fn update_regret(&mut self, rewards: &[Scalar]) -> Result<(), JsError> {
+        self.inner.update_regret(rewards).map_err(to_js)
+    }
+
+    #[wasm_bindgen(js_name = currentStrategy)]
+    pub fn current_strategy(&self) -> Vec<Scalar> {
+        self.inner.current_strategy().to_vec()
+    }
+
+    #[wasm_bindgen(js_name = bestWeight)]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+}
+
+#[wasm_bindgen(js_name = RPSRunner)]
+pub struct WasmRpsRunner {
+    inner: RPSRunner,
+    rng: StdRng,
+}
+
+#[wasm_bindgen(js_class = RPSRunner)]
+impl WasmRpsRunner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> Result<WasmRpsRunner, JsError> {
+        Ok(Self {
+            inner: RPSRunner::new().map_err(to_js)?,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// Play and update `iterations` times. Call this from an
+    /// animation frame to watch the average strategy converge.
+    pub fn run(&mut self, iterations: usize) -> Result<(), JsError> {
+        for _i in 0..iterations {
+            self.inner.run_one_with_rng(&mut self.rng);
+            self.inner.update_regret().map_err(to_js)?;
+        }
+        Ok(())
+    }
+
+    /// Player one's average strategy.
+    #[wasm_bindgen(js_name = bestWeight)]
+    pub fn best_weight(&self) -> Vec<Scalar> {
+        self.inner.best_weight()
+    }
+}
@@ -0,0 +1,228 @@
+#![allow(clippy::cast_precision_loss)]
+//! Regret matching over a sliding window. Only the instantaneous
+//! regrets from the most recent `window` updates count, so when
+//! the opponent adapts the strategy follows rather than staying
+//! anchored to what used to work.
+use ndarray::prelude::*;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+use crate::averaging::AveragingScheme;
+use crate::compensated::CompensatedArray;
+use crate::dist::StrategyDist;
+use crate::errors::LittleError;
+use crate::regret_minimizer::{self, RegretMinimizer, UpdateOptions};
+use crate::scalar::{self, Accumulator, Scalar};
+
+pub(crate) const DEFAULT_WINDOW: usize = 100;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowedRegretMatcher {
+    // Ring buffer with one row of instantaneous regret per update.
+    history: Array2<Scalar>,
+    // Next row of the ring buffer to write.
+    head: usize,
+    // Sum of all the rows in the ring buffer.
+    window_regret: Array1<Scalar>,
+    // The chance each expert has of being chosen
+    p: Array1<Scalar>,
+    sum_p: CompensatedArray<Accumulator>,
+    // How the strategies played are weighted in the average.
+    averaging: AveragingScheme,
+    // The distribution that generates actions.
+    dist: StrategyDist,
+    num_updates: usize,
+}
+
+impl WindowedRegretMatcher {
+    pub fn new_with_params(num_experts: usize, window: usize) -> Result<Self, LittleError> {
+        if window == 0 {
+            return Err(LittleError::InvalidParameter("window must be positive"));
+        }
+        let p = regret_minimizer::uniform(num_experts);
+        let dist = regret_minimizer::build_dist(&p)?;
+        Ok(Self {
+            history: Array2::zeros((window, num_experts)),
+            head: 0,
+            window_regret: Array1::zeros(num_experts),
+            p: Array1::from(p),
+            sum_p: CompensatedArray::zeros(num_experts),
+            averaging: AveragingScheme::default(),
+            dist,
+            num_updates: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn window(&self) -> usize {
+        self.history.nrows()
+    }
+
+    fn update_view(
+        &mut self,
+        reward_array: ArrayView1<Scalar>,
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        self.num_updates += 1;
+        if options.contribute_to_average {
+            self.averaging.accumulate_weighted(
+                self.num_updates,
+                &mut self.sum_p,
+                self.p.view(),
+                options.average_weight,
+            );
+        }
+
+        let expected = self.p.dot(&reward_array);
+        let mut row = self.history.row_mut(self.head);
+        // Whatever was in this slot is now falling out of the window.
+        self.window_regret -= &row;
+        row.assign(&reward_array);
+        row -= expected;
+        row *= options.regret_weight;
+        self.window_regret += &row;
+
+        self.head = (self.head + 1) % self.window();
+        self.play_regrets()
+    }
+
+    // Set the strategy from the regret in the window.
+    fn play_regrets(&mut self) -> Result<(), LittleError> {
+        regret_minimizer::regret_match(
+            self.window_regret.as_slice().unwrap(),
+            self.p.as_slice_mut().unwrap(),
+        );
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    /// The regret summed over the current window.
+    #[must_use]
+    pub fn window_regret(&self) -> Vec<Scalar> {
+        self.window_regret.to_vec()
+    }
+}
+
+impl RegretMinimizer for WindowedRegretMatcher {
+    fn new(num_experts: usize) -> Result<Self, LittleError> {
+        Self::new_with_params(num_experts, DEFAULT_WINDOW)
+    }
+
+    fn num_experts(&self) -> usize {
+        self.p.len()
+    }
+
+    fn num_updates(&self) -> usize {
+        self.num_updates
+    }
+
+    fn hyperparameters(&self) -> Vec<(&'static str, Scalar)> {
+        vec![("window", self.window() as Scalar)]
+    }
+
+    fn next_action<R: Rng>(&self, rng: &mut R) -> usize {
+        regret_minimizer::sample(&self.dist, rng)
+    }
+
+    fn update_regret_with_options(
+        &mut self,
+        rewards: &[Scalar],
+        options: UpdateOptions,
+    ) -> Result<(), LittleError> {
+        regret_minimizer::check_rewards_len(rewards, self.p.len())?;
+        self.update_view(ArrayView1::from(rewards), options)
+    }
+
+    fn accumulate_average(
+        &mut self,
+        strategy: &[Scalar],
+        weight: Scalar,
+    ) -> Result<(), LittleError> {
+        self.averaging
+            .accumulate_strategy(self.num_updates, &mut self.sum_p, strategy, weight)
+    }
+
+    fn current_strategy(&self) -> &[Scalar] {
+        self.p.as_slice().unwrap()
+    }
+
+    fn best_weight(&self) -> Vec<Scalar> {
+        AveragingScheme::average(self.sum_p.values()).unwrap_or_else(|| self.p.to_vec())
+    }
+
+    fn cumulative_regrets(&self) -> Vec<Scalar> {
+        self.window_regret.to_vec()
+    }
+
+    fn with_averaging(mut self, averaging: AveragingScheme) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    fn averaging(&self) -> AveragingScheme {
+        self.averaging
+    }
+
+    fn reset(&mut self) -> Result<(), LittleError> {
+        self.sum_p.fill(0.0);
+        self.num_updates = 0;
+        self.soft_reset()
+    }
+
+    fn soft_reset(&mut self) -> Result<(), LittleError> {
+        self.history.fill(0.0);
+        self.head = 0;
+        self.window_regret.fill(0.0);
+        regret_minimizer::fill_uniform(self.p.as_slice_mut().unwrap());
+        self.dist.set_weights(self.p.as_slice().unwrap())?;
+        Ok(())
+    }
+
+    fn warm_start_from_strategy(strategy: &[Scalar]) -> Result<Self, LittleError> {
+        let strategy = regret_minimizer::normalize_strategy(strategy)?;
+        let mut matcher = Self::warm_start_from_regrets(&strategy)?;
+        matcher.sum_p = CompensatedArray::from(scalar::widen(&strategy));
+        matcher.num_updates = 1;
+        Ok(matcher)
+    }
+
+    /// The regrets take up one slot in the window, so they are
+    /// forgotten like any other update once the window moves on.
+    fn warm_start_from_regrets(regrets: &[Scalar]) -> Result<Self, LittleError> {
+        let mut matcher = Self::new(regrets.len())?;
+        let window = matcher.window();
+        matcher
+            .history
+            .row_mut(0)
+            .assign(&ArrayView1::from(regrets));
+        matcher.window_regret.assign(&ArrayView1::from(regrets));
+        matcher.head = 1 % window;
+        matcher.play_regrets()?;
+        Ok(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forgets_old_regret() {
+        let mut m = WindowedRegretMatcher::new_with_params(2, 5).unwrap();
+        for _i in 0..100 {
+            m.update_regret(&[1.0, 0.0]).unwrap();
+        }
+        assert_eq!(m.current_strategy(), &[1.0, 0.0]);
+        // An infinite memory matcher would take ~100 rounds to
+        // switch. The window only remembers 5.
+        for _i in 0..6 {
+            m.update_regret(&[0.0, 1.0]).unwrap();
+        }
+        assert_eq!(m.current_strategy(), &[0.0, 1.0]);
+    }
+}
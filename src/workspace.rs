@@ -0,0 +1,53 @@
+//! Scratch space for the methods that need temporary buffers. A
+//! tree solver touching thousands of information states can keep
+//! one `Workspace` per thread and hand it to every matcher, so the
+//! buffers grow once to the widest decision and are then reused.
+use std::vec::Vec;
+
+use crate::scalar::Scalar;
+
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    pub(crate) strategy: Vec<Scalar>,
+    pub(crate) rewards: Vec<Scalar>,
+}
+
+impl Workspace {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A workspace that won't need to grow for decisions with up
+    /// to `num_experts` actions.
+    #[must_use]
+    pub fn with_capacity(num_experts: usize) -> Self {
+        Self {
+            strategy: Vec::with_capacity(num_experts),
+            rewards: Vec::with_capacity(num_experts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regret_minimizer::RegretMinimizer;
+    use crate::vanilla_cfr::VanillaCfrRegretMatcher;
+
+    #[test]
+    fn test_shared_workspace() {
+        let mut workspace = Workspace::with_capacity(3);
+        let mut plain = VanillaCfrRegretMatcher::new(3).unwrap();
+        let mut reused = VanillaCfrRegretMatcher::new(3).unwrap();
+        let mask = [true, false, true];
+        for _i in 0..10 {
+            plain.update_regret_masked(&[1.0, 5.0, 0.0], &mask).unwrap();
+            reused
+                .update_regret_masked_with_workspace(&[1.0, 5.0, 0.0], &mask, &mut workspace)
+                .unwrap();
+        }
+        assert_eq!(plain.cumulative_regrets(), reused.cumulative_regrets());
+        assert_eq!(workspace.rewards.capacity(), 3);
+    }
+}